@@ -0,0 +1,128 @@
+//! Get the key pair that's used for signing chat messages.
+
+use azalea_crypto::{KeyError, RsaPrivateKey};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::{
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum FetchCertificatesError {
+    #[error("Http error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Invalid key: {0}")]
+    Key(#[from] KeyError),
+    #[error("Invalid public key signature: {0}")]
+    Signature(#[from] base64::DecodeError),
+    #[error("Invalid date: {0}")]
+    InvalidDate(String),
+}
+
+/// A player's profile key pair, signed by Mojang.
+#[derive(Clone)]
+pub struct Certificates {
+    pub private_key: RsaPrivateKey,
+    /// The X.509 encoded public key, which is what's sent to servers.
+    pub public_key_der: Vec<u8>,
+    /// Mojang's signature of the public key, which lets servers check that
+    /// the key belongs to this player.
+    pub signature_v2: Vec<u8>,
+    /// When the key pair expires, in milliseconds since the Unix epoch.
+    pub expires_at: u64,
+    /// When a new key pair should be fetched, in milliseconds since the Unix
+    /// epoch.
+    pub refresh_after: u64,
+}
+
+impl Certificates {
+    /// Whether it's time to fetch a new key pair.
+    pub fn should_refresh(&self) -> bool {
+        now_millis() >= self.refresh_after
+    }
+
+    pub fn is_expired(&self) -> bool {
+        now_millis() >= self.expires_at
+    }
+}
+
+impl fmt::Debug for Certificates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // don't accidentally print the private key in logs
+        f.debug_struct("Certificates")
+            .field("private_key", &"..")
+            .field("public_key_der", &self.public_key_der)
+            .field("signature_v2", &self.signature_v2)
+            .field("expires_at", &self.expires_at)
+            .field("refresh_after", &self.refresh_after)
+            .finish()
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time shouldn't be before epoch")
+        .as_millis() as u64
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificatesResponse {
+    pub key_pair: KeyPairResponse,
+    pub public_key_signature_v2: String,
+    /// Looks like `2023-03-16T03:42:27.497282Z`.
+    pub expires_at: String,
+    pub refreshed_after: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyPairResponse {
+    /// A PKCS#8 key in PEM, even though it's labelled `RSA PRIVATE KEY`.
+    pub private_key: String,
+    /// An X.509 key in PEM, even though it's labelled `RSA PUBLIC KEY`.
+    pub public_key: String,
+}
+
+impl TryFrom<CertificatesResponse> for Certificates {
+    type Error = FetchCertificatesError;
+
+    fn try_from(res: CertificatesResponse) -> Result<Self, Self::Error> {
+        let parse_date = |date: &str| {
+            DateTime::parse_from_rfc3339(date)
+                .map(|date| date.with_timezone(&Utc).timestamp_millis() as u64)
+                .map_err(|e| FetchCertificatesError::InvalidDate(format!("{date}: {e}")))
+        };
+        Ok(Certificates {
+            private_key: azalea_crypto::private_key_from_pem(&res.key_pair.private_key)?,
+            public_key_der: azalea_crypto::pem_to_der(&res.key_pair.public_key)?,
+            signature_v2: base64::engine::general_purpose::STANDARD
+                .decode(&res.public_key_signature_v2)?,
+            expires_at: parse_date(&res.expires_at)?,
+            refresh_after: parse_date(&res.refreshed_after)?,
+        })
+    }
+}
+
+/// Get a new key pair for signing chat messages from Mojang. The key pair is
+/// valid for a couple of days.
+pub async fn fetch_certificates(
+    minecraft_access_token: &str,
+) -> Result<Certificates, FetchCertificatesError> {
+    let client = reqwest::Client::new();
+
+    let res = client
+        .post("https://api.minecraftservices.com/player/certificates")
+        .header("Authorization", format!("Bearer {minecraft_access_token}"))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<CertificatesResponse>()
+        .await?;
+
+    res.try_into()
+}
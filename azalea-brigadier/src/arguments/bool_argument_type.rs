@@ -0,0 +1,40 @@
+use std::{any::Any, rc::Rc};
+
+use crate::{
+    context::CommandContext,
+    exceptions::CommandSyntaxException,
+    string_reader::StringReader,
+    suggestion::{Suggestions, SuggestionsBuilder},
+};
+
+use super::ArgumentType;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Boolean;
+
+impl ArgumentType for Boolean {
+    fn parse(&self, reader: &mut StringReader) -> Result<Rc<dyn Any>, CommandSyntaxException> {
+        Ok(Rc::new(reader.read_boolean()?))
+    }
+
+    fn list_suggestions(&self, mut builder: SuggestionsBuilder) -> Suggestions {
+        if "true".starts_with(builder.remaining_lowercase()) {
+            builder.suggest("true");
+        }
+        if "false".starts_with(builder.remaining_lowercase()) {
+            builder.suggest("false");
+        }
+        builder.build()
+    }
+}
+
+pub fn bool() -> impl ArgumentType {
+    Boolean
+}
+pub fn get_bool<S>(context: &CommandContext<S>, name: &str) -> Option<bool> {
+    context
+        .argument(name)
+        .unwrap()
+        .downcast_ref::<bool>()
+        .copied()
+}
@@ -8,8 +8,8 @@ use crate::{
 
 use super::ArgumentType;
 
-#[derive(Default)]
-struct Integer {
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Integer {
     pub minimum: Option<i32>,
     pub maximum: Option<i32>,
 }
@@ -1,4 +1,9 @@
 mod argument_type;
+pub mod bool_argument_type;
+pub mod double_argument_type;
+pub mod float_argument_type;
 pub mod integer_argument_type;
+pub mod long_argument_type;
+pub mod string_argument_type;
 
 pub use argument_type::ArgumentType;
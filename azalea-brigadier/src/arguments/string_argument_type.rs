@@ -0,0 +1,75 @@
+use std::{any::Any, rc::Rc};
+
+use crate::{
+    context::CommandContext, exceptions::CommandSyntaxException, string_reader::StringReader,
+};
+
+use super::ArgumentType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringArgument {
+    /// Match up until the next space.
+    SingleWord,
+    /// Same as single word unless the argument is wrapped in quotes, in which
+    /// case it can contain spaces.
+    QuotablePhrase,
+    /// Match the rest of the input.
+    GreedyPhrase,
+}
+
+impl ArgumentType for StringArgument {
+    fn parse(&self, reader: &mut StringReader) -> Result<Rc<dyn Any>, CommandSyntaxException> {
+        let result = match self {
+            StringArgument::SingleWord => reader.read_unquoted_string().to_string(),
+            StringArgument::QuotablePhrase => reader.read_string()?,
+            StringArgument::GreedyPhrase => {
+                let text = reader.remaining().to_string();
+                reader.cursor = reader.total_length();
+                text
+            }
+        };
+        Ok(Rc::new(result))
+    }
+}
+
+/// Match up until the next space.
+pub fn word() -> impl ArgumentType {
+    StringArgument::SingleWord
+}
+/// Match up until the next space, or the rest of the input if it's wrapped in
+/// quotes.
+pub fn string() -> impl ArgumentType {
+    StringArgument::QuotablePhrase
+}
+/// Match the rest of the input.
+pub fn greedy_string() -> impl ArgumentType {
+    StringArgument::GreedyPhrase
+}
+pub fn get_string<S>(context: &CommandContext<S>, name: &str) -> Option<String> {
+    context
+        .argument(name)
+        .unwrap()
+        .downcast_ref::<String>()
+        .cloned()
+}
+
+/// Wrap the input in quotes if it can't be read as a single word.
+pub fn escape_if_required(input: &str) -> String {
+    if input
+        .chars()
+        .all(StringReader::is_allowed_in_unquoted_string)
+    {
+        return input.to_string();
+    }
+
+    let mut result = String::with_capacity(input.len() + 2);
+    result.push('"');
+    for c in input.chars() {
+        if c == '\\' || c == '"' {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+    result.push('"');
+    result
+}
@@ -1,6 +1,9 @@
 use super::argument_builder::{ArgumentBuilder, ArgumentBuilderType};
 use crate::{
-    arguments::ArgumentType, exceptions::CommandSyntaxException, string_reader::StringReader,
+    arguments::ArgumentType,
+    exceptions::CommandSyntaxException,
+    string_reader::StringReader,
+    suggestion::{Suggestions, SuggestionsBuilder},
 };
 use std::{any::Any, fmt::Debug, rc::Rc};
 
@@ -22,6 +25,10 @@ impl Argument {
     pub fn parse(&self, reader: &mut StringReader) -> Result<Rc<dyn Any>, CommandSyntaxException> {
         self.parser.parse(reader)
     }
+
+    pub fn list_suggestions(&self, builder: SuggestionsBuilder) -> Suggestions {
+        self.parser.list_suggestions(builder)
+    }
 }
 
 impl From<Argument> for ArgumentBuilderType {
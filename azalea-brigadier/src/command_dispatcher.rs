@@ -4,9 +4,17 @@ use crate::{
     exceptions::{BuiltInExceptions, CommandSyntaxException},
     parse_results::ParseResults,
     string_reader::StringReader,
+    suggestion::{Suggestions, SuggestionsBuilder},
     tree::CommandNode,
 };
-use std::{cell::RefCell, cmp::Ordering, collections::HashMap, marker::PhantomData, mem, rc::Rc};
+use std::{
+    cell::RefCell,
+    cmp::{self, Ordering},
+    collections::HashMap,
+    marker::PhantomData,
+    mem,
+    rc::Rc,
+};
 
 #[derive(Default)]
 pub struct CommandDispatcher<S> {
@@ -285,6 +293,212 @@ impl<S> CommandDispatcher<S> {
         })
         // Ok(if forked { successful_forks } else { result })
     }
+
+    /// Get every possible way to run the commands under a node, like
+    /// `["tp <target>", "tp <x> <y> <z>"]`. If `restricted` is true, nodes that
+    /// the source can't use are skipped.
+    pub fn get_all_usage(
+        &self,
+        node: &CommandNode<S>,
+        source: Rc<S>,
+        restricted: bool,
+    ) -> Vec<String> {
+        let mut result = vec![];
+        self.get_all_usage_recursive(node, source, &mut result, "", restricted);
+        result
+    }
+
+    fn get_all_usage_recursive(
+        &self,
+        node: &CommandNode<S>,
+        source: Rc<S>,
+        result: &mut Vec<String>,
+        prefix: &str,
+        restricted: bool,
+    ) {
+        if restricted && !node.can_use(source.clone()) {
+            return;
+        }
+        if node.command.is_some() {
+            result.push(prefix.to_owned());
+        }
+        if let Some(redirect) = &node.redirect {
+            let redirect = if Rc::ptr_eq(redirect, &self.root) {
+                "...".to_string()
+            } else {
+                format!("-> {}", redirect.borrow().usage_text())
+            };
+            if prefix.is_empty() {
+                result.push(format!("{} {redirect}", node.usage_text()));
+            } else {
+                result.push(format!("{prefix} {redirect}"));
+            }
+        } else {
+            for child in node.children.values() {
+                let child = child.borrow();
+                let child_prefix = if prefix.is_empty() {
+                    child.usage_text()
+                } else {
+                    format!("{prefix} {}", child.usage_text())
+                };
+                self.get_all_usage_recursive(
+                    &child,
+                    source.clone(),
+                    result,
+                    &child_prefix,
+                    restricted,
+                );
+            }
+        }
+    }
+
+    /// Get a short usage for each child of a node that the source can use,
+    /// like `tp (<target>|<x>)`. Optional arguments are in square brackets,
+    /// and a choice of arguments is in parentheses.
+    pub fn get_smart_usage(
+        &self,
+        node: &CommandNode<S>,
+        source: Rc<S>,
+    ) -> Vec<(Rc<RefCell<CommandNode<S>>>, String)> {
+        let mut result = Vec::new();
+
+        let optional = node.command.is_some();
+        for child in node.children.values() {
+            let usage =
+                self.get_smart_usage_recursive(&child.borrow(), source.clone(), optional, false);
+            if let Some(usage) = usage {
+                result.push((child.clone(), usage));
+            }
+        }
+
+        result
+    }
+
+    fn get_smart_usage_recursive(
+        &self,
+        node: &CommandNode<S>,
+        source: Rc<S>,
+        optional: bool,
+        deep: bool,
+    ) -> Option<String> {
+        if !node.can_use(source.clone()) {
+            return None;
+        }
+
+        let this = if optional {
+            format!("[{}]", node.usage_text())
+        } else {
+            node.usage_text()
+        };
+        let child_optional = node.command.is_some();
+        let (open, close) = if child_optional {
+            ("[", "]")
+        } else {
+            ("(", ")")
+        };
+
+        if deep {
+            return Some(this);
+        }
+
+        if let Some(redirect) = &node.redirect {
+            let redirect = if Rc::ptr_eq(redirect, &self.root) {
+                "...".to_string()
+            } else {
+                format!("-> {}", redirect.borrow().usage_text())
+            };
+            return Some(format!("{this} {redirect}"));
+        }
+
+        let children = node
+            .children
+            .values()
+            .filter(|child| child.borrow().can_use(source.clone()))
+            .collect::<Vec<_>>();
+        match children.len() {
+            0 => {}
+            1 => {
+                let usage = self.get_smart_usage_recursive(
+                    &children[0].borrow(),
+                    source,
+                    child_optional,
+                    child_optional,
+                );
+                if let Some(usage) = usage {
+                    return Some(format!("{this} {usage}"));
+                }
+            }
+            _ => {
+                let mut child_usage = Vec::new();
+                for child in &children {
+                    let usage = self.get_smart_usage_recursive(
+                        &child.borrow(),
+                        source.clone(),
+                        child_optional,
+                        true,
+                    );
+                    if let Some(usage) = usage {
+                        if !child_usage.contains(&usage) {
+                            child_usage.push(usage);
+                        }
+                    }
+                }
+                if child_usage.len() == 1 {
+                    let usage = &child_usage[0];
+                    let usage = if child_optional {
+                        format!("[{usage}]")
+                    } else {
+                        usage.clone()
+                    };
+                    return Some(format!("{this} {usage}"));
+                } else if child_usage.len() > 1 {
+                    let usages = children
+                        .iter()
+                        .map(|child| child.borrow().usage_text())
+                        .collect::<Vec<_>>()
+                        .join("|");
+                    return Some(format!("{this} {open}{usages}{close}"));
+                }
+            }
+        }
+
+        Some(this)
+    }
+
+    /// Get the suggestions for what could be typed at the end of the parsed
+    /// input.
+    pub fn get_completion_suggestions(&self, parse: ParseResults<S>) -> Suggestions {
+        let cursor = parse.reader.total_length();
+        self.get_completion_suggestions_with_cursor(parse, cursor)
+    }
+
+    pub fn get_completion_suggestions_with_cursor(
+        &self,
+        parse: ParseResults<S>,
+        cursor: usize,
+    ) -> Suggestions {
+        let node_before_cursor = parse.context.find_suggestion_context(cursor);
+        let parent = node_before_cursor.parent.borrow();
+        let start = cmp::min(node_before_cursor.start_pos, cursor);
+
+        let full_input = parse.reader.string();
+        let truncated_input = &full_input[..cursor];
+        let truncated_input_lowercase = truncated_input.to_lowercase();
+
+        let mut all_suggestions = Vec::new();
+        for node in parent.children.values() {
+            let suggestions =
+                node.borrow()
+                    .list_suggestions(SuggestionsBuilder::new_with_lowercase(
+                        truncated_input,
+                        &truncated_input_lowercase,
+                        start,
+                    ));
+            all_suggestions.push(suggestions);
+        }
+
+        Suggestions::merge(full_input, &all_suggestions)
+    }
 }
 
 impl<S> Clone for CommandDispatcher<S> {
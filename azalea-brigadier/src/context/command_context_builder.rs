@@ -1,6 +1,6 @@
 use super::{
     command_context::CommandContext, parsed_command_node::ParsedCommandNode,
-    string_range::StringRange, ParsedArgument,
+    string_range::StringRange, ParsedArgument, SuggestionContext,
 };
 use crate::{
     command_dispatcher::CommandDispatcher,
@@ -97,6 +97,45 @@ impl<S> CommandContextBuilder<S> {
             input: input.to_string(),
         }
     }
+
+    /// Find the node that comes before the cursor, so its children can be
+    /// suggested.
+    pub fn find_suggestion_context(&self, cursor: usize) -> SuggestionContext<S> {
+        if self.range.start() > cursor {
+            panic!("Can't find node before cursor");
+        }
+
+        if self.range.end() < cursor {
+            if let Some(child) = &self.child {
+                child.find_suggestion_context(cursor)
+            } else if let Some(last) = self.nodes.last() {
+                SuggestionContext {
+                    parent: last.node.clone(),
+                    start_pos: last.range.end() + 1,
+                }
+            } else {
+                SuggestionContext {
+                    parent: self.root.clone(),
+                    start_pos: self.range.start(),
+                }
+            }
+        } else {
+            let mut prev = self.root.clone();
+            for node in &self.nodes {
+                if node.range.start() <= cursor && cursor <= node.range.end() {
+                    return SuggestionContext {
+                        parent: prev,
+                        start_pos: node.range.start(),
+                    };
+                }
+                prev = node.node.clone();
+            }
+            SuggestionContext {
+                parent: prev,
+                start_pos: self.range.start(),
+            }
+        }
+    }
 }
 
 impl<S> Debug for CommandContextBuilder<S> {
@@ -0,0 +1,9 @@
+use crate::tree::CommandNode;
+use std::{cell::RefCell, rc::Rc};
+
+/// The node whose children should be suggested at a cursor position, and
+/// where in the input the suggestions start.
+pub struct SuggestionContext<S> {
+    pub parent: Rc<RefCell<CommandNode<S>>>,
+    pub start_pos: usize,
+}
@@ -57,15 +57,20 @@ impl CommandSyntaxException {
             if let Some(cursor) = self.cursor {
                 let mut builder = String::new();
                 let cursor = cmp::min(input.len(), cursor);
+                let before_cursor = &input[..cursor];
+                // the cursor is a byte index, but the context is counted in
+                // characters
+                let context_start = before_cursor
+                    .char_indices()
+                    .rev()
+                    .nth(CONTEXT_AMOUNT - 1)
+                    .map_or(0, |(i, _)| i);
 
-                if cursor > CONTEXT_AMOUNT {
+                if context_start > 0 {
                     builder.push_str("...");
                 }
 
-                builder.push_str(
-                    &input
-                        [(cmp::max(0, cursor as isize - CONTEXT_AMOUNT as isize) as usize)..cursor],
-                );
+                builder.push_str(&before_cursor[context_start..]);
                 builder.push_str("<--[HERE]");
 
                 return Some(builder);
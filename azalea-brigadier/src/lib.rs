@@ -9,4 +9,5 @@ pub mod modifier;
 pub mod parse_results;
 pub mod string_reader;
 pub mod suggestion;
+pub mod sync_command_dispatcher;
 pub mod tree;
@@ -4,6 +4,7 @@ use std::str::FromStr;
 #[derive(Clone)]
 pub struct StringReader {
     string: String,
+    /// The byte index of the next character to read.
     pub cursor: usize,
 }
 
@@ -55,11 +56,12 @@ impl StringReader {
     }
 
     pub fn peek(&self) -> char {
-        self.string.chars().nth(self.cursor).unwrap()
+        self.remaining().chars().next().unwrap()
     }
 
+    /// Get the character `offset` characters after the cursor.
     pub fn peek_offset(&self, offset: usize) -> char {
-        self.string.chars().nth(self.cursor + offset).unwrap()
+        self.remaining().chars().nth(offset).unwrap()
     }
 
     pub fn cursor(&self) -> usize {
@@ -68,12 +70,12 @@ impl StringReader {
 
     pub fn read(&mut self) -> char {
         let c = self.peek();
-        self.cursor += 1;
+        self.cursor += c.len_utf8();
         c
     }
 
     pub fn skip(&mut self) {
-        self.cursor += 1;
+        self.cursor += self.peek().len_utf8();
     }
 
     pub fn is_allowed_number(c: char) -> bool {
@@ -217,7 +219,7 @@ impl StringReader {
                     result.push(c);
                     escaped = false;
                 } else {
-                    self.cursor -= 1;
+                    self.cursor -= c.len_utf8();
                     return Err(BuiltInExceptions::ReaderInvalidEscape { character: c }
                         .create_with_context(self));
                 }
@@ -0,0 +1,98 @@
+use super::{Suggestion, Suggestions};
+use crate::context::StringRange;
+use std::collections::HashSet;
+
+/// Collects the suggestions for the part of the input after `start`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuggestionsBuilder {
+    input: String,
+    input_lowercase: String,
+    start: usize,
+    remaining: String,
+    remaining_lowercase: String,
+    result: Vec<Suggestion>,
+}
+
+impl SuggestionsBuilder {
+    pub fn new(input: &str, start: usize) -> Self {
+        Self::new_with_lowercase(input, &input.to_lowercase(), start)
+    }
+
+    pub fn new_with_lowercase(input: &str, input_lowercase: &str, start: usize) -> Self {
+        let remaining = input[start..].to_string();
+        Self {
+            input: input.to_string(),
+            input_lowercase: input_lowercase.to_string(),
+            start,
+            remaining_lowercase: remaining.to_lowercase(),
+            remaining,
+            result: Vec::new(),
+        }
+    }
+
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn remaining(&self) -> &str {
+        &self.remaining
+    }
+
+    pub fn remaining_lowercase(&self) -> &str {
+        &self.remaining_lowercase
+    }
+
+    pub fn build(&self) -> Suggestions {
+        Suggestions::create(
+            &self.input,
+            &self.result.iter().cloned().collect::<HashSet<_>>(),
+        )
+    }
+
+    /// Suggest replacing the remaining input with `text`. Nothing is added if
+    /// the text is the same as what's already there.
+    pub fn suggest(&mut self, text: &str) -> &mut Self {
+        if text == self.remaining {
+            return self;
+        }
+        self.result.push(Suggestion {
+            text: text.to_string(),
+            range: StringRange::between(self.start, self.input.len()),
+            tooltip: None,
+        });
+        self
+    }
+
+    pub fn suggest_with_tooltip(&mut self, text: &str, tooltip: String) -> &mut Self {
+        if text == self.remaining {
+            return self;
+        }
+        self.result.push(Suggestion {
+            text: text.to_string(),
+            range: StringRange::between(self.start, self.input.len()),
+            tooltip: Some(tooltip),
+        });
+        self
+    }
+
+    /// Add the suggestions from another builder to this one.
+    pub fn add(&mut self, other: &SuggestionsBuilder) -> &mut Self {
+        self.result.extend(other.result.iter().cloned());
+        self
+    }
+
+    /// Make a new empty builder for the same input, starting at a different
+    /// position.
+    pub fn create_offset(&self, start: usize) -> SuggestionsBuilder {
+        SuggestionsBuilder::new_with_lowercase(&self.input, &self.input_lowercase, start)
+    }
+
+    /// Make a new empty builder with the same input and start.
+    pub fn restart(&self) -> SuggestionsBuilder {
+        self.create_offset(self.start)
+    }
+}
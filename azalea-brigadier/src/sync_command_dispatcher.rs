@@ -0,0 +1,225 @@
+use crate::{
+    command_dispatcher::CommandDispatcher, exceptions::CommandSyntaxException,
+    string_reader::StringReader, suggestion::Suggestions, tree::CommandNode,
+};
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+    sync::{Arc, Weak},
+};
+
+type Registration<S> = Arc<dyn Fn(&mut CommandDispatcher<S>) + Send + Sync>;
+
+thread_local! {
+    /// The dispatchers that were built on this thread, by the address of the
+    /// [`SyncCommandDispatcher::id`] they were built for.
+    static DISPATCHERS: RefCell<HashMap<usize, CachedDispatcher>> = RefCell::new(HashMap::new());
+}
+
+struct CachedDispatcher {
+    /// This stops being upgradable when the [`SyncCommandDispatcher`] is
+    /// dropped or has more commands registered, which means the dispatcher
+    /// can't be used anymore.
+    owner: Weak<()>,
+    /// A [`BuiltDispatcher`].
+    dispatcher: Rc<dyn Any>,
+}
+
+/// A dispatcher that was built from a [`SyncCommandDispatcher`]'s
+/// registrations.
+struct BuiltDispatcher<S>(CommandDispatcher<S>);
+
+impl<S> Drop for BuiltDispatcher<S> {
+    fn drop(&mut self) {
+        // redirects often point back to the root, which would make the nodes
+        // keep each other alive forever
+        let mut nodes = vec![];
+        collect_nodes(&self.0.root, &mut HashSet::new(), &mut nodes);
+        for node in nodes {
+            node.borrow_mut().redirect = None;
+        }
+    }
+}
+
+/// A [`CommandDispatcher`] that can be shared between threads.
+///
+/// A dispatcher's nodes are reference counted with [`Rc`], so they can't be
+/// sent to other threads. Instead, this keeps the functions that register the
+/// commands, and each thread builds its own dispatcher with them the first
+/// time it's used there. The dispatcher is kept until this is dropped or
+/// more commands are registered.
+///
+/// ```
+/// use azalea_brigadier::{
+///     builder::literal_argument_builder::literal,
+///     sync_command_dispatcher::SyncCommandDispatcher,
+/// };
+/// use std::{rc::Rc, sync::Arc, thread};
+///
+/// let mut commands = SyncCommandDispatcher::<()>::new();
+/// commands.register(|dispatcher| {
+///     dispatcher.register(literal("ping").executes(|_| 1));
+/// });
+/// let commands = Arc::new(commands);
+///
+/// thread::spawn(move || {
+///     assert_eq!(commands.execute("ping".into(), Rc::new(())).unwrap(), 1);
+/// })
+/// .join()
+/// .unwrap();
+/// ```
+pub struct SyncCommandDispatcher<S> {
+    registrations: Vec<Registration<S>>,
+    /// Identifies the dispatchers that were built with the current
+    /// registrations. This is replaced whenever a command is registered.
+    id: Arc<()>,
+}
+
+impl<S: 'static> SyncCommandDispatcher<S> {
+    pub fn new() -> Self {
+        Self {
+            registrations: Vec::new(),
+            id: Arc::new(()),
+        }
+    }
+
+    /// Add a function that registers commands. It's called every time a
+    /// dispatcher is built, in the order the functions were added.
+    pub fn register(
+        &mut self,
+        registration: impl Fn(&mut CommandDispatcher<S>) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.registrations.push(Arc::new(registration));
+        // the dispatchers that were already built don't have this command
+        self.id = Arc::new(());
+        self
+    }
+
+    /// Run the function with this thread's [`CommandDispatcher`], building it
+    /// with the registered commands if this is the first time it's used on
+    /// this thread.
+    pub fn with_dispatcher<R>(&self, f: impl FnOnce(&CommandDispatcher<S>) -> R) -> R {
+        let dispatcher = self.dispatcher();
+        f(&dispatcher.0)
+    }
+
+    fn dispatcher(&self) -> Rc<BuiltDispatcher<S>> {
+        let key = Arc::as_ptr(&self.id) as usize;
+        let cached = DISPATCHERS.with(|dispatchers| {
+            let mut dispatchers = dispatchers.borrow_mut();
+            // forget the dispatchers that can't be used anymore
+            dispatchers.retain(|_, cached| cached.owner.strong_count() > 0);
+            dispatchers
+                .get(&key)
+                .map(|cached| cached.dispatcher.clone())
+        });
+        if let Some(cached) = cached {
+            return cached
+                .downcast()
+                .expect("Dispatchers are only cached for the SyncCommandDispatcher they're from");
+        }
+
+        let mut dispatcher = CommandDispatcher::new();
+        for registration in &self.registrations {
+            registration(&mut dispatcher);
+        }
+        let dispatcher = Rc::new(BuiltDispatcher(dispatcher));
+        DISPATCHERS.with(|dispatchers| {
+            dispatchers.borrow_mut().insert(
+                key,
+                CachedDispatcher {
+                    owner: Arc::downgrade(&self.id),
+                    dispatcher: dispatcher.clone(),
+                },
+            )
+        });
+        dispatcher
+    }
+
+    pub fn execute(
+        &self,
+        input: StringReader,
+        source: Rc<S>,
+    ) -> Result<i32, CommandSyntaxException> {
+        self.with_dispatcher(|dispatcher| dispatcher.execute(input, source))
+    }
+
+    pub fn get_completion_suggestions(&self, input: StringReader, source: Rc<S>) -> Suggestions {
+        self.with_dispatcher(|dispatcher| {
+            dispatcher.get_completion_suggestions(dispatcher.parse(input, source))
+        })
+    }
+
+    /// Get every possible way to run the commands under the node at `path`
+    /// (or the root if it's empty). See [`CommandDispatcher::get_all_usage`].
+    ///
+    /// If there's no node at the path, this is empty.
+    pub fn get_all_usage(&self, path: &[&str], source: Rc<S>, restricted: bool) -> Vec<String> {
+        self.with_dispatcher(|dispatcher| {
+            let Some(node) = dispatcher.find_node(path) else {
+                return vec![];
+            };
+            let node = node.borrow();
+            dispatcher.get_all_usage(&node, source, restricted)
+        })
+    }
+
+    /// Get the name and a short usage of each child of the node at `path` (or
+    /// the root if it's empty) that the source can use. See
+    /// [`CommandDispatcher::get_smart_usage`].
+    ///
+    /// If there's no node at the path, this is empty.
+    pub fn get_smart_usage(&self, path: &[&str], source: Rc<S>) -> Vec<(String, String)> {
+        self.with_dispatcher(|dispatcher| {
+            let Some(node) = dispatcher.find_node(path) else {
+                return vec![];
+            };
+            let node = node.borrow();
+            dispatcher
+                .get_smart_usage(&node, source)
+                .into_iter()
+                .map(|(child, usage)| (child.borrow().name().to_owned(), usage))
+                .collect()
+        })
+    }
+}
+
+/// Add every node that can be reached from `node` to `nodes`, including
+/// through redirects. `seen` has the pointers of the nodes that were already
+/// added.
+fn collect_nodes<S>(
+    node: &Rc<RefCell<CommandNode<S>>>,
+    seen: &mut HashSet<*const RefCell<CommandNode<S>>>,
+    nodes: &mut Vec<Rc<RefCell<CommandNode<S>>>>,
+) {
+    if !seen.insert(Rc::as_ptr(node)) {
+        return;
+    }
+    nodes.push(node.clone());
+
+    let node = node.borrow();
+    for child in node.children.values() {
+        collect_nodes(child, seen, nodes);
+    }
+    if let Some(redirect) = &node.redirect {
+        collect_nodes(redirect, seen, nodes);
+    }
+}
+
+impl<S: 'static> Default for SyncCommandDispatcher<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Clone for SyncCommandDispatcher<S> {
+    fn clone(&self) -> Self {
+        Self {
+            registrations: self.registrations.clone(),
+            // it has the same commands, so it can use the same dispatchers
+            id: self.id.clone(),
+        }
+    }
+}
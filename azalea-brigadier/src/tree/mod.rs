@@ -7,8 +7,10 @@ use crate::{
     exceptions::{BuiltInExceptions, CommandSyntaxException},
     modifier::RedirectModifier,
     string_reader::StringReader,
+    suggestion::{Suggestions, SuggestionsBuilder},
 };
-use std::{cell::RefCell, collections::HashMap, fmt::Debug, hash::Hash, ptr, rc::Rc};
+use indexmap::IndexMap;
+use std::{cell::RefCell, fmt::Debug, hash::Hash, ptr, rc::Rc};
 
 pub type Command<S> = Option<Rc<dyn Fn(&CommandContext<S>) -> i32>>;
 
@@ -17,9 +19,12 @@ pub type Command<S> = Option<Rc<dyn Fn(&CommandContext<S>) -> i32>>;
 pub struct CommandNode<S> {
     pub value: ArgumentBuilderType,
 
-    pub children: HashMap<String, Rc<RefCell<CommandNode<S>>>>,
-    pub literals: HashMap<String, Rc<RefCell<CommandNode<S>>>>,
-    pub arguments: HashMap<String, Rc<RefCell<CommandNode<S>>>>,
+    // these keep the order the children were added in, like vanilla, since
+    // that decides which argument is tried first and the order of usages and
+    // suggestions
+    pub children: IndexMap<String, Rc<RefCell<CommandNode<S>>>>,
+    pub literals: IndexMap<String, Rc<RefCell<CommandNode<S>>>>,
+    pub arguments: IndexMap<String, Rc<RefCell<CommandNode<S>>>>,
 
     pub command: Command<S>,
     pub requirement: Rc<dyn Fn(Rc<S>) -> bool>,
@@ -72,12 +77,7 @@ impl<S> CommandNode<S> {
             while input.can_read() && input.peek() != ' ' {
                 input.skip();
             }
-            let text: String = input
-                .string()
-                .chars()
-                .skip(cursor)
-                .take(input.cursor() - cursor)
-                .collect();
+            let text = input.string()[cursor..input.cursor()].to_string();
             input.cursor = cursor;
             let literal = literals.get(&text);
             if let Some(literal) = literal {
@@ -123,6 +123,15 @@ impl<S> CommandNode<S> {
         }
     }
 
+    /// The text that's shown for this node in usages, like `<name>` for
+    /// arguments.
+    pub fn usage_text(&self) -> String {
+        match &self.value {
+            ArgumentBuilderType::Argument(argument) => format!("<{}>", argument.name),
+            ArgumentBuilderType::Literal(literal) => literal.value.clone(),
+        }
+    }
+
     pub fn child(&self, name: &str) -> Option<Rc<RefCell<CommandNode<S>>>> {
         self.children.get(name).cloned()
     }
@@ -166,6 +175,23 @@ impl<S> CommandNode<S> {
         }
     }
 
+    pub fn list_suggestions(&self, mut builder: SuggestionsBuilder) -> Suggestions {
+        match &self.value {
+            ArgumentBuilderType::Argument(argument) => argument.list_suggestions(builder),
+            ArgumentBuilderType::Literal(literal) => {
+                if literal
+                    .value
+                    .to_lowercase()
+                    .starts_with(builder.remaining_lowercase())
+                {
+                    builder.suggest(&literal.value).build()
+                } else {
+                    Suggestions::default()
+                }
+            }
+        }
+    }
+
     fn parse(&self, reader: &mut StringReader) -> Option<usize> {
         match self.value {
             ArgumentBuilderType::Argument(_) => {
@@ -214,9 +240,9 @@ impl<S> Default for CommandNode<S> {
         Self {
             value: ArgumentBuilderType::Literal(Literal::default()),
 
-            children: HashMap::new(),
-            literals: HashMap::new(),
-            arguments: HashMap::new(),
+            children: IndexMap::new(),
+            literals: IndexMap::new(),
+            arguments: IndexMap::new(),
 
             command: None,
             requirement: Rc::new(|_| true),
@@ -0,0 +1,47 @@
+use azalea_brigadier::{
+    arguments::{bool_argument_type::bool, ArgumentType},
+    string_reader::StringReader,
+    suggestion::{Suggestion, SuggestionsBuilder},
+};
+
+#[test]
+fn parse() {
+    let mut reader = StringReader::from("true");
+    let result = bool().parse(&mut reader).unwrap();
+    assert_eq!(result.downcast_ref::<bool>(), Some(&true));
+    assert!(!reader.can_read());
+
+    let mut reader = StringReader::from("false");
+    let result = bool().parse(&mut reader).unwrap();
+    assert_eq!(result.downcast_ref::<bool>(), Some(&false));
+}
+
+#[test]
+fn parse_invalid() {
+    let mut reader = StringReader::from("yes");
+    assert!(bool().parse(&mut reader).is_err());
+    assert_eq!(reader.cursor(), 0);
+}
+
+#[test]
+fn list_suggestions() {
+    let suggestions = bool().list_suggestions(SuggestionsBuilder::new("", 0));
+    assert_eq!(
+        suggestions
+            .suggestions
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect::<Vec<_>>(),
+        vec!["false", "true"]
+    );
+
+    let suggestions = bool().list_suggestions(SuggestionsBuilder::new("T", 0));
+    assert_eq!(
+        suggestions.suggestions,
+        vec![Suggestion {
+            text: "true".to_string(),
+            range: suggestions.range.clone(),
+            tooltip: None,
+        }]
+    );
+}
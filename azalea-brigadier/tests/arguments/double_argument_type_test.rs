@@ -0,0 +1,49 @@
+use azalea_brigadier::{
+    arguments::{
+        double_argument_type::{double, Double},
+        ArgumentType,
+    },
+    exceptions::BuiltInExceptions,
+    string_reader::StringReader,
+};
+
+#[test]
+fn parse() {
+    let mut reader = StringReader::from("15.5 hello");
+    let result = double().parse(&mut reader).unwrap();
+    assert_eq!(result.downcast_ref::<f64>(), Some(&15.5));
+    assert_eq!(reader.remaining(), " hello");
+}
+
+#[test]
+fn parse_too_small() {
+    let mut reader = StringReader::from("-5");
+    let result = Double {
+        minimum: Some(0.),
+        maximum: Some(100.),
+    }
+    .parse(&mut reader);
+    assert_eq!(
+        result.err().unwrap().type_,
+        BuiltInExceptions::DoubleTooSmall {
+            found: -5.,
+            min: 0.,
+        }
+    );
+    assert_eq!(reader.cursor(), 0);
+}
+
+#[test]
+fn parse_too_big() {
+    let mut reader = StringReader::from("5");
+    let result = Double {
+        minimum: Some(-100.),
+        maximum: Some(0.),
+    }
+    .parse(&mut reader);
+    assert_eq!(
+        result.err().unwrap().type_,
+        BuiltInExceptions::DoubleTooBig { found: 5., max: 0. }
+    );
+    assert_eq!(reader.cursor(), 0);
+}
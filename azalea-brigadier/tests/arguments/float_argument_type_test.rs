@@ -0,0 +1,49 @@
+use azalea_brigadier::{
+    arguments::{
+        float_argument_type::{float, Float},
+        ArgumentType,
+    },
+    exceptions::BuiltInExceptions,
+    string_reader::StringReader,
+};
+
+#[test]
+fn parse() {
+    let mut reader = StringReader::from("15.5 hello");
+    let result = float().parse(&mut reader).unwrap();
+    assert_eq!(result.downcast_ref::<f32>(), Some(&15.5));
+    assert_eq!(reader.remaining(), " hello");
+}
+
+#[test]
+fn parse_too_small() {
+    let mut reader = StringReader::from("-5");
+    let result = Float {
+        minimum: Some(0.),
+        maximum: Some(100.),
+    }
+    .parse(&mut reader);
+    assert_eq!(
+        result.err().unwrap().type_,
+        BuiltInExceptions::FloatTooSmall {
+            found: -5.,
+            min: 0.,
+        }
+    );
+    assert_eq!(reader.cursor(), 0);
+}
+
+#[test]
+fn parse_too_big() {
+    let mut reader = StringReader::from("5");
+    let result = Float {
+        minimum: Some(-100.),
+        maximum: Some(0.),
+    }
+    .parse(&mut reader);
+    assert_eq!(
+        result.err().unwrap().type_,
+        BuiltInExceptions::FloatTooBig { found: 5., max: 0. }
+    );
+    assert_eq!(reader.cursor(), 0);
+}
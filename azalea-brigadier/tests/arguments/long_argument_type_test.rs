@@ -0,0 +1,46 @@
+use azalea_brigadier::{
+    arguments::{
+        long_argument_type::{long, Long},
+        ArgumentType,
+    },
+    exceptions::BuiltInExceptions,
+    string_reader::StringReader,
+};
+
+#[test]
+fn parse() {
+    let mut reader = StringReader::from("15 hello");
+    let result = long().parse(&mut reader).unwrap();
+    assert_eq!(result.downcast_ref::<i64>(), Some(&15));
+    assert_eq!(reader.remaining(), " hello");
+}
+
+#[test]
+fn parse_too_small() {
+    let mut reader = StringReader::from("-5");
+    let result = Long {
+        minimum: Some(0),
+        maximum: Some(100),
+    }
+    .parse(&mut reader);
+    assert_eq!(
+        result.err().unwrap().type_,
+        BuiltInExceptions::LongTooSmall { found: -5, min: 0 }
+    );
+    assert_eq!(reader.cursor(), 0);
+}
+
+#[test]
+fn parse_too_big() {
+    let mut reader = StringReader::from("5");
+    let result = Long {
+        minimum: Some(-100),
+        maximum: Some(0),
+    }
+    .parse(&mut reader);
+    assert_eq!(
+        result.err().unwrap().type_,
+        BuiltInExceptions::LongTooBig { found: 5, max: 0 }
+    );
+    assert_eq!(reader.cursor(), 0);
+}
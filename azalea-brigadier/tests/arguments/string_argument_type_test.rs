@@ -0,0 +1,67 @@
+use azalea_brigadier::{
+    arguments::{
+        string_argument_type::{escape_if_required, greedy_string, string, word},
+        ArgumentType,
+    },
+    string_reader::StringReader,
+};
+
+#[test]
+fn test_parse_word() {
+    let mut reader = StringReader::from("hello world");
+    let result = word().parse(&mut reader).unwrap();
+    assert_eq!(result.downcast_ref::<String>().unwrap(), "hello");
+    assert_eq!(reader.remaining(), " world");
+}
+
+#[test]
+fn test_parse_string() {
+    let mut reader = StringReader::from("\"hello world\" foo");
+    let result = string().parse(&mut reader).unwrap();
+    assert_eq!(result.downcast_ref::<String>().unwrap(), "hello world");
+    assert_eq!(reader.remaining(), " foo");
+
+    let mut reader = StringReader::from("hello world");
+    let result = string().parse(&mut reader).unwrap();
+    assert_eq!(result.downcast_ref::<String>().unwrap(), "hello");
+}
+
+#[test]
+fn test_parse_greedy_string() {
+    let mut reader = StringReader::from("Hello world! This is a test.");
+    let result = greedy_string().parse(&mut reader).unwrap();
+    assert_eq!(
+        result.downcast_ref::<String>().unwrap(),
+        "Hello world! This is a test."
+    );
+    assert!(!reader.can_read());
+}
+
+#[test]
+fn test_escape_if_required_not_required() {
+    assert_eq!(escape_if_required("hello"), "hello");
+    assert_eq!(escape_if_required(""), "");
+}
+
+#[test]
+fn test_escape_if_required_multiple_words() {
+    assert_eq!(escape_if_required("hello world"), "\"hello world\"");
+}
+
+#[test]
+fn test_escape_if_required_quote() {
+    assert_eq!(
+        escape_if_required("hello \"world\"!"),
+        "\"hello \\\"world\\\"!\""
+    );
+}
+
+#[test]
+fn test_escape_if_required_escapes() {
+    assert_eq!(escape_if_required("\\"), "\"\\\\\"");
+}
+
+#[test]
+fn test_escape_if_required_single_quote() {
+    assert_eq!(escape_if_required("\""), "\"\\\"\"");
+}
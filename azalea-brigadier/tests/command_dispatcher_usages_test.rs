@@ -1 +1,185 @@
+use std::{cell::RefCell, rc::Rc};
 
+use azalea_brigadier::{
+    builder::literal_argument_builder::literal, command_dispatcher::CommandDispatcher,
+    string_reader::StringReader, tree::CommandNode,
+};
+
+#[derive(Debug, PartialEq)]
+struct CommandSource {}
+
+fn input_with_offset(input: &str, offset: usize) -> StringReader {
+    let mut result: StringReader = input.into();
+    result.cursor = offset;
+    result
+}
+
+fn setup() -> CommandDispatcher<CommandSource> {
+    let command = |_: &_| 0;
+
+    let mut subject = CommandDispatcher::new();
+    subject.register(
+        literal("a")
+            .then(
+                literal("1")
+                    .then(literal("i").executes(command))
+                    .then(literal("ii").executes(command)),
+            )
+            .then(
+                literal("2")
+                    .then(literal("i").executes(command))
+                    .then(literal("ii").executes(command)),
+            ),
+    );
+    subject.register(literal("b").then(literal("1").executes(command)));
+    subject.register(literal("c").executes(command));
+    subject.register(literal("d").requires(|_| false).executes(command));
+    subject.register(
+        literal("e").executes(command).then(
+            literal("1")
+                .executes(command)
+                .then(literal("i").executes(command))
+                .then(literal("ii").executes(command)),
+        ),
+    );
+    subject.register(
+        literal("f")
+            .then(
+                literal("1")
+                    .then(literal("i").executes(command))
+                    .then(literal("ii").executes(command).requires(|_| false)),
+            )
+            .then(
+                literal("2")
+                    .then(literal("i").executes(command).requires(|_| false))
+                    .then(literal("ii").executes(command)),
+            ),
+    );
+    subject.register(
+        literal("g")
+            .executes(command)
+            .then(literal("1").then(literal("i").executes(command))),
+    );
+    let h = subject.register(
+        literal("h")
+            .executes(command)
+            .then(literal("1").then(literal("i").executes(command)))
+            .then(literal("2").then(literal("i").then(literal("ii").executes(command))))
+            .then(literal("3").executes(command)),
+    );
+    subject.register(
+        literal("i")
+            .executes(command)
+            .then(literal("1").executes(command))
+            .then(literal("2").executes(command)),
+    );
+    subject.register(literal("j").redirect(subject.root.clone()));
+    subject.register(literal("k").redirect(h));
+    subject
+}
+
+fn get(
+    subject: &CommandDispatcher<CommandSource>,
+    command: &str,
+) -> Rc<RefCell<CommandNode<CommandSource>>> {
+    let path = command.split(' ').collect::<Vec<_>>();
+    subject.find_node(&path).unwrap()
+}
+
+fn smart_usage(
+    subject: &CommandDispatcher<CommandSource>,
+    node: &CommandNode<CommandSource>,
+) -> Vec<(Rc<RefCell<CommandNode<CommandSource>>>, String)> {
+    subject.get_smart_usage(node, Rc::new(CommandSource {}))
+}
+
+#[test]
+fn test_all_usage_no_commands() {
+    let subject = CommandDispatcher::<CommandSource>::new();
+    let results = subject.get_all_usage(&subject.root.borrow(), Rc::new(CommandSource {}), true);
+    assert!(results.is_empty());
+}
+
+#[test]
+fn test_smart_usage_no_commands() {
+    let subject = CommandDispatcher::<CommandSource>::new();
+    let results = smart_usage(&subject, &subject.root.borrow());
+    assert!(results.is_empty());
+}
+
+#[test]
+fn test_all_usage_root() {
+    let subject = setup();
+    let results = subject.get_all_usage(&subject.root.borrow(), Rc::new(CommandSource {}), true);
+    assert_eq!(
+        results,
+        vec![
+            "a 1 i", "a 1 ii", "a 2 i", "a 2 ii", "b 1", "c", "e", "e 1", "e 1 i", "e 1 ii",
+            "f 1 i", "f 2 ii", "g", "g 1 i", "h", "h 1 i", "h 2 i ii", "h 3", "i", "i 1", "i 2",
+            "j ...", "k -> h",
+        ]
+    );
+}
+
+#[test]
+fn test_all_usage_insertion_order() {
+    let command = |_: &_| 0;
+
+    let mut subject = CommandDispatcher::<CommandSource>::new();
+    subject.register(literal("b").executes(command));
+    subject.register(literal("a").executes(command));
+    subject.register(literal("c").executes(command));
+    let results = subject.get_all_usage(&subject.root.borrow(), Rc::new(CommandSource {}), true);
+    assert_eq!(results, vec!["b", "a", "c"]);
+}
+
+#[test]
+fn test_smart_usage_root() {
+    let subject = setup();
+    let results = smart_usage(&subject, &subject.root.borrow());
+    assert_eq!(
+        results,
+        vec![
+            (get(&subject, "a"), "a (1|2)".to_string()),
+            (get(&subject, "b"), "b 1".to_string()),
+            (get(&subject, "c"), "c".to_string()),
+            (get(&subject, "e"), "e [1]".to_string()),
+            (get(&subject, "f"), "f (1|2)".to_string()),
+            (get(&subject, "g"), "g [1]".to_string()),
+            (get(&subject, "h"), "h [1|2|3]".to_string()),
+            (get(&subject, "i"), "i [1|2]".to_string()),
+            (get(&subject, "j"), "j ...".to_string()),
+            (get(&subject, "k"), "k -> h".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_smart_usage_h() {
+    let subject = setup();
+    let results = smart_usage(&subject, &get(&subject, "h").borrow());
+    assert_eq!(
+        results,
+        vec![
+            (get(&subject, "h 1"), "[1] i".to_string()),
+            (get(&subject, "h 2"), "[2] i ii".to_string()),
+            (get(&subject, "h 3"), "[3]".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_smart_usage_offset_h() {
+    let subject = setup();
+    let offset_h = input_with_offset("/|/|/h", 5);
+    let parse = subject.parse(offset_h, Rc::new(CommandSource {}));
+    let results = smart_usage(&subject, &parse.context.nodes[0].node.borrow());
+    assert_eq!(
+        results,
+        vec![
+            (get(&subject, "h 1"), "[1] i".to_string()),
+            (get(&subject, "h 2"), "[2] i ii".to_string()),
+            (get(&subject, "h 3"), "[3]".to_string()),
+        ]
+    );
+}
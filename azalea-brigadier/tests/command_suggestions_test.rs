@@ -1 +1,403 @@
+use std::rc::Rc;
 
+use azalea_brigadier::{
+    arguments::{integer_argument_type::integer, string_argument_type::word},
+    builder::{literal_argument_builder::literal, required_argument_builder::argument},
+    command_dispatcher::CommandDispatcher,
+    context::StringRange,
+    string_reader::StringReader,
+    suggestion::{Suggestion, Suggestions},
+};
+
+#[derive(Debug, PartialEq)]
+struct CommandSource {}
+
+fn input_with_offset(input: &str, offset: usize) -> StringReader {
+    let mut result: StringReader = input.into();
+    result.cursor = offset;
+    result
+}
+
+fn suggestions_with_range(range: StringRange, texts: &[&str]) -> Vec<Suggestion> {
+    texts
+        .iter()
+        .map(|text| Suggestion {
+            text: text.to_string(),
+            range: range.clone(),
+            tooltip: None,
+        })
+        .collect()
+}
+
+fn test_suggestions(
+    subject: &CommandDispatcher<CommandSource>,
+    contents: &str,
+    cursor: usize,
+    range: StringRange,
+    suggestions: &[&str],
+) {
+    let result = subject.get_completion_suggestions_with_cursor(
+        subject.parse(contents.into(), Rc::new(CommandSource {})),
+        cursor,
+    );
+    assert_eq!(result.range, range);
+    assert_eq!(
+        result.suggestions,
+        suggestions_with_range(range, suggestions)
+    );
+}
+
+fn get_completion_suggestions(
+    subject: &CommandDispatcher<CommandSource>,
+    input: StringReader,
+) -> Suggestions {
+    subject.get_completion_suggestions(subject.parse(input, Rc::new(CommandSource {})))
+}
+
+#[test]
+fn get_completion_suggestions_root_commands() {
+    let mut subject = CommandDispatcher::new();
+    subject.register(literal("foo"));
+    subject.register(literal("bar"));
+    subject.register(literal("baz"));
+
+    let result = get_completion_suggestions(&subject, "".into());
+
+    assert_eq!(result.range, StringRange::at(0));
+    assert_eq!(
+        result.suggestions,
+        suggestions_with_range(StringRange::at(0), &["bar", "baz", "foo"])
+    );
+}
+
+#[test]
+fn get_completion_suggestions_root_commands_with_input_offset() {
+    let mut subject = CommandDispatcher::new();
+    subject.register(literal("foo"));
+    subject.register(literal("bar"));
+    subject.register(literal("baz"));
+
+    let result = get_completion_suggestions(&subject, input_with_offset("OOO", 3));
+
+    assert_eq!(result.range, StringRange::at(3));
+    assert_eq!(
+        result.suggestions,
+        suggestions_with_range(StringRange::at(3), &["bar", "baz", "foo"])
+    );
+}
+
+#[test]
+fn get_completion_suggestions_root_commands_partial() {
+    let mut subject = CommandDispatcher::new();
+    subject.register(literal("foo"));
+    subject.register(literal("bar"));
+    subject.register(literal("baz"));
+
+    let result = get_completion_suggestions(&subject, "b".into());
+
+    assert_eq!(result.range, StringRange::between(0, 1));
+    assert_eq!(
+        result.suggestions,
+        suggestions_with_range(StringRange::between(0, 1), &["bar", "baz"])
+    );
+}
+
+#[test]
+fn get_completion_suggestions_root_commands_partial_with_input_offset() {
+    let mut subject = CommandDispatcher::new();
+    subject.register(literal("foo"));
+    subject.register(literal("bar"));
+    subject.register(literal("baz"));
+
+    let result = get_completion_suggestions(&subject, input_with_offset("Zb", 1));
+
+    assert_eq!(result.range, StringRange::between(1, 2));
+    assert_eq!(
+        result.suggestions,
+        suggestions_with_range(StringRange::between(1, 2), &["bar", "baz"])
+    );
+}
+
+#[test]
+fn get_completion_suggestions_sub_commands() {
+    let mut subject = CommandDispatcher::new();
+    subject.register(
+        literal("parent")
+            .then(literal("foo"))
+            .then(literal("bar"))
+            .then(literal("baz")),
+    );
+
+    let result = get_completion_suggestions(&subject, "parent ".into());
+
+    assert_eq!(result.range, StringRange::at(7));
+    assert_eq!(
+        result.suggestions,
+        suggestions_with_range(StringRange::at(7), &["bar", "baz", "foo"])
+    );
+}
+
+#[test]
+fn get_completion_suggestions_moving_cursor_sub_commands() {
+    let mut subject = CommandDispatcher::new();
+    subject.register(
+        literal("parent_one")
+            .then(literal("faz"))
+            .then(literal("fbz"))
+            .then(literal("gaz")),
+    );
+    subject.register(literal("parent_two"));
+
+    test_suggestions(
+        &subject,
+        "parent_one faz ",
+        0,
+        StringRange::at(0),
+        &["parent_one", "parent_two"],
+    );
+    test_suggestions(
+        &subject,
+        "parent_one faz ",
+        1,
+        StringRange::between(0, 1),
+        &["parent_one", "parent_two"],
+    );
+    test_suggestions(
+        &subject,
+        "parent_one faz ",
+        7,
+        StringRange::between(0, 7),
+        &["parent_one", "parent_two"],
+    );
+    test_suggestions(
+        &subject,
+        "parent_one faz ",
+        8,
+        StringRange::between(0, 8),
+        &["parent_one"],
+    );
+    test_suggestions(&subject, "parent_one faz ", 10, StringRange::at(0), &[]);
+    test_suggestions(
+        &subject,
+        "parent_one faz ",
+        11,
+        StringRange::at(11),
+        &["faz", "fbz", "gaz"],
+    );
+    test_suggestions(
+        &subject,
+        "parent_one faz ",
+        12,
+        StringRange::between(11, 12),
+        &["faz", "fbz"],
+    );
+    test_suggestions(
+        &subject,
+        "parent_one faz ",
+        13,
+        StringRange::between(11, 13),
+        &["faz"],
+    );
+    test_suggestions(&subject, "parent_one faz ", 14, StringRange::at(0), &[]);
+    test_suggestions(&subject, "parent_one faz ", 15, StringRange::at(0), &[]);
+}
+
+#[test]
+fn get_completion_suggestions_sub_commands_partial() {
+    let mut subject = CommandDispatcher::new();
+    subject.register(
+        literal("parent")
+            .then(literal("foo"))
+            .then(literal("bar"))
+            .then(literal("baz")),
+    );
+
+    let result = get_completion_suggestions(&subject, "parent b".into());
+
+    assert_eq!(result.range, StringRange::between(7, 8));
+    assert_eq!(
+        result.suggestions,
+        suggestions_with_range(StringRange::between(7, 8), &["bar", "baz"])
+    );
+}
+
+#[test]
+fn get_completion_suggestions_sub_commands_partial_with_input_offset() {
+    let mut subject = CommandDispatcher::new();
+    subject.register(
+        literal("parent")
+            .then(literal("foo"))
+            .then(literal("bar"))
+            .then(literal("baz")),
+    );
+
+    let result = get_completion_suggestions(&subject, input_with_offset("junk parent b", 5));
+
+    assert_eq!(result.range, StringRange::between(12, 13));
+    assert_eq!(
+        result.suggestions,
+        suggestions_with_range(StringRange::between(12, 13), &["bar", "baz"])
+    );
+}
+
+#[test]
+fn get_completion_suggestions_redirect() {
+    let mut subject = CommandDispatcher::new();
+    let actual = subject.register(literal("actual").then(literal("sub")));
+    subject.register(literal("redirect").redirect(actual));
+
+    let result = get_completion_suggestions(&subject, "redirect ".into());
+
+    assert_eq!(result.range, StringRange::at(9));
+    assert_eq!(
+        result.suggestions,
+        suggestions_with_range(StringRange::at(9), &["sub"])
+    );
+}
+
+#[test]
+fn get_completion_suggestions_redirect_partial() {
+    let mut subject = CommandDispatcher::new();
+    let actual = subject.register(literal("actual").then(literal("sub")));
+    subject.register(literal("redirect").redirect(actual));
+
+    let result = get_completion_suggestions(&subject, "redirect s".into());
+
+    assert_eq!(result.range, StringRange::between(9, 10));
+    assert_eq!(
+        result.suggestions,
+        suggestions_with_range(StringRange::between(9, 10), &["sub"])
+    );
+}
+
+#[test]
+fn get_completion_suggestions_moving_cursor_redirect() {
+    let mut subject = CommandDispatcher::new();
+    let actual_one = subject.register(
+        literal("actual_one")
+            .then(literal("faz"))
+            .then(literal("fbz"))
+            .then(literal("gaz")),
+    );
+    subject.register(literal("actual_two"));
+    subject.register(literal("redirect_one").redirect(actual_one.clone()));
+    subject.register(literal("redirect_two").redirect(actual_one));
+
+    test_suggestions(
+        &subject,
+        "redirect_one faz ",
+        0,
+        StringRange::at(0),
+        &["actual_one", "actual_two", "redirect_one", "redirect_two"],
+    );
+    test_suggestions(
+        &subject,
+        "redirect_one faz ",
+        9,
+        StringRange::between(0, 9),
+        &["redirect_one", "redirect_two"],
+    );
+    test_suggestions(
+        &subject,
+        "redirect_one faz ",
+        10,
+        StringRange::between(0, 10),
+        &["redirect_one"],
+    );
+    test_suggestions(&subject, "redirect_one faz ", 12, StringRange::at(0), &[]);
+    test_suggestions(
+        &subject,
+        "redirect_one faz ",
+        13,
+        StringRange::at(13),
+        &["faz", "fbz", "gaz"],
+    );
+    test_suggestions(
+        &subject,
+        "redirect_one faz ",
+        14,
+        StringRange::between(13, 14),
+        &["faz", "fbz"],
+    );
+    test_suggestions(
+        &subject,
+        "redirect_one faz ",
+        15,
+        StringRange::between(13, 15),
+        &["faz"],
+    );
+    test_suggestions(&subject, "redirect_one faz ", 16, StringRange::at(0), &[]);
+    test_suggestions(&subject, "redirect_one faz ", 17, StringRange::at(0), &[]);
+}
+
+#[test]
+fn get_completion_suggestions_redirect_partial_with_input_offset() {
+    let mut subject = CommandDispatcher::new();
+    let actual = subject.register(literal("actual").then(literal("sub")));
+    subject.register(literal("redirect").redirect(actual));
+
+    let result = get_completion_suggestions(&subject, input_with_offset("/redirect s", 1));
+
+    assert_eq!(result.range, StringRange::between(10, 11));
+    assert_eq!(
+        result.suggestions,
+        suggestions_with_range(StringRange::between(10, 11), &["sub"])
+    );
+}
+
+#[test]
+fn get_completion_suggestions_redirect_lots() {
+    let mut subject = CommandDispatcher::new();
+    let lots = subject.register(literal("redirect"));
+    subject.register(
+        literal("redirect").then(literal("loop").then(argument("loop", integer()).redirect(lots))),
+    );
+
+    let result = get_completion_suggestions(&subject, "redirect loop 1 loop 02 loop 003 ".into());
+
+    assert_eq!(result.range, StringRange::at(33));
+    assert_eq!(
+        result.suggestions,
+        suggestions_with_range(StringRange::at(33), &["loop"])
+    );
+}
+
+#[test]
+fn get_completion_suggestions_execute_simulation() {
+    let mut subject = CommandDispatcher::new();
+    let execute = subject.register(literal("execute"));
+    subject.register(
+        literal("execute")
+            .then(literal("as").then(argument("name", word()).redirect(execute.clone())))
+            .then(literal("store").then(argument("name", word()).redirect(execute)))
+            .then(literal("run").executes(|_| 0)),
+    );
+
+    let result = get_completion_suggestions(&subject, "execute as Dinnerbone as".into());
+
+    assert!(result.suggestions.is_empty());
+}
+
+#[test]
+fn get_completion_suggestions_execute_simulation_partial() {
+    let mut subject = CommandDispatcher::new();
+    let execute = subject.register(literal("execute"));
+    subject.register(
+        literal("execute")
+            .then(
+                literal("as")
+                    .then(literal("bar").redirect(execute.clone()))
+                    .then(literal("baz").redirect(execute.clone())),
+            )
+            .then(literal("store").then(argument("name", word()).redirect(execute)))
+            .then(literal("run").executes(|_| 0)),
+    );
+
+    let result = get_completion_suggestions(&subject, "execute as bar as ".into());
+
+    assert_eq!(result.range, StringRange::at(18));
+    assert_eq!(
+        result.suggestions,
+        suggestions_with_range(StringRange::at(18), &["bar", "baz"])
+    );
+}
@@ -64,6 +64,16 @@ fn read() {
     assert_eq!(reader.cursor(), 3);
 }
 
+#[test]
+fn read_multibyte() {
+    let mut reader = StringReader::from("é hi".to_string());
+    assert_eq!(reader.read(), 'é');
+    assert_eq!(reader.get_read(), "é");
+    assert_eq!(reader.peek_offset(1), 'h');
+    reader.skip();
+    assert_eq!(reader.remaining(), "hi");
+}
+
 #[test]
 fn skip() {
     let mut reader = StringReader::from("abc".to_string());
@@ -0,0 +1,98 @@
+use azalea_brigadier::{
+    context::StringRange,
+    suggestion::{Suggestion, SuggestionsBuilder},
+};
+
+fn builder() -> SuggestionsBuilder {
+    SuggestionsBuilder::new("Hello w", 6)
+}
+
+fn suggestion(text: &str) -> Suggestion {
+    Suggestion {
+        text: text.to_string(),
+        range: StringRange::between(6, 7),
+        tooltip: None,
+    }
+}
+
+#[test]
+fn suggest_appends() {
+    let result = builder().suggest("world!").build();
+    assert_eq!(result.suggestions, vec![suggestion("world!")]);
+    assert_eq!(result.range, StringRange::between(6, 7));
+    assert!(!result.suggestions.is_empty());
+}
+
+#[test]
+fn suggest_replaces() {
+    let result = builder().suggest("everybody").build();
+    assert_eq!(result.suggestions, vec![suggestion("everybody")]);
+    assert_eq!(result.range, StringRange::between(6, 7));
+    assert!(!result.suggestions.is_empty());
+}
+
+#[test]
+fn suggest_noop() {
+    let result = builder().suggest("w").build();
+    assert!(result.suggestions.is_empty());
+}
+
+#[test]
+fn suggest_multiple() {
+    let result = builder()
+        .suggest("world!")
+        .suggest("everybody")
+        .suggest("weekend")
+        .build();
+    assert_eq!(
+        result.suggestions,
+        vec![
+            suggestion("everybody"),
+            suggestion("weekend"),
+            suggestion("world!"),
+        ]
+    );
+    assert_eq!(result.range, StringRange::between(6, 7));
+    assert!(!result.suggestions.is_empty());
+}
+
+#[test]
+fn restart() {
+    let mut builder = builder();
+    builder.suggest("won't be included");
+    let mut other = builder.restart();
+    other.suggest("works");
+    let result = other.build();
+    assert_eq!(result.suggestions, vec![suggestion("works")]);
+}
+
+#[test]
+fn create_offset() {
+    let mut builder = builder();
+    builder.suggest("won't be included");
+    let mut other = builder.create_offset(0);
+    other.suggest("Hi");
+    let result = other.build();
+    assert_eq!(
+        result.suggestions,
+        vec![Suggestion {
+            text: "Hi".to_string(),
+            range: StringRange::between(0, 7),
+            tooltip: None,
+        }]
+    );
+    assert_eq!(other.remaining(), "Hello w");
+}
+
+#[test]
+fn add() {
+    let mut builder = builder();
+    let mut other = builder.restart();
+    other.suggest("world!");
+    builder.suggest("everybody").add(&other);
+    let result = builder.build();
+    assert_eq!(
+        result.suggestions,
+        vec![suggestion("everybody"), suggestion("world!")]
+    );
+}
@@ -0,0 +1,189 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicI32, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use azalea_brigadier::{
+    arguments::integer_argument_type::{get_integer, integer},
+    builder::{literal_argument_builder::literal, required_argument_builder::argument},
+    sync_command_dispatcher::SyncCommandDispatcher,
+};
+
+#[derive(Debug, PartialEq)]
+struct CommandSource {}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn is_send_and_sync() {
+    assert_send_sync::<SyncCommandDispatcher<CommandSource>>();
+}
+
+#[test]
+fn execute_from_other_thread() {
+    let total = Arc::new(AtomicI32::new(0));
+
+    let mut subject = SyncCommandDispatcher::<CommandSource>::new();
+    let total_clone = total.clone();
+    subject.register(move |dispatcher| {
+        let total = total_clone.clone();
+        dispatcher.register(
+            literal("add").then(argument("n", integer()).executes(move |c| {
+                let n = get_integer(c, "n").unwrap();
+                total.fetch_add(n, Ordering::SeqCst);
+                n
+            })),
+        );
+    });
+    let subject = Arc::new(subject);
+
+    let handles = (1..=3)
+        .map(|n| {
+            let subject = subject.clone();
+            thread::spawn(move || {
+                subject
+                    .execute(
+                        format!("add {n}").as_str().into(),
+                        Rc::new(CommandSource {}),
+                    )
+                    .unwrap()
+            })
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(total.load(Ordering::SeqCst), 6);
+}
+
+#[test]
+fn redirects_and_suggestions() {
+    let mut subject = SyncCommandDispatcher::<CommandSource>::new();
+    subject.register(|dispatcher| {
+        dispatcher.register(literal("actual").executes(|_| 42));
+        let root = dispatcher.root.clone();
+        dispatcher.register(literal("redirected").redirect(root));
+    });
+
+    assert_eq!(
+        subject
+            .execute("redirected actual".into(), Rc::new(CommandSource {}))
+            .unwrap(),
+        42
+    );
+
+    let result =
+        subject.get_completion_suggestions("redirected a".into(), Rc::new(CommandSource {}));
+    assert_eq!(result.suggestions[0].text, "actual");
+
+    assert_eq!(
+        subject
+            .execute(
+                "redirected redirected actual".into(),
+                Rc::new(CommandSource {})
+            )
+            .unwrap(),
+        42
+    );
+}
+
+#[test]
+fn built_once_per_thread() {
+    let builds = Arc::new(AtomicUsize::new(0));
+
+    let mut subject = SyncCommandDispatcher::<CommandSource>::new();
+    let builds_clone = builds.clone();
+    subject.register(move |dispatcher| {
+        builds_clone.fetch_add(1, Ordering::SeqCst);
+        dispatcher.register(literal("foo").executes(|_| 1));
+    });
+
+    for _ in 0..3 {
+        subject
+            .execute("foo".into(), Rc::new(CommandSource {}))
+            .unwrap();
+    }
+    assert_eq!(builds.load(Ordering::SeqCst), 1);
+
+    // another thread gets its own dispatcher
+    let other = subject.clone();
+    thread::spawn(move || {
+        other
+            .execute("foo".into(), Rc::new(CommandSource {}))
+            .unwrap();
+    })
+    .join()
+    .unwrap();
+    assert_eq!(builds.load(Ordering::SeqCst), 2);
+
+    // and registering another command makes it get built again
+    subject.register(|dispatcher| {
+        dispatcher.register(literal("bar").executes(|_| 2));
+    });
+    assert_eq!(
+        subject
+            .execute("bar".into(), Rc::new(CommandSource {}))
+            .unwrap(),
+        2
+    );
+    assert_eq!(builds.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn usable_after_panic() {
+    let mut subject = SyncCommandDispatcher::<CommandSource>::new();
+    subject.register(|dispatcher| {
+        dispatcher.register(literal("foo").executes(|_| 1));
+    });
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        subject.with_dispatcher(|_| panic!("oh no"));
+    }));
+    assert!(result.is_err());
+
+    assert_eq!(
+        subject
+            .execute("foo".into(), Rc::new(CommandSource {}))
+            .unwrap(),
+        1
+    );
+}
+
+#[test]
+fn usage() {
+    let mut subject = SyncCommandDispatcher::<CommandSource>::new();
+    subject.register(|dispatcher| {
+        dispatcher.register(
+            literal("tp")
+                .then(argument("target", integer()).executes(|_| 1))
+                .then(literal("here").executes(|_| 1)),
+        );
+        dispatcher.register(literal("ping").executes(|_| 1));
+    });
+
+    assert_eq!(
+        subject.get_all_usage(&[], Rc::new(CommandSource {}), true),
+        vec!["tp <target>", "tp here", "ping"]
+    );
+    assert_eq!(
+        subject.get_all_usage(&["tp"], Rc::new(CommandSource {}), true),
+        vec!["<target>", "here"]
+    );
+    assert!(subject
+        .get_all_usage(&["nope"], Rc::new(CommandSource {}), true)
+        .is_empty());
+
+    assert_eq!(
+        subject.get_smart_usage(&[], Rc::new(CommandSource {})),
+        vec![
+            ("tp".to_string(), "tp (<target>|here)".to_string()),
+            ("ping".to_string(), "ping".to_string()),
+        ]
+    );
+}
@@ -1,8 +1,11 @@
 use quote::{quote, ToTokens};
 use syn::{self, punctuated::Punctuated, token::Comma, Data, Field, FieldsNamed, Ident};
 
+/// Make the code for reading each field. If `shared` is true, the fields are
+/// read with `read_from_shared` and there has to be a `shared` variable.
 fn read_named_fields(
     named: &Punctuated<Field, Comma>,
+    shared: bool,
 ) -> (Vec<proc_macro2::TokenStream>, Vec<&Option<Ident>>) {
     let read_fields = named
         .iter()
@@ -17,6 +20,10 @@ fn read_named_fields(
                         quote! {
                             let #field_name = azalea_buf::McBufVarReadable::var_read_from(buf)?;
                         }
+                    } else if shared {
+                        quote! {
+                            let #field_name = azalea_buf::McBufReadable::read_from_shared(buf, shared)?;
+                        }
                     } else {
                         quote! {
                             let #field_name = azalea_buf::McBufReadable::read_from(buf)?;
@@ -43,11 +50,15 @@ pub fn create_impl_mcbufreadable(ident: &Ident, data: &Data) -> proc_macro2::Tok
                 panic!("#[derive(McBuf)] can only be used on structs with named fields")
             };
 
-            let (read_fields, read_field_names) = read_named_fields(named);
+            let (read_fields, read_field_names) = read_named_fields(named, true);
 
             quote! {
             impl azalea_buf::McBufReadable for #ident {
                 fn read_from(buf: &mut std::io::Cursor<&[u8]>) -> Result<Self, azalea_buf::BufReadError> {
+                    Self::read_from_shared(buf, &azalea_buf::Bytes::new())
+                }
+
+                fn read_from_shared(buf: &mut std::io::Cursor<&[u8]>, shared: &azalea_buf::Bytes) -> Result<Self, azalea_buf::BufReadError> {
                     #(#read_fields)*
                     Ok(#ident {
                         #(#read_field_names: #read_field_names),*
@@ -86,7 +97,7 @@ pub fn create_impl_mcbufreadable(ident: &Ident, data: &Data) -> proc_macro2::Tok
                 }
                 let reader = match &variant.fields {
                     syn::Fields::Named(f) => {
-                        let (read_fields, read_field_names) = read_named_fields(&f.named);
+                        let (read_fields, read_field_names) = read_named_fields(&f.named, false);
 
                         quote! {
                             #(#read_fields)*
@@ -7,12 +7,15 @@
 mod definitions;
 mod read;
 mod serializable_uuid;
+mod shared;
 mod write;
 
 pub use azalea_buf_macros::*;
+pub use bytes::Bytes;
 pub use definitions::*;
 pub use read::{BufReadError, McBufReadable, McBufVarReadable};
 pub use serializable_uuid::*;
+pub use shared::SharedString;
 pub use write::{McBufVarWritable, McBufWritable};
 
 // const DEFAULT_NBT_QUOTA: u32 = 2097152;
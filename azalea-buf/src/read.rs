@@ -1,5 +1,6 @@
 use super::{UnsizedByteArray, MAX_STRING_LENGTH};
 use byteorder::{ReadBytesExt, BE};
+use bytes::Bytes;
 use log::warn;
 use std::{
     backtrace::Backtrace,
@@ -52,7 +53,10 @@ pub enum BufReadError {
     },
 }
 
-fn read_bytes<'a>(buf: &'a mut Cursor<&[u8]>, length: usize) -> Result<&'a [u8], BufReadError> {
+pub(crate) fn read_bytes<'a>(
+    buf: &'a mut Cursor<&[u8]>,
+    length: usize,
+) -> Result<&'a [u8], BufReadError> {
     if length > (buf.get_ref().len() - buf.position() as usize) {
         return Err(BufReadError::UnexpectedEof {
             attempted_read: length,
@@ -67,6 +71,14 @@ fn read_bytes<'a>(buf: &'a mut Cursor<&[u8]>, length: usize) -> Result<&'a [u8],
 }
 
 fn read_utf_with_len(buf: &mut Cursor<&[u8]>, max_length: u32) -> Result<String, BufReadError> {
+    read_str_with_len(buf, max_length).map(str::to_owned)
+}
+
+/// Read a string without copying it out of the buffer.
+pub(crate) fn read_str_with_len<'a>(
+    buf: &'a mut Cursor<&[u8]>,
+    max_length: u32,
+) -> Result<&'a str, BufReadError> {
     let length = u32::var_read_from(buf)?;
     // i don't know why it's multiplied by 4 but it's like that in mojang's code so
     if length > max_length * 4 {
@@ -77,13 +89,11 @@ fn read_utf_with_len(buf: &mut Cursor<&[u8]>, max_length: u32) -> Result<String,
     }
 
     let buffer = read_bytes(buf, length as usize)?;
-    let string = std::str::from_utf8(buffer)
-        .map_err(|_| BufReadError::InvalidUtf8 {
-            bytes: buffer.to_vec(),
-            lossy: String::from_utf8_lossy(buffer).to_string(),
-            // backtrace: Backtrace::capture(),
-        })?
-        .to_string();
+    let string = std::str::from_utf8(buffer).map_err(|_| BufReadError::InvalidUtf8 {
+        bytes: buffer.to_vec(),
+        lossy: String::from_utf8_lossy(buffer).to_string(),
+        // backtrace: Backtrace::capture(),
+    })?;
     if string.len() > length as usize {
         return Err(BufReadError::StringLengthTooLong { length, max_length });
     }
@@ -114,6 +124,15 @@ where
     Self: Sized,
 {
     fn read_from(buf: &mut Cursor<&[u8]>) -> Result<Self, BufReadError>;
+
+    /// Read from `buf`, which is a cursor over `shared`. Anything that can be
+    /// a slice of the buffer (like [`Bytes`]) shares its memory instead of
+    /// being copied.
+    ///
+    /// Types that contain other types should pass `shared` along to them.
+    fn read_from_shared(buf: &mut Cursor<&[u8]>, _shared: &Bytes) -> Result<Self, BufReadError> {
+        Self::read_from(buf)
+    }
 }
 
 pub trait McBufVarReadable
@@ -188,6 +207,18 @@ impl<T: McBufReadable + Send> McBufReadable for Vec<T> {
         }
         Ok(contents)
     }
+
+    default fn read_from_shared(
+        buf: &mut Cursor<&[u8]>,
+        shared: &Bytes,
+    ) -> Result<Self, BufReadError> {
+        let length = u32::var_read_from(buf)? as usize;
+        let mut contents = vec![];
+        for _ in 0..length {
+            contents.push(T::read_from_shared(buf, shared)?);
+        }
+        Ok(contents)
+    }
 }
 
 impl<K: McBufReadable + Send + Eq + Hash, V: McBufReadable + Send> McBufReadable for HashMap<K, V> {
@@ -199,6 +230,21 @@ impl<K: McBufReadable + Send + Eq + Hash, V: McBufReadable + Send> McBufReadable
         }
         Ok(contents)
     }
+
+    default fn read_from_shared(
+        buf: &mut Cursor<&[u8]>,
+        shared: &Bytes,
+    ) -> Result<Self, BufReadError> {
+        let length = i32::var_read_from(buf)? as usize;
+        let mut contents = HashMap::new();
+        for _ in 0..length {
+            contents.insert(
+                K::read_from_shared(buf, shared)?,
+                V::read_from_shared(buf, shared)?,
+            );
+        }
+        Ok(contents)
+    }
 }
 
 impl<K: McBufReadable + Send + Eq + Hash, V: McBufVarReadable + Send> McBufVarReadable
@@ -219,6 +265,10 @@ impl McBufReadable for Vec<u8> {
         let length = i32::var_read_from(buf)? as usize;
         read_bytes(buf, length).map(|b| b.to_vec())
     }
+
+    fn read_from_shared(buf: &mut Cursor<&[u8]>, _shared: &Bytes) -> Result<Self, BufReadError> {
+        Self::read_from(buf)
+    }
 }
 
 impl McBufReadable for String {
@@ -323,6 +373,18 @@ impl<T: McBufReadable> McBufReadable for Option<T> {
             None
         })
     }
+
+    default fn read_from_shared(
+        buf: &mut Cursor<&[u8]>,
+        shared: &Bytes,
+    ) -> Result<Self, BufReadError> {
+        let present = bool::read_from(buf)?;
+        Ok(if present {
+            Some(T::read_from_shared(buf, shared)?)
+        } else {
+            None
+        })
+    }
 }
 
 impl<T: McBufVarReadable> McBufVarReadable for Option<T> {
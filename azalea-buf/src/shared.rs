@@ -0,0 +1,190 @@
+//! Reading byte arrays and strings without copying them.
+//!
+//! When something is read with [`McBufReadable::read_from_shared`], these
+//! types point into the buffer that's being read instead of copying out of it,
+//! which is a lot faster for big byte arrays like chunk data.
+
+use crate::{
+    read::{read_bytes, read_str_with_len},
+    BufReadError, McBufReadable, McBufVarReadable, McBufVarWritable, McBufWritable,
+    MAX_STRING_LENGTH,
+};
+use bytes::Bytes;
+use std::{
+    borrow::Borrow,
+    fmt,
+    hash::{Hash, Hasher},
+    io::{Cursor, Write},
+    ops::Deref,
+};
+
+/// Whether `data` is part of `buffer`'s memory.
+fn is_within(buffer: &Bytes, data: &[u8]) -> bool {
+    let start = buffer.as_ptr() as usize;
+    let data_start = data.as_ptr() as usize;
+    data_start >= start && data_start + data.len() <= start + buffer.len()
+}
+
+/// Make `data` into [`Bytes`], sharing `shared`'s memory if it's a part of
+/// it.
+fn slice_or_copy(shared: &Bytes, data: &[u8]) -> Bytes {
+    if is_within(shared, data) {
+        shared.slice_ref(data)
+    } else {
+        Bytes::copy_from_slice(data)
+    }
+}
+
+impl McBufReadable for Bytes {
+    fn read_from(buf: &mut Cursor<&[u8]>) -> Result<Self, BufReadError> {
+        let length = i32::var_read_from(buf)? as usize;
+        read_bytes(buf, length).map(Bytes::copy_from_slice)
+    }
+
+    /// ```
+    /// # use azalea_buf::McBufReadable;
+    /// # use bytes::Bytes;
+    /// # use std::io::Cursor;
+    /// let buffer = Bytes::from_static(&[3, 1, 2, 3]);
+    /// let data = Bytes::read_from_shared(&mut Cursor::new(&buffer[..]), &buffer).unwrap();
+    /// assert_eq!(data, [1, 2, 3][..]);
+    /// ```
+    fn read_from_shared(buf: &mut Cursor<&[u8]>, shared: &Bytes) -> Result<Self, BufReadError> {
+        let length = i32::var_read_from(buf)? as usize;
+        read_bytes(buf, length).map(|data| slice_or_copy(shared, data))
+    }
+}
+
+impl McBufWritable for Bytes {
+    fn write_into(&self, buf: &mut impl Write) -> Result<(), std::io::Error> {
+        (self.len() as u32).var_write_into(buf)?;
+        buf.write_all(self)
+    }
+}
+
+/// An immutable string that shares memory with the buffer it was read from
+/// when it's read with [`McBufReadable::read_from_shared`]. This is used for
+/// the identifiers in packets that have a lot of them, so they don't all have
+/// to be allocated separately.
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SharedString(Bytes);
+
+impl SharedString {
+    pub fn as_str(&self) -> &str {
+        // SAFETY: the bytes are always valid UTF-8, since they're either from a
+        // string or were checked when they were read
+        unsafe { std::str::from_utf8_unchecked(&self.0) }
+    }
+}
+
+impl Deref for SharedString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+impl Borrow<str> for SharedString {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+// this has to hash the same way as a str so it can be looked up by one
+impl Hash for SharedString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+impl fmt::Debug for SharedString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+impl fmt::Display for SharedString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl From<&'static str> for SharedString {
+    fn from(s: &'static str) -> Self {
+        Self(Bytes::from_static(s.as_bytes()))
+    }
+}
+impl From<String> for SharedString {
+    fn from(s: String) -> Self {
+        Self(Bytes::from(s))
+    }
+}
+
+impl McBufReadable for SharedString {
+    fn read_from(buf: &mut Cursor<&[u8]>) -> Result<Self, BufReadError> {
+        String::read_from(buf).map(Self::from)
+    }
+
+    fn read_from_shared(buf: &mut Cursor<&[u8]>, shared: &Bytes) -> Result<Self, BufReadError> {
+        let string = read_str_with_len(buf, MAX_STRING_LENGTH.into())?;
+        Ok(Self(slice_or_copy(shared, string.as_bytes())))
+    }
+}
+
+impl McBufWritable for SharedString {
+    fn write_into(&self, buf: &mut impl Write) -> Result<(), std::io::Error> {
+        self.as_str().write_into(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_shared_bytes() {
+        let mut data = Vec::new();
+        vec![1u8, 2, 3].write_into(&mut data).unwrap();
+        5u32.var_write_into(&mut data).unwrap();
+        let buffer = Bytes::from(data);
+
+        let mut buf = Cursor::new(&buffer[..]);
+        let bytes = Bytes::read_from_shared(&mut buf, &buffer).unwrap();
+        let number = u32::var_read_from(&mut buf).unwrap();
+        assert_eq!(bytes, [1, 2, 3][..]);
+        assert_eq!(number, 5);
+        // it should point into the original buffer instead of being a copy
+        assert_eq!(bytes.as_ptr(), buffer[1..].as_ptr());
+    }
+
+    #[test]
+    fn test_read_unshared_bytes() {
+        let data = vec![2, 4, 5];
+        let bytes = Bytes::read_from(&mut Cursor::new(&data[..])).unwrap();
+        assert_eq!(bytes, [4, 5][..]);
+
+        // a buffer that the data isn't in gets ignored
+        let bytes = Bytes::read_from_shared(&mut Cursor::new(&data[..]), &Bytes::from_static(&[1]))
+            .unwrap();
+        assert_eq!(bytes, [4, 5][..]);
+    }
+
+    #[test]
+    fn test_read_shared_strings() {
+        let mut data = Vec::new();
+        vec!["minecraft:stone", "minecraft:dirt"]
+            .write_into(&mut data)
+            .unwrap();
+        let buffer = Bytes::from(data);
+
+        let strings =
+            Vec::<SharedString>::read_from_shared(&mut Cursor::new(&buffer[..]), &buffer).unwrap();
+        assert_eq!(
+            strings,
+            vec!["minecraft:stone".into(), "minecraft:dirt".into()]
+        );
+        assert_eq!(strings[1].as_str(), "minecraft:dirt");
+        assert_eq!(strings[0].as_ptr(), buffer[2..].as_ptr());
+
+        let mut written = Vec::new();
+        strings.write_into(&mut written).unwrap();
+        assert_eq!(written, buffer);
+    }
+}
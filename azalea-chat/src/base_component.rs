@@ -4,7 +4,8 @@ use serde::Serialize;
 #[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct BaseComponent {
     // implements mutablecomponent
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    // the components serialize these as "extra" themselves
+    #[serde(skip)]
     pub siblings: Vec<FormattedText>,
     #[serde(flatten)]
     pub style: Style,
@@ -0,0 +1,304 @@
+//! Build chat components in code.
+//!
+//! ```
+//! use azalea_chat::{builder::text, style::ChatFormatting};
+//!
+//! let message = text("Click here")
+//!     .color(ChatFormatting::Green)
+//!     .bold()
+//!     .click_run("/spawn")
+//!     .hover_text(text("Teleport to spawn").italic())
+//!     .append(text(" to go home"));
+//!
+//! assert_eq!(message.to_string(), "Click here to go home");
+//! println!("/tellraw @a {}", message.to_json());
+//! ```
+
+use crate::{
+    events::{ClickAction, ClickEvent, HoverEvent},
+    keybind_component::KeybindComponent,
+    style::{ChatFormatting, Style, TextColor},
+    translatable_component::{StringOrComponent, TranslatableComponent},
+    FormattedText,
+};
+
+/// A component with some text that isn't translated.
+///
+/// Unlike [`TextComponent::new`](crate::text_component::TextComponent::new),
+/// legacy formatting codes in the text aren't parsed.
+pub fn text(text: impl Into<String>) -> FormattedText {
+    FormattedText::from(text.into())
+}
+
+/// A component that's translated by the client, like `chat.type.text` with
+/// the sender and message as arguments.
+pub fn translatable(
+    key: impl Into<String>,
+    args: impl IntoIterator<Item = impl Into<FormattedText>>,
+) -> FormattedText {
+    let args = args
+        .into_iter()
+        .map(|arg| match arg.into() {
+            // unstyled text is sent as just a string, like when it's deserialized
+            FormattedText::Text(c) if c.base.siblings.is_empty() && c.base.style.is_empty() => {
+                StringOrComponent::String(c.text)
+            }
+            arg => StringOrComponent::FormattedText(arg),
+        })
+        .collect();
+    FormattedText::Translatable(TranslatableComponent::new(key.into(), args))
+}
+
+/// A component that shows the key a keybind like `key.jump` is bound to.
+pub fn keybind(key: impl Into<String>) -> FormattedText {
+    FormattedText::Keybind(KeybindComponent::new(key.into()))
+}
+
+impl FormattedText {
+    fn style_mut(&mut self) -> &mut Style {
+        &mut self.get_base_mut().style
+    }
+
+    /// Set the color of this component and its siblings. Formatting codes
+    /// like [`ChatFormatting::Bold`] are applied like they are in legacy
+    /// text.
+    pub fn color(mut self, color: ChatFormatting) -> Self {
+        if color.is_format() || color == ChatFormatting::Reset {
+            self.style_mut().apply_formatting(&color);
+        } else {
+            self.style_mut().color = color.try_into().ok();
+        }
+        self
+    }
+
+    /// Set the color of this component and its siblings to an RGB color,
+    /// like `0xff8000`.
+    pub fn rgb(mut self, rgb: u32) -> Self {
+        self.style_mut().color = Some(TextColor::from_rgb(rgb));
+        self
+    }
+
+    pub fn bold(mut self) -> Self {
+        self.style_mut().bold = Some(true);
+        self
+    }
+
+    pub fn italic(mut self) -> Self {
+        self.style_mut().italic = Some(true);
+        self
+    }
+
+    pub fn underlined(mut self) -> Self {
+        self.style_mut().underlined = Some(true);
+        self
+    }
+
+    pub fn strikethrough(mut self) -> Self {
+        self.style_mut().strikethrough = Some(true);
+        self
+    }
+
+    pub fn obfuscated(mut self) -> Self {
+        self.style_mut().obfuscated = Some(true);
+        self
+    }
+
+    /// Set the font, like `minecraft:uniform`.
+    pub fn font(mut self, font: impl Into<String>) -> Self {
+        self.style_mut().font = Some(font.into());
+        self
+    }
+
+    /// Set the text that's inserted into the chat box when this is
+    /// shift-clicked.
+    pub fn insertion(mut self, insertion: impl Into<String>) -> Self {
+        self.style_mut().insertion = Some(insertion.into());
+        self
+    }
+
+    pub fn click(mut self, click_event: ClickEvent) -> Self {
+        self.style_mut().click_event = Some(click_event);
+        self
+    }
+
+    /// Run a command (or send a chat message) when this is clicked.
+    pub fn click_run(self, command: impl Into<String>) -> Self {
+        self.click(ClickEvent::new(ClickAction::RunCommand, command))
+    }
+
+    /// Put some text in the chat box when this is clicked.
+    pub fn click_suggest(self, command: impl Into<String>) -> Self {
+        self.click(ClickEvent::new(ClickAction::SuggestCommand, command))
+    }
+
+    /// Open a URL when this is clicked. The client asks before opening it.
+    pub fn click_open_url(self, url: impl Into<String>) -> Self {
+        self.click(ClickEvent::new(ClickAction::OpenUrl, url))
+    }
+
+    /// Copy some text to the clipboard when this is clicked.
+    pub fn click_copy(self, text: impl Into<String>) -> Self {
+        self.click(ClickEvent::new(ClickAction::CopyToClipboard, text))
+    }
+
+    /// Go to a page in a book when this is clicked.
+    pub fn click_change_page(self, page: u32) -> Self {
+        self.click(ClickEvent::new(ClickAction::ChangePage, page.to_string()))
+    }
+
+    pub fn hover(mut self, hover_event: HoverEvent) -> Self {
+        self.style_mut().hover_event = Some(hover_event);
+        self
+    }
+
+    /// Show a tooltip with some text when this is hovered over.
+    pub fn hover_text(self, text: impl Into<FormattedText>) -> Self {
+        self.hover(HoverEvent::ShowText(Box::new(text.into())))
+    }
+
+    /// Add a sibling after this component. Siblings inherit this component's
+    /// style.
+    pub fn append(mut self, sibling: impl Into<FormattedText>) -> Self {
+        self.get_base_mut().siblings.push(sibling.into());
+        self
+    }
+
+    /// Serialize this component into the JSON that the game uses, like for
+    /// `/tellraw` or book pages.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("components should always serialize")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::ItemStackInfo;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    fn assert_round_trip(component: &FormattedText) {
+        let json = component.to_json();
+        assert_eq!(
+            &FormattedText::deserialize(&json).unwrap(),
+            component,
+            "{json} didn't deserialize to the same component"
+        );
+    }
+
+    #[test]
+    fn test_text() {
+        let component = text("hi");
+        assert_eq!(component.to_json(), json!({"text": "hi"}));
+        assert_round_trip(&component);
+
+        // legacy codes are kept as they are
+        let component = text("§ahi");
+        assert_eq!(component.to_json(), json!({"text": "§ahi"}));
+    }
+
+    #[test]
+    fn test_styles() {
+        let component = text("Click here")
+            .color(ChatFormatting::Red)
+            .bold()
+            .italic()
+            .underlined()
+            .strikethrough()
+            .obfuscated()
+            .font("minecraft:uniform")
+            .insertion("hi")
+            .click_run("/spawn")
+            .hover_text(text("Teleport to spawn").rgb(0xabcdef));
+        assert_eq!(
+            component.to_json(),
+            json!({
+                "text": "Click here",
+                "color": "red",
+                "bold": true,
+                "italic": true,
+                "underlined": true,
+                "strikethrough": true,
+                "obfuscated": true,
+                "font": "minecraft:uniform",
+                "insertion": "hi",
+                "clickEvent": {"action": "run_command", "value": "/spawn"},
+                "hoverEvent": {
+                    "action": "show_text",
+                    "contents": {"text": "Teleport to spawn", "color": "#ABCDEF"}
+                }
+            })
+        );
+        assert_round_trip(&component);
+    }
+
+    #[test]
+    fn test_color_with_formatting_code() {
+        assert_eq!(
+            text("a").color(ChatFormatting::Bold).to_json(),
+            json!({"text": "a", "bold": true})
+        );
+    }
+
+    #[test]
+    fn test_siblings() {
+        let component = text("a")
+            .color(ChatFormatting::Gold)
+            .append(text("b").bold())
+            .append("c");
+        assert_eq!(
+            component.to_json(),
+            json!({
+                "text": "a",
+                "color": "gold",
+                "extra": [{"text": "b", "bold": true}, {"text": "c"}]
+            })
+        );
+        assert_eq!(component.to_string(), "abc");
+        assert_round_trip(&component);
+    }
+
+    #[test]
+    fn test_translatable() {
+        let component = translatable(
+            "chat.type.text",
+            [text("Steve").color(ChatFormatting::Yellow), text("hi")],
+        )
+        .append("!");
+        assert_eq!(
+            component.to_json(),
+            json!({
+                "translate": "chat.type.text",
+                "with": [{"text": "Steve", "color": "yellow"}, "hi"],
+                "extra": [{"text": "!"}]
+            })
+        );
+        assert_eq!(component.to_string(), "<Steve> hi!");
+        assert_round_trip(&component);
+
+        let component = translatable("multiplayer.disconnect.kicked", [] as [&str; 0]);
+        assert_eq!(
+            component.to_json(),
+            json!({"translate": "multiplayer.disconnect.kicked"})
+        );
+        assert_round_trip(&component);
+    }
+
+    #[test]
+    fn test_events() {
+        let item = HoverEvent::ShowItem(ItemStackInfo {
+            id: "minecraft:diamond".to_string(),
+            count: 64,
+            tag: None,
+        });
+        for component in [
+            keybind("key.jump").click_suggest("/jump"),
+            text("docs").click_open_url("https://example.com"),
+            text("copy").click_copy("secret"),
+            text("next").click_change_page(2),
+            text("item").hover(item),
+        ] {
+            assert_round_trip(&component);
+        }
+    }
+}
@@ -1,11 +1,16 @@
 use crate::{
     base_component::BaseComponent,
+    keybind_component::KeybindComponent,
+    nbt_component::{NbtComponent, NbtSource},
+    score_component::ScoreComponent,
+    selector_component::SelectorComponent,
     style::{ChatFormatting, Style},
     text_component::TextComponent,
     translatable_component::{StringOrComponent, TranslatableComponent},
 };
 #[cfg(feature = "azalea-buf")]
 use azalea_buf::{BufReadError, McBufReadable, McBufWritable};
+use azalea_language::Language;
 use log::debug;
 use once_cell::sync::Lazy;
 use serde::{de, Deserialize, Deserializer, Serialize};
@@ -20,6 +25,10 @@ use std::{
 pub enum FormattedText {
     Text(TextComponent),
     Translatable(TranslatableComponent),
+    Score(ScoreComponent),
+    Selector(SelectorComponent),
+    Keybind(KeybindComponent),
+    Nbt(NbtComponent),
 }
 
 pub static DEFAULT_STYLE: Lazy<Style> = Lazy::new(|| Style {
@@ -33,6 +42,10 @@ impl FormattedText {
         match self {
             Self::Text(c) => &mut c.base,
             Self::Translatable(c) => &mut c.base,
+            Self::Score(c) => &mut c.base,
+            Self::Selector(c) => &mut c.base,
+            Self::Keybind(c) => &mut c.base,
+            Self::Nbt(c) => &mut c.base,
         }
     }
 
@@ -40,12 +53,112 @@ impl FormattedText {
         match self {
             Self::Text(c) => &c.base,
             Self::Translatable(c) => &c.base,
+            Self::Score(c) => &c.base,
+            Self::Selector(c) => &c.base,
+            Self::Keybind(c) => &c.base,
+            Self::Nbt(c) => &c.base,
         }
     }
 
-    /// Add a component as a sibling of this one
-    fn append(&mut self, sibling: FormattedText) {
-        self.get_base_mut().siblings.push(sibling);
+    /// Call `visitor` with the text of this component and all of its
+    /// siblings, in order, along with the style that the text is shown with.
+    ///
+    /// The styles include everything inherited from the parent components,
+    /// and translations are resolved, so this is what
+    /// [`FormattedText::to_plain`] and [`FormattedText::to_html`] are built
+    /// on.
+    ///
+    /// ```
+    /// use azalea_chat::FormattedText;
+    /// use serde::de::Deserialize;
+    ///
+    /// let component = FormattedText::deserialize(&serde_json::json!({
+    ///    "text": "Hello, ",
+    ///    "bold": true,
+    ///    "extra": ["world!"],
+    /// })).unwrap();
+    ///
+    /// let mut bold_text = String::new();
+    /// component.visit(|text, style| {
+    ///     if style.bold == Some(true) {
+    ///         bold_text.push_str(text);
+    ///     }
+    /// });
+    /// assert_eq!(bold_text, "Hello, world!");
+    /// ```
+    pub fn visit(&self, visitor: impl FnMut(&str, &Style)) {
+        self.visit_with_language(&azalea_language::default_language(), visitor);
+    }
+
+    /// Like [`FormattedText::visit`], but translations are resolved with the
+    /// given [`Language`] instead of the default one.
+    pub fn visit_with_language(&self, language: &Language, mut visitor: impl FnMut(&str, &Style)) {
+        self.visit_with_parent_style(language, &Style::default(), &mut visitor);
+    }
+
+    fn visit_with_parent_style(
+        &self,
+        language: &Language,
+        parent_style: &Style,
+        visitor: &mut impl FnMut(&str, &Style),
+    ) {
+        let own_style = &self.get_base().style;
+        let mut style = if own_style.reset {
+            Style::default()
+        } else {
+            parent_style.clone()
+        };
+        style.apply(own_style);
+
+        match self {
+            Self::Text(c) => visitor(&c.text, &style),
+            Self::Translatable(c) => match c.read_with_language(language) {
+                Ok(translated) => {
+                    Self::Text(translated).visit_with_parent_style(language, &style, visitor)
+                }
+                // vanilla shows the untranslated text if it's invalid
+                Err(_) => visitor(c.template(language), &style),
+            },
+            Self::Score(c) => visitor(&c.contents(), &style),
+            Self::Selector(c) => visitor(&c.contents(), &style),
+            Self::Keybind(c) => visitor(&c.contents_with_language(language), &style),
+            Self::Nbt(c) => visitor(&c.contents(), &style),
+        }
+
+        for sibling in &self.get_base().siblings {
+            sibling.visit_with_parent_style(language, &style, visitor);
+        }
+    }
+
+    /// Get the text of this component without any styling. Translations are
+    /// resolved with their arguments, so this is the same as
+    /// [`FormattedText::to_string`](ToString::to_string).
+    pub fn to_plain(&self) -> String {
+        self.to_plain_with_language(&azalea_language::default_language())
+    }
+
+    /// Get the text of this component without any styling, with translations
+    /// from the given [`Language`]. Keys that the language doesn't have are
+    /// translated with `en_us`.
+    ///
+    /// ```
+    /// use azalea_chat::FormattedText;
+    /// use azalea_language::Language;
+    /// use serde::de::Deserialize;
+    ///
+    /// let german = Language::from_bytes(br#"{"death.attack.fall": "%1$s fiel zu tief"}"#).unwrap();
+    /// let component = FormattedText::deserialize(&serde_json::json!({
+    ///    "translate": "death.attack.fall",
+    ///    "with": ["Steve"],
+    /// })).unwrap();
+    ///
+    /// assert_eq!(component.to_plain_with_language(&german), "Steve fiel zu tief");
+    /// assert_eq!(component.to_plain(), "Steve hit the ground too hard");
+    /// ```
+    pub fn to_plain_with_language(&self, language: &Language) -> String {
+        let mut plain = String::new();
+        self.visit_with_language(language, |text, _| plain.push_str(text));
+        plain
     }
 
     /// Get the "separator" component from the json
@@ -101,6 +214,10 @@ impl FormattedText {
             let component_text = match &component {
                 Self::Text(c) => c.text.to_string(),
                 Self::Translatable(c) => c.to_string(),
+                Self::Score(c) => c.contents(),
+                Self::Selector(c) => c.contents(),
+                Self::Keybind(c) => c.contents(),
+                Self::Nbt(c) => c.contents(),
             };
 
             let component_style = &component.get_base().style;
@@ -197,41 +314,71 @@ impl<'de> Deserialize<'de> for FormattedText {
                     ));
                 }
             } else if let Some(score) = json.get("score") {
-                // object = GsonHelper.getAsJsonObject(jsonObject, "score");
-                if score.get("name").is_none() || score.get("objective").is_none() {
+                let (Some(name), Some(objective)) = (
+                    score.get("name").and_then(|v| v.as_str()),
+                    score.get("objective").and_then(|v| v.as_str()),
+                ) else {
                     return Err(de::Error::missing_field(
                         "A score component needs at least a name and an objective",
                     ));
-                }
-                // TODO
-                return Err(de::Error::custom(
-                    "score text components aren't yet supported",
-                ));
-            } else if json.get("selector").is_some() {
-                return Err(de::Error::custom(
-                    "selector text components aren't yet supported",
-                ));
-            } else if json.get("keybind").is_some() {
-                return Err(de::Error::custom(
-                    "keybind text components aren't yet supported",
+                };
+                let mut score_component =
+                    ScoreComponent::new(name.to_string(), objective.to_string());
+                score_component.value = score
+                    .get("value")
+                    .and_then(|v| v.as_str())
+                    .map(|v| v.to_string());
+                component = FormattedText::Score(score_component);
+            } else if let Some(selector) = json.get("selector") {
+                let selector = selector
+                    .as_str()
+                    .ok_or_else(|| de::Error::custom("\"selector\" must be a string"))?;
+                let separator = FormattedText::parse_separator(&json).map_err(de::Error::custom)?;
+                component = FormattedText::Selector(SelectorComponent::new(
+                    selector.to_string(),
+                    separator,
                 ));
+            } else if let Some(keybind) = json.get("keybind") {
+                let keybind = keybind
+                    .as_str()
+                    .ok_or_else(|| de::Error::custom("\"keybind\" must be a string"))?;
+                component = FormattedText::Keybind(KeybindComponent::new(keybind.to_string()));
             } else {
-                let Some(_nbt) = json.get("nbt") else {
+                let Some(nbt) = json.get("nbt") else {
                     return Err(de::Error::custom(
                         format!("Don't know how to turn {json} into a FormattedText").as_str(),
                     ));
                 };
-                let _separator =
-                    FormattedText::parse_separator(&json).map_err(de::Error::custom)?;
+                let nbt = nbt
+                    .as_str()
+                    .ok_or_else(|| de::Error::custom("\"nbt\" must be a string"))?;
+                let separator = FormattedText::parse_separator(&json).map_err(de::Error::custom)?;
+                let interpret = json
+                    .get("interpret")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
 
-                let _interpret = match json.get("interpret") {
-                    Some(v) => v.as_bool().ok_or(Some(false)).unwrap(),
-                    None => false,
+                let source_field = |name: &str| {
+                    json.get(name)
+                        .and_then(|v| v.as_str())
+                        .map(|v| v.to_string())
                 };
-                if let Some(_block) = json.get("block") {}
-                return Err(de::Error::custom(
-                    "nbt text components aren't yet supported",
-                ));
+                let source = if let Some(block) = source_field("block") {
+                    NbtSource::Block(block)
+                } else if let Some(entity) = source_field("entity") {
+                    NbtSource::Entity(entity)
+                } else if let Some(storage) = source_field("storage") {
+                    NbtSource::Storage(storage)
+                } else {
+                    return Err(de::Error::custom(
+                        format!("Don't know how to turn {json} into a FormattedText").as_str(),
+                    ));
+                };
+
+                let mut nbt_component = NbtComponent::new(nbt.to_string(), source);
+                nbt_component.interpret = interpret;
+                nbt_component.separator = separator.map(Box::new);
+                component = FormattedText::Nbt(nbt_component);
             }
             if let Some(extra) = json.get("extra") {
                 let Some(extra) = extra.as_array() else {
@@ -243,7 +390,7 @@ impl<'de> Deserialize<'de> for FormattedText {
                 for extra_component in extra {
                     let sibling =
                         FormattedText::deserialize(extra_component).map_err(de::Error::custom)?;
-                    component.append(sibling);
+                    component.get_base_mut().siblings.push(sibling);
                 }
             }
 
@@ -264,7 +411,7 @@ impl<'de> Deserialize<'de> for FormattedText {
         let mut component =
             FormattedText::deserialize(&json_array[0]).map_err(de::Error::custom)?;
         for i in 1..json_array.len() {
-            component.append(
+            component.get_base_mut().siblings.push(
                 FormattedText::deserialize(json_array.get(i).unwrap())
                     .map_err(de::Error::custom)?,
             );
@@ -309,10 +456,7 @@ impl From<&str> for FormattedText {
 
 impl Display for FormattedText {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            FormattedText::Text(c) => c.fmt(f),
-            FormattedText::Translatable(c) => c.fmt(f),
-        }
+        f.write_str(&self.to_plain())
     }
 }
 
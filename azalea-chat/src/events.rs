@@ -1,26 +1,335 @@
-enum ClickAction {
-    OPEN_URL = Action::new("open_url", true),
-    OPEN_FILE = Action::new("open_file", false),
-    RUN_COMMAND = Action::new("run_command", true),
-    SUGGEST_COMMAND = Action::new("suggest_command", true),
-    CHANGE_PAGE = Action::new("change_page", true),
-    COPY_TO_CLIPBOARD = Action::new("copy_to_clipboard", true),
-}
+//! Click and hover events, which make text do things when it's clicked or
+//! hovered over.
+
+use crate::FormattedText;
+use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
+use serde_json::Value;
+use uuid::Uuid;
 
-struct ClickAction {
-    pub name: String,
-    pub allow_from_server: bool,
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ClickAction {
+    OpenUrl,
+    OpenFile,
+    RunCommand,
+    SuggestCommand,
+    ChangePage,
+    CopyToClipboard,
 }
 
 impl ClickAction {
-    fn new(name: &str, allow_from_server: bool) -> Self {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClickAction::OpenUrl => "open_url",
+            ClickAction::OpenFile => "open_file",
+            ClickAction::RunCommand => "run_command",
+            ClickAction::SuggestCommand => "suggest_command",
+            ClickAction::ChangePage => "change_page",
+            ClickAction::CopyToClipboard => "copy_to_clipboard",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ClickAction> {
+        match name {
+            "open_url" => Some(ClickAction::OpenUrl),
+            "open_file" => Some(ClickAction::OpenFile),
+            "run_command" => Some(ClickAction::RunCommand),
+            "suggest_command" => Some(ClickAction::SuggestCommand),
+            "change_page" => Some(ClickAction::ChangePage),
+            "copy_to_clipboard" => Some(ClickAction::CopyToClipboard),
+            _ => None,
+        }
+    }
+
+    /// Whether servers are allowed to send this action. The game only uses
+    /// `open_file` for screenshot links, so it's ignored when it's received.
+    pub fn allow_from_server(&self) -> bool {
+        !matches!(self, ClickAction::OpenFile)
+    }
+}
+
+impl Serialize for ClickAction {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.name())
+    }
+}
+
+/// Something that happens when text is clicked, like running a command.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct ClickEvent {
+    pub action: ClickAction,
+    pub value: String,
+}
+
+impl ClickEvent {
+    pub fn new(action: ClickAction, value: impl Into<String>) -> Self {
         Self {
-            name: name.to_string(),
-            allow_from_server,
+            action,
+            value: value.into(),
+        }
+    }
+
+    /// Read a click event from the `clickEvent` field of a component. This
+    /// returns `None` if it's invalid or the action isn't allowed from
+    /// servers, like in vanilla.
+    pub fn deserialize(json: &Value) -> Option<ClickEvent> {
+        let action = ClickAction::from_name(json.get("action")?.as_str()?)?;
+        let value = json.get("value")?.as_str()?;
+        if !action.allow_from_server() {
+            return None;
+        }
+        Some(ClickEvent::new(action, value))
+    }
+}
+
+/// Something that's shown when text is hovered over.
+#[derive(Clone, Debug, PartialEq)]
+pub enum HoverEvent {
+    ShowText(Box<FormattedText>),
+    ShowItem(ItemStackInfo),
+    ShowEntity(EntityTooltipInfo),
+}
+
+/// The item that's shown by [`HoverEvent::ShowItem`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ItemStackInfo {
+    /// The item's id, like `minecraft:diamond_sword`.
+    pub id: String,
+    pub count: i32,
+    /// The item's NBT as SNBT, like `{Damage:5}`.
+    pub tag: Option<String>,
+}
+
+/// The entity that's shown by [`HoverEvent::ShowEntity`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntityTooltipInfo {
+    /// The type of entity, like `minecraft:pig`.
+    pub kind: String,
+    pub id: Uuid,
+    pub name: Option<Box<FormattedText>>,
+}
+
+impl HoverEvent {
+    pub fn action_name(&self) -> &'static str {
+        match self {
+            HoverEvent::ShowText(_) => "show_text",
+            HoverEvent::ShowItem(_) => "show_item",
+            HoverEvent::ShowEntity(_) => "show_entity",
+        }
+    }
+
+    /// Read a hover event from the `hoverEvent` field of a component,
+    /// returning `None` if it's invalid.
+    ///
+    /// Hover events from before 1.16 have a `value` instead of `contents`.
+    /// Those are only supported for `show_text`, since the others have SNBT
+    /// in them.
+    pub fn deserialize(json: &Value) -> Option<HoverEvent> {
+        let action = json.get("action")?.as_str()?;
+        let Some(contents) = json.get("contents") else {
+            let value = json.get("value")?;
+            return match action {
+                "show_text" => Some(HoverEvent::ShowText(Box::new(
+                    FormattedText::deserialize(value).ok()?,
+                ))),
+                _ => None,
+            };
+        };
+        match action {
+            "show_text" => Some(HoverEvent::ShowText(Box::new(
+                FormattedText::deserialize(contents).ok()?,
+            ))),
+            "show_item" => {
+                // the contents can also be just the id
+                if let Some(id) = contents.as_str() {
+                    return Some(HoverEvent::ShowItem(ItemStackInfo {
+                        id: id.to_string(),
+                        count: 1,
+                        tag: None,
+                    }));
+                }
+                Some(HoverEvent::ShowItem(ItemStackInfo {
+                    id: contents.get("id")?.as_str()?.to_string(),
+                    count: match contents.get("count") {
+                        Some(count) => count.as_i64()? as i32,
+                        None => 1,
+                    },
+                    tag: match contents.get("tag") {
+                        Some(tag) => Some(tag.as_str()?.to_string()),
+                        None => None,
+                    },
+                }))
+            }
+            "show_entity" => Some(HoverEvent::ShowEntity(EntityTooltipInfo {
+                kind: contents.get("type")?.as_str()?.to_string(),
+                id: parse_uuid(contents.get("id")?)?,
+                name: match contents.get("name") {
+                    Some(name) => Some(Box::new(FormattedText::deserialize(name).ok()?)),
+                    None => None,
+                },
+            })),
+            _ => None,
+        }
+    }
+}
+
+/// UUIDs in hover events are either strings or arrays of four ints.
+fn parse_uuid(json: &Value) -> Option<Uuid> {
+    if let Some(s) = json.as_str() {
+        return Uuid::parse_str(s).ok();
+    }
+    let ints = json.as_array()?;
+    if ints.len() != 4 {
+        return None;
+    }
+    let mut bytes = [0; 16];
+    for (i, int) in ints.iter().enumerate() {
+        let int = int.as_i64()? as i32;
+        bytes[i * 4..i * 4 + 4].copy_from_slice(&int.to_be_bytes());
+    }
+    Some(Uuid::from_bytes(bytes))
+}
+
+impl Serialize for HoverEvent {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("HoverEvent", 2)?;
+        state.serialize_field("action", self.action_name())?;
+        match self {
+            HoverEvent::ShowText(text) => state.serialize_field("contents", text)?,
+            HoverEvent::ShowItem(item) => state.serialize_field("contents", item)?,
+            HoverEvent::ShowEntity(entity) => state.serialize_field("contents", entity)?,
+        }
+        state.end()
+    }
+}
+
+impl Serialize for ItemStackInfo {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let len = 1 + usize::from(self.count != 1) + usize::from(self.tag.is_some());
+        let mut state = serializer.serialize_struct("ItemStackInfo", len)?;
+        state.serialize_field("id", &self.id)?;
+        if self.count != 1 {
+            state.serialize_field("count", &self.count)?;
         }
+        if let Some(tag) = &self.tag {
+            state.serialize_field("tag", tag)?;
+        }
+        state.end()
     }
 }
 
-struct ClickEvent {
-    action: ClickAction,
+impl Serialize for EntityTooltipInfo {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let len = 2 + usize::from(self.name.is_some());
+        let mut state = serializer.serialize_struct("EntityTooltipInfo", len)?;
+        state.serialize_field("type", &self.kind)?;
+        state.serialize_field("id", &self.id.to_string())?;
+        if let Some(name) = &self.name {
+            state.serialize_field("name", name)?;
+        }
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_click_event() {
+        let json = json!({"action": "run_command", "value": "/tpaccept"});
+        let event = ClickEvent::deserialize(&json).unwrap();
+        assert_eq!(event, ClickEvent::new(ClickAction::RunCommand, "/tpaccept"));
+        assert_eq!(serde_json::to_value(&event).unwrap(), json);
+
+        assert_eq!(
+            ClickEvent::deserialize(&json!({"action": "open_file", "value": "a.png"})),
+            None
+        );
+        assert_eq!(
+            ClickEvent::deserialize(&json!({"action": "explode", "value": ""})),
+            None
+        );
+    }
+
+    #[test]
+    fn test_show_text() {
+        let json = json!({"action": "show_text", "contents": {"text": "hi"}});
+        let event = HoverEvent::deserialize(&json).unwrap();
+        assert_eq!(event, HoverEvent::ShowText(Box::new("hi".into())));
+        assert_eq!(serde_json::to_value(&event).unwrap(), json);
+
+        let legacy = json!({"action": "show_text", "value": "hi"});
+        assert_eq!(HoverEvent::deserialize(&legacy), Some(event));
+    }
+
+    #[test]
+    fn test_show_item() {
+        let json = json!({
+            "action": "show_item",
+            "contents": {"id": "minecraft:diamond_sword", "count": 2, "tag": "{Damage:5}"}
+        });
+        let event = HoverEvent::deserialize(&json).unwrap();
+        assert_eq!(
+            event,
+            HoverEvent::ShowItem(ItemStackInfo {
+                id: "minecraft:diamond_sword".to_string(),
+                count: 2,
+                tag: Some("{Damage:5}".to_string()),
+            })
+        );
+        assert_eq!(serde_json::to_value(&event).unwrap(), json);
+
+        let json = json!({"action": "show_item", "contents": {"id": "minecraft:stone"}});
+        let event = HoverEvent::deserialize(&json).unwrap();
+        assert_eq!(serde_json::to_value(&event).unwrap(), json);
+    }
+
+    #[test]
+    fn test_show_entity() {
+        let json = json!({
+            "action": "show_entity",
+            "contents": {
+                "type": "minecraft:pig",
+                "id": "6536bfed-8695-48fd-83a1-ecd24cf2a0fd",
+                "name": {"text": "Pig"}
+            }
+        });
+        let event = HoverEvent::deserialize(&json).unwrap();
+        let HoverEvent::ShowEntity(entity) = &event else {
+            panic!("expected show_entity, got {event:?}");
+        };
+        assert_eq!(entity.kind, "minecraft:pig");
+        assert_eq!(
+            entity.id,
+            Uuid::parse_str("6536bfed-8695-48fd-83a1-ecd24cf2a0fd").unwrap()
+        );
+        assert_eq!(serde_json::to_value(&event).unwrap(), json);
+
+        let int_array = json!({
+            "action": "show_entity",
+            "contents": {
+                "type": "minecraft:pig",
+                "id": [1698086893, -2037036803, -2086540078, 1290969341]
+            }
+        });
+        let HoverEvent::ShowEntity(int_array_entity) = HoverEvent::deserialize(&int_array).unwrap()
+        else {
+            panic!("expected show_entity");
+        };
+        assert_eq!(int_array_entity.id, entity.id);
+        assert_eq!(int_array_entity.name, None);
+    }
 }
@@ -0,0 +1,184 @@
+//! Render a [`FormattedText`] as HTML.
+
+use crate::{events::ClickAction, style::Style, FormattedText};
+use std::fmt::Write;
+
+impl FormattedText {
+    /// Convert this component into HTML, with inline styles for the colors
+    /// and formatting and links for `open_url` click events.
+    ///
+    /// The text is escaped, so it's safe to put the result directly in a page.
+    /// Obfuscated text has no CSS equivalent, so it's shown like normal text.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use azalea_chat::FormattedText;
+    /// use serde::de::Deserialize;
+    ///
+    /// let component = FormattedText::deserialize(&serde_json::json!({
+    ///    "text": "<hi>",
+    ///    "color": "red",
+    ///    "bold": true,
+    /// })).unwrap();
+    ///
+    /// assert_eq!(
+    ///     component.to_html(),
+    ///     r#"<span style="color: #ff5555; font-weight: bold">&lt;hi&gt;</span>"#
+    /// );
+    /// ```
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        self.visit(|text, style| {
+            if text.is_empty() {
+                return;
+            }
+
+            let url = style
+                .click_event
+                .as_ref()
+                .filter(|event| event.action == ClickAction::OpenUrl)
+                .map(|event| event.value.as_str())
+                .filter(|url| is_safe_url(url));
+            if let Some(url) = url {
+                write!(html, "<a href=\"{}\">", escape(url)).unwrap();
+            }
+
+            let css = css(style);
+            if css.is_empty() {
+                html.push_str(&escape(text));
+            } else {
+                write!(html, "<span style=\"{css}\">{}</span>", escape(text)).unwrap();
+            }
+
+            if url.is_some() {
+                html.push_str("</a>");
+            }
+        });
+        html
+    }
+}
+
+/// The inline CSS for a style, like `color: #ff5555; font-weight: bold`.
+fn css(style: &Style) -> String {
+    let mut properties = Vec::new();
+    if let Some(color) = &style.color {
+        properties.push(format!("color: #{:06x}", color.value));
+    }
+    if style.bold == Some(true) {
+        properties.push("font-weight: bold".to_string());
+    }
+    if style.italic == Some(true) {
+        properties.push("font-style: italic".to_string());
+    }
+
+    let mut decorations = Vec::new();
+    if style.underlined == Some(true) {
+        decorations.push("underline");
+    }
+    if style.strikethrough == Some(true) {
+        decorations.push("line-through");
+    }
+    if !decorations.is_empty() {
+        properties.push(format!("text-decoration: {}", decorations.join(" ")));
+    }
+
+    properties.join("; ")
+}
+
+/// Only link to http and https urls, like vanilla. Anything else (like
+/// `javascript:`) could do more than open a page.
+fn is_safe_url(url: &str) -> bool {
+    let url = url.to_ascii_lowercase();
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    fn deserialize(json: serde_json::Value) -> FormattedText {
+        FormattedText::deserialize(&json).unwrap()
+    }
+
+    #[test]
+    fn test_plain_text() {
+        assert_eq!(FormattedText::from("hello").to_html(), "hello");
+        assert_eq!(
+            FormattedText::from("<script>alert('&')</script>").to_html(),
+            "&lt;script&gt;alert(&#39;&amp;&#39;)&lt;/script&gt;"
+        );
+    }
+
+    #[test]
+    fn test_inherited_styles() {
+        let component = deserialize(json!({
+            "text": "a",
+            "color": "#abcdef",
+            "underlined": true,
+            "extra": [
+                {"text": "b", "italic": true, "strikethrough": true},
+                {"text": "c", "underlined": false, "color": "gold"}
+            ]
+        }));
+        assert_eq!(
+            component.to_html(),
+            concat!(
+                r#"<span style="color: #abcdef; text-decoration: underline">a</span>"#,
+                r#"<span style="color: #abcdef; font-style: italic; text-decoration: underline line-through">b</span>"#,
+                r#"<span style="color: #ffaa00">c</span>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_links() {
+        let component = deserialize(json!([
+            {
+                "text": "click",
+                "clickEvent": {"action": "open_url", "value": "https://example.com/?a=1&b=\"2\""}
+            },
+            {
+                "text": " not a link",
+                "clickEvent": {"action": "run_command", "value": "/help"}
+            },
+            {
+                "text": " unsafe",
+                "clickEvent": {"action": "open_url", "value": "javascript:alert(1)"}
+            }
+        ]));
+        assert_eq!(
+            component.to_html(),
+            r#"<a href="https://example.com/?a=1&amp;b=&quot;2&quot;">click</a> not a link unsafe"#
+        );
+    }
+
+    #[test]
+    fn test_translation() {
+        let component = deserialize(json!({
+            "translate": "chat.type.text",
+            "with": [{"text": "Steve", "color": "yellow"}, "<3"]
+        }));
+        assert_eq!(
+            component.to_html(),
+            r#"&lt;<span style="color: #ffff55">Steve</span>&gt; &lt;3"#
+        );
+    }
+}
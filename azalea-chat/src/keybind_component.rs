@@ -0,0 +1,167 @@
+use crate::{base_component::BaseComponent, FormattedText};
+use azalea_language::Language;
+use serde::{ser::SerializeMap, Serialize, Serializer, __private::ser::FlatMapSerializer};
+use std::fmt::Display;
+
+/// A component that shows the key that's bound to something, like
+/// `key.jump`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeybindComponent {
+    pub base: BaseComponent,
+    pub key: String,
+}
+
+impl Serialize for KeybindComponent {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_map(None)?;
+        state.serialize_entry("keybind", &self.key)?;
+        Serialize::serialize(&self.base, FlatMapSerializer(&mut state))?;
+        if !self.base.siblings.is_empty() {
+            state.serialize_entry("extra", &self.base.siblings)?;
+        }
+        state.end()
+    }
+}
+
+/// The key that each keybind is bound to by default.
+fn default_key(keybind: &str) -> Option<&'static str> {
+    Some(match keybind {
+        "key.attack" => "key.mouse.left",
+        "key.use" => "key.mouse.right",
+        "key.forward" => "key.keyboard.w",
+        "key.left" => "key.keyboard.a",
+        "key.back" => "key.keyboard.s",
+        "key.right" => "key.keyboard.d",
+        "key.jump" => "key.keyboard.space",
+        "key.sneak" => "key.keyboard.left.shift",
+        "key.sprint" => "key.keyboard.left.control",
+        "key.drop" => "key.keyboard.q",
+        "key.inventory" => "key.keyboard.e",
+        "key.chat" => "key.keyboard.t",
+        "key.playerlist" => "key.keyboard.tab",
+        "key.pickItem" => "key.mouse.middle",
+        "key.command" => "key.keyboard.slash",
+        "key.socialInteractions" => "key.keyboard.p",
+        "key.screenshot" => "key.keyboard.f2",
+        "key.togglePerspective" => "key.keyboard.f5",
+        "key.smoothCamera" => "key.keyboard.unknown",
+        "key.fullscreen" => "key.keyboard.f11",
+        "key.spectatorOutlines" => "key.keyboard.unknown",
+        "key.swapOffhand" => "key.keyboard.f",
+        "key.saveToolbarActivator" => "key.keyboard.c",
+        "key.loadToolbarActivator" => "key.keyboard.x",
+        "key.advancements" => "key.keyboard.l",
+        "key.hotbar.1" => "key.keyboard.1",
+        "key.hotbar.2" => "key.keyboard.2",
+        "key.hotbar.3" => "key.keyboard.3",
+        "key.hotbar.4" => "key.keyboard.4",
+        "key.hotbar.5" => "key.keyboard.5",
+        "key.hotbar.6" => "key.keyboard.6",
+        "key.hotbar.7" => "key.keyboard.7",
+        "key.hotbar.8" => "key.keyboard.8",
+        "key.hotbar.9" => "key.keyboard.9",
+        _ => return None,
+    })
+}
+
+impl KeybindComponent {
+    pub fn new(key: String) -> Self {
+        Self {
+            base: BaseComponent::new(),
+            key,
+        }
+    }
+
+    /// The text that's shown for this component, without its siblings. This
+    /// is the name of the key that the keybind is bound to by default, like
+    /// "Space" for `key.jump`, in the
+    /// [default language](azalea_language::default_language).
+    pub fn contents(&self) -> String {
+        self.contents_with_language(&azalea_language::default_language())
+    }
+
+    /// Like [`KeybindComponent::contents`], but the key name is translated
+    /// with the given [`Language`].
+    pub fn contents_with_language(&self, language: &Language) -> String {
+        let Some(key) = default_key(&self.key) else {
+            // vanilla translates the keybind itself if it doesn't exist
+            return language.get(&self.key).unwrap_or(&self.key).to_string();
+        };
+        if let Some(name) = language.get(key) {
+            return name.to_string();
+        }
+        // letters and numbers aren't in the language file, the game gets
+        // their names from the keyboard layout
+        key.trim_start_matches("key.keyboard.").to_uppercase()
+    }
+}
+
+impl Display for KeybindComponent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        FormattedText::Keybind(self.clone()).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keybind_contents() {
+        assert_eq!(
+            KeybindComponent::new("key.jump".to_string()).contents(),
+            "Space"
+        );
+        assert_eq!(
+            KeybindComponent::new("key.forward".to_string()).contents(),
+            "W"
+        );
+        assert_eq!(
+            KeybindComponent::new("key.sneak".to_string()).contents(),
+            "Left Shift"
+        );
+        assert_eq!(
+            KeybindComponent::new("key.smoothCamera".to_string()).contents(),
+            "Not bound"
+        );
+        // keybinds that we don't know about are translated
+        assert_eq!(
+            KeybindComponent::new("key.categories.movement".to_string()).contents(),
+            "Movement"
+        );
+        assert_eq!(
+            KeybindComponent::new("key.mymod.dance".to_string()).contents(),
+            "key.mymod.dance"
+        );
+    }
+
+    #[test]
+    fn test_keybind_contents_with_language() {
+        let german = Language::from_bytes(
+            br#"{"key.keyboard.space": "Leertaste", "key.categories.movement": "Bewegung"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            KeybindComponent::new("key.jump".to_string()).contents_with_language(&german),
+            "Leertaste"
+        );
+        assert_eq!(
+            KeybindComponent::new("key.categories.movement".to_string())
+                .contents_with_language(&german),
+            "Bewegung"
+        );
+        // missing keys still fall back to en_us
+        assert_eq!(
+            KeybindComponent::new("key.sneak".to_string()).contents_with_language(&german),
+            "Left Shift"
+        );
+        assert_eq!(
+            FormattedText::Keybind(KeybindComponent::new("key.jump".to_string()))
+                .to_plain_with_language(&german),
+            "Leertaste"
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//! Convert between [`FormattedText`] and strings with legacy formatting codes,
+//! like `§aHello §lworld`.
+//!
+//! Colors reset the bold, italic, underlined, strikethrough and obfuscated
+//! formatting like they do in vanilla, and `§r` resets everything. Hex colors
+//! use the `§x§r§r§g§g§b§b` format from Spigot and BungeeCord.
+
+use crate::{
+    base_component::BaseComponent,
+    style::{ChatFormatting, Style, TextColor},
+    text_component::TextComponent,
+    FormattedText,
+};
+
+/// The symbol that vanilla uses for formatting codes.
+pub const LEGACY_FORMATTING_CODE_SYMBOL: char = '§';
+
+impl FormattedText {
+    /// Parse a string with legacy formatting codes that start with `symbol`.
+    /// This is usually `§`, but some plugins use `&`.
+    ///
+    /// ```
+    /// use azalea_chat::FormattedText;
+    ///
+    /// let text = FormattedText::from_legacy("&aHello &lworld", '&');
+    /// assert_eq!(text.to_string(), "Hello world");
+    /// assert_eq!(text.to_legacy(), "§aHello §lworld");
+    /// ```
+    pub fn from_legacy(legacy: &str, symbol: char) -> FormattedText {
+        FormattedText::Text(parse_legacy(legacy, symbol))
+    }
+
+    /// Convert this into a string with `§` formatting codes.
+    ///
+    /// Click events, hover events and fonts can't be represented with
+    /// formatting codes, so they're lost.
+    pub fn to_legacy(&self) -> String {
+        self.to_legacy_with_symbol(LEGACY_FORMATTING_CODE_SYMBOL)
+    }
+
+    /// Convert this into a string with formatting codes that start with
+    /// `symbol`, see [`FormattedText::to_legacy`].
+    pub fn to_legacy_with_symbol(&self, symbol: char) -> String {
+        let mut legacy = String::new();
+        // the color and formatting that the text we've written so far has
+        let mut last_style = Style::default();
+        self.visit(|text, style| {
+            if text.is_empty() {
+                return;
+            }
+
+            let color_changed =
+                style.color.as_ref().map(|c| c.value) != last_style.color.as_ref().map(|c| c.value);
+            let removed_formatting = decorations(&last_style)
+                .iter()
+                .any(|formatting| !decorations(style).contains(formatting));
+
+            if color_changed || removed_formatting {
+                // colors reset formatting, so it has to be written again
+                match &style.color {
+                    Some(color) => push_color(&mut legacy, symbol, color),
+                    None => push_code(&mut legacy, symbol, ChatFormatting::Reset.code()),
+                }
+                for formatting in decorations(style) {
+                    push_code(&mut legacy, symbol, formatting.code());
+                }
+            } else {
+                for formatting in decorations(style) {
+                    if !decorations(&last_style).contains(&formatting) {
+                        push_code(&mut legacy, symbol, formatting.code());
+                    }
+                }
+            }
+
+            legacy.push_str(text);
+            last_style = style.clone();
+        });
+        legacy
+    }
+}
+
+/// Parse a legacy string into a component. If there's more than one style,
+/// the parts are siblings of an empty component so they don't inherit each
+/// other's styles.
+pub(crate) fn parse_legacy(legacy: &str, symbol: char) -> TextComponent {
+    let mut components = Vec::new();
+    let mut text = String::new();
+    let mut style = Style::default();
+
+    let chars = legacy.chars().collect::<Vec<_>>();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] != symbol {
+            text.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        // a symbol at the end is ignored
+        let Some(code) = chars.get(i + 1).map(|c| c.to_ascii_lowercase()) else {
+            break;
+        };
+
+        let new_style = if code == 'x' {
+            parse_hex_color(&chars[i + 2..], symbol).map(|color| {
+                i += 12;
+                color_style(color)
+            })
+        } else {
+            ChatFormatting::from_code(code).map(|formatting| match formatting {
+                ChatFormatting::Reset => Style {
+                    reset: true,
+                    ..Style::default()
+                },
+                formatting if formatting.is_format() => {
+                    let mut style = style.clone();
+                    style.apply_formatting(&formatting);
+                    style
+                }
+                formatting => color_style(formatting.try_into().unwrap()),
+            })
+        };
+        // unknown codes are skipped
+        if let Some(new_style) = new_style {
+            if !text.is_empty() {
+                components.push(segment(std::mem::take(&mut text), style));
+            }
+            style = new_style;
+        }
+        i += 2;
+    }
+    if !text.is_empty() || components.is_empty() {
+        components.push(segment(text, style));
+    }
+
+    if components.len() == 1 {
+        return components.remove(0);
+    }
+    TextComponent {
+        base: BaseComponent {
+            siblings: components.into_iter().map(TextComponent::get).collect(),
+            style: Style::default(),
+        },
+        text: String::new(),
+    }
+}
+
+fn segment(text: String, style: Style) -> TextComponent {
+    TextComponent {
+        base: BaseComponent {
+            siblings: Vec::new(),
+            style,
+        },
+        text,
+    }
+}
+
+/// The style after a color code, which resets formatting.
+fn color_style(color: TextColor) -> Style {
+    Style {
+        color: Some(color),
+        bold: Some(false),
+        italic: Some(false),
+        underlined: Some(false),
+        strikethrough: Some(false),
+        obfuscated: Some(false),
+        ..Style::default()
+    }
+}
+
+/// Parse the `§r§r§g§g§b§b` after a `§x`.
+fn parse_hex_color(chars: &[char], symbol: char) -> Option<TextColor> {
+    let chars = chars.get(..12)?;
+    let mut value = 0;
+    for pair in chars.chunks(2) {
+        if pair[0] != symbol {
+            return None;
+        }
+        value = value * 16 + pair[1].to_digit(16)?;
+    }
+    TextColor::parse(format!("#{value:06X}"))
+}
+
+fn push_code(legacy: &mut String, symbol: char, code: char) {
+    legacy.push(symbol);
+    legacy.push(code);
+}
+
+fn push_color(legacy: &mut String, symbol: char, color: &TextColor) {
+    let named = ChatFormatting::FORMATTERS
+        .iter()
+        .find(|formatting| formatting.color() == Some(color.value));
+    if let Some(formatting) = named {
+        push_code(legacy, symbol, formatting.code());
+        return;
+    }
+    push_code(legacy, symbol, 'x');
+    for digit in format!("{:06x}", color.value).chars() {
+        push_code(legacy, symbol, digit);
+    }
+}
+
+/// The formatting codes that are enabled in a style.
+fn decorations(style: &Style) -> Vec<ChatFormatting> {
+    [
+        (style.obfuscated, ChatFormatting::Obfuscated),
+        (style.bold, ChatFormatting::Bold),
+        (style.strikethrough, ChatFormatting::Strikethrough),
+        (style.underlined, ChatFormatting::Underline),
+        (style.italic, ChatFormatting::Italic),
+    ]
+    .into_iter()
+    .filter(|(enabled, _)| enabled.unwrap_or(false))
+    .map(|(_, formatting)| formatting)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn color(formatting: ChatFormatting) -> Option<TextColor> {
+        Some(TextColor::parse(formatting.name().to_string()).unwrap())
+    }
+
+    #[test]
+    fn test_colors_reset_formatting() {
+        let FormattedText::Text(text) = FormattedText::from_legacy("§lA§cB§oC", '§') else {
+            unreachable!()
+        };
+        let styles = text
+            .base
+            .siblings
+            .iter()
+            .map(|sibling| sibling.get_base().style.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(styles[0].bold, Some(true));
+        assert_eq!(styles[1].color, color(ChatFormatting::Red));
+        assert_eq!(styles[1].bold, Some(false));
+        assert_eq!(styles[2].color, color(ChatFormatting::Red));
+        assert_eq!(styles[2].italic, Some(true));
+    }
+
+    #[test]
+    fn test_single_style() {
+        let text = FormattedText::from_legacy("§aHi", '§');
+        assert_eq!(text.get_base().siblings.len(), 0);
+        assert_eq!(text.get_base().style.color, color(ChatFormatting::Green));
+        assert_eq!(text.to_string(), "Hi");
+    }
+
+    #[test]
+    fn test_unknown_codes() {
+        // like vanilla, unknown codes and a symbol at the end are removed
+        assert_eq!(FormattedText::from_legacy("a§zb§", '§').to_string(), "ab");
+        assert_eq!(FormattedText::from_legacy("§Ab", '§').to_legacy(), "§ab");
+        assert_eq!(FormattedText::from_legacy("", '§').to_string(), "");
+    }
+
+    #[test]
+    fn test_ampersand() {
+        let text = FormattedText::from_legacy("&cred, &lbold", '&');
+        assert_eq!(text.to_string(), "red, bold");
+        assert_eq!(text.to_legacy(), "§cred, §lbold");
+        assert_eq!(text.to_legacy_with_symbol('&'), "&cred, &lbold");
+    }
+
+    #[test]
+    fn test_hex_color() {
+        let text = FormattedText::from_legacy("§x§F§F§8§0§0§0orange§x§1§2invalid", '§');
+        let orange = text.get_base().siblings[0].get_base().style.color.clone();
+        assert_eq!(orange.unwrap().value, 0xff8000);
+        // an incomplete hex color is an unknown code followed by normal codes
+        assert_eq!(text.to_string(), "orangeinvalid");
+        assert_eq!(text.to_legacy(), "§x§f§f§8§0§0§0orange§2invalid");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for legacy in [
+            "§aHello §lworld",
+            "§aHypixel Network  §c[1.8-1.18]\n§b§lHAPPY HOLIDAYS",
+            "§lbold§r plain §x§1§2§3§4§5§6hex",
+            "plain §othen italic",
+        ] {
+            assert_eq!(FormattedText::from_legacy(legacy, '§').to_legacy(), legacy);
+        }
+    }
+
+    #[test]
+    fn test_to_legacy_inherits_styles() {
+        let text: FormattedText = serde::Deserialize::deserialize(&serde_json::json!({
+            "text": "a",
+            "color": "red",
+            "bold": true,
+            "extra": [{"text": "b", "bold": false}, {"text": "c", "color": "#123456"}]
+        }))
+        .unwrap();
+        assert_eq!(text.to_legacy(), "§c§la§cb§x§1§2§3§4§5§6§lc");
+    }
+}
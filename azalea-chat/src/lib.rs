@@ -1,7 +1,15 @@
 #![doc = include_str!("../README.md")]
 
 pub mod base_component;
+pub mod builder;
 mod component;
+pub mod events;
+mod html;
+pub mod keybind_component;
+pub mod legacy;
+pub mod nbt_component;
+pub mod score_component;
+pub mod selector_component;
 pub mod style;
 pub mod text_component;
 pub mod translatable_component;
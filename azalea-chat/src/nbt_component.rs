@@ -0,0 +1,94 @@
+use crate::{base_component::BaseComponent, FormattedText};
+use serde::{ser::SerializeMap, Serialize, Serializer, __private::ser::FlatMapSerializer};
+use std::fmt::Display;
+
+/// Where an [`NbtComponent`] gets its NBT from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NbtSource {
+    /// The coordinates of a block entity, like `~ ~-1 ~`.
+    Block(String),
+    /// A selector for an entity, like `@s`.
+    Entity(String),
+    /// The resource location of command storage.
+    Storage(String),
+}
+
+impl NbtSource {
+    /// The name of the field in JSON that has the source.
+    pub fn field_name(&self) -> &'static str {
+        match self {
+            NbtSource::Block(_) => "block",
+            NbtSource::Entity(_) => "entity",
+            NbtSource::Storage(_) => "storage",
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        match self {
+            NbtSource::Block(value) | NbtSource::Entity(value) | NbtSource::Storage(value) => value,
+        }
+    }
+}
+
+/// A component that shows NBT from a block entity, entity or command
+/// storage.
+///
+/// These are resolved by the server like
+/// [`ScoreComponent`](crate::score_component::ScoreComponent)s are.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NbtComponent {
+    pub base: BaseComponent,
+    /// The NBT path, like `Inventory[0].id`.
+    pub path: String,
+    /// Whether the NBT should be parsed as a component instead of shown as
+    /// SNBT.
+    pub interpret: bool,
+    /// The component that's put between the values if the path matches more
+    /// than one. This defaults to a comma.
+    pub separator: Option<Box<FormattedText>>,
+    pub source: NbtSource,
+}
+
+impl Serialize for NbtComponent {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_map(None)?;
+        state.serialize_entry("nbt", &self.path)?;
+        state.serialize_entry("interpret", &self.interpret)?;
+        if let Some(separator) = &self.separator {
+            state.serialize_entry("separator", separator)?;
+        }
+        state.serialize_entry(self.source.field_name(), self.source.value())?;
+        Serialize::serialize(&self.base, FlatMapSerializer(&mut state))?;
+        if !self.base.siblings.is_empty() {
+            state.serialize_entry("extra", &self.base.siblings)?;
+        }
+        state.end()
+    }
+}
+
+impl NbtComponent {
+    pub fn new(path: String, source: NbtSource) -> Self {
+        Self {
+            base: BaseComponent::new(),
+            path,
+            interpret: false,
+            separator: None,
+            source,
+        }
+    }
+
+    /// The text that's shown for this component, without its siblings. We
+    /// can't look up the NBT, so this is the path.
+    pub fn contents(&self) -> String {
+        self.path.clone()
+    }
+}
+
+impl Display for NbtComponent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        FormattedText::Nbt(self.clone()).fmt(f)
+    }
+}
@@ -0,0 +1,73 @@
+use crate::{base_component::BaseComponent, FormattedText};
+use serde::{ser::SerializeMap, Serialize, Serializer, __private::ser::FlatMapSerializer};
+use std::fmt::Display;
+
+/// A component that shows an entity's score in an objective.
+///
+/// The server replaces these with the score before sending them, so clients
+/// usually only see them in places that aren't resolved, like signs that were
+/// placed with commands.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScoreComponent {
+    pub base: BaseComponent,
+    /// The name of the player or entity, or a selector like `@p`.
+    pub name: String,
+    pub objective: String,
+    /// The resolved score. This was removed from vanilla in 1.19, but some
+    /// servers still send it.
+    pub value: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ScoreContents<'a> {
+    name: &'a str,
+    objective: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: &'a Option<String>,
+}
+
+impl Serialize for ScoreComponent {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_map(None)?;
+        state.serialize_entry(
+            "score",
+            &ScoreContents {
+                name: &self.name,
+                objective: &self.objective,
+                value: &self.value,
+            },
+        )?;
+        Serialize::serialize(&self.base, FlatMapSerializer(&mut state))?;
+        if !self.base.siblings.is_empty() {
+            state.serialize_entry("extra", &self.base.siblings)?;
+        }
+        state.end()
+    }
+}
+
+impl ScoreComponent {
+    pub fn new(name: String, objective: String) -> Self {
+        Self {
+            base: BaseComponent::new(),
+            name,
+            objective,
+            value: None,
+        }
+    }
+
+    /// The text that's shown for this component, without its siblings. This
+    /// is the value if the server sent one, and otherwise nothing like in
+    /// vanilla.
+    pub fn contents(&self) -> String {
+        self.value.clone().unwrap_or_default()
+    }
+}
+
+impl Display for ScoreComponent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        FormattedText::Score(self.clone()).fmt(f)
+    }
+}
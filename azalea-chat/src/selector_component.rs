@@ -0,0 +1,57 @@
+use crate::{base_component::BaseComponent, FormattedText};
+use serde::{ser::SerializeMap, Serialize, Serializer, __private::ser::FlatMapSerializer};
+use std::fmt::Display;
+
+/// A component that shows the names of the entities matched by a selector.
+///
+/// Like [`ScoreComponent`](crate::score_component::ScoreComponent), these are
+/// resolved by the server, so clients only see them where they weren't.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SelectorComponent {
+    pub base: BaseComponent,
+    /// The selector, like `@e[type=pig]`.
+    pub pattern: String,
+    /// The component that's put between the names. This defaults to a gray
+    /// comma.
+    pub separator: Option<Box<FormattedText>>,
+}
+
+impl Serialize for SelectorComponent {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_map(None)?;
+        state.serialize_entry("selector", &self.pattern)?;
+        if let Some(separator) = &self.separator {
+            state.serialize_entry("separator", separator)?;
+        }
+        Serialize::serialize(&self.base, FlatMapSerializer(&mut state))?;
+        if !self.base.siblings.is_empty() {
+            state.serialize_entry("extra", &self.base.siblings)?;
+        }
+        state.end()
+    }
+}
+
+impl SelectorComponent {
+    pub fn new(pattern: String, separator: Option<FormattedText>) -> Self {
+        Self {
+            base: BaseComponent::new(),
+            pattern,
+            separator: separator.map(Box::new),
+        }
+    }
+
+    /// The text that's shown for this component, without its siblings. We
+    /// can't resolve selectors, so this is the selector itself.
+    pub fn contents(&self) -> String {
+        self.pattern.clone()
+    }
+}
+
+impl Display for SelectorComponent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        FormattedText::Selector(self.clone()).fmt(f)
+    }
+}
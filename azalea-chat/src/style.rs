@@ -1,5 +1,6 @@
 use std::{collections::HashMap, fmt};
 
+use crate::events::{ClickEvent, HoverEvent};
 #[cfg(feature = "azalea-buf")]
 use azalea_buf::McBuf;
 use once_cell::sync::Lazy;
@@ -39,7 +40,7 @@ impl TextColor {
         None
     }
 
-    fn from_rgb(value: u32) -> TextColor {
+    pub fn from_rgb(value: u32) -> TextColor {
         TextColor { value, name: None }
     }
 }
@@ -299,6 +300,12 @@ pub struct Style {
     pub underlined: Option<bool>,
     pub strikethrough: Option<bool>,
     pub obfuscated: Option<bool>,
+    pub click_event: Option<ClickEvent>,
+    pub hover_event: Option<HoverEvent>,
+    /// Text that's inserted into the chat box when this is shift-clicked.
+    pub insertion: Option<String>,
+    /// The resource location of the font, like `minecraft:uniform`.
+    pub font: Option<String>,
     /// Whether formatting should be reset before applying these styles
     pub reset: bool,
 }
@@ -317,7 +324,10 @@ impl Serialize for Style {
                 + usize::from(self.underlined.is_some())
                 + usize::from(self.strikethrough.is_some())
                 + usize::from(self.obfuscated.is_some())
-        };
+        } + usize::from(self.click_event.is_some())
+            + usize::from(self.hover_event.is_some())
+            + usize::from(self.insertion.is_some())
+            + usize::from(self.font.is_some());
         let mut state = serializer.serialize_struct("Style", len)?;
         if let Some(color) = &self.color {
             state.serialize_field("color", color)?;
@@ -349,6 +359,18 @@ impl Serialize for Style {
         } else if self.reset {
             state.serialize_field("obfuscated", &false)?;
         }
+        if let Some(click_event) = &self.click_event {
+            state.serialize_field("clickEvent", click_event)?;
+        }
+        if let Some(hover_event) = &self.hover_event {
+            state.serialize_field("hoverEvent", hover_event)?;
+        }
+        if let Some(insertion) = &self.insertion {
+            state.serialize_field("insertion", insertion)?;
+        }
+        if let Some(font) = &self.font {
+            state.serialize_field("font", font)?;
+        }
         state.end()
     }
 }
@@ -370,6 +392,20 @@ impl Style {
                 .get("color")
                 .and_then(|v| v.as_str())
                 .and_then(|v| TextColor::parse(v.to_string()));
+            let click_event = json_object
+                .get("clickEvent")
+                .and_then(ClickEvent::deserialize);
+            let hover_event = json_object
+                .get("hoverEvent")
+                .and_then(HoverEvent::deserialize);
+            let insertion = json_object
+                .get("insertion")
+                .and_then(|v| v.as_str())
+                .map(|v| v.to_string());
+            let font = json_object
+                .get("font")
+                .and_then(|v| v.as_str())
+                .map(|v| v.to_string());
             Style {
                 color,
                 bold,
@@ -377,6 +413,10 @@ impl Style {
                 underlined,
                 strikethrough,
                 obfuscated,
+                click_event,
+                hover_event,
+                insertion,
+                font,
                 ..Style::default()
             }
         } else {
@@ -392,6 +432,10 @@ impl Style {
             && self.underlined.is_none()
             && self.strikethrough.is_none()
             && self.obfuscated.is_none()
+            && self.click_event.is_none()
+            && self.hover_event.is_none()
+            && self.insertion.is_none()
+            && self.font.is_none()
     }
 
     /// find the necessary ansi code to get from this style to another
@@ -486,6 +530,18 @@ impl Style {
         if let Some(obfuscated) = &style.obfuscated {
             self.obfuscated = Some(*obfuscated);
         }
+        if let Some(click_event) = &style.click_event {
+            self.click_event = Some(click_event.clone());
+        }
+        if let Some(hover_event) = &style.hover_event {
+            self.hover_event = Some(hover_event.clone());
+        }
+        if let Some(insertion) = &style.insertion {
+            self.insertion = Some(insertion.clone());
+        }
+        if let Some(font) = &style.font {
+            self.font = Some(font.clone());
+        }
     }
 
     /// Apply a ChatFormatting to this style
@@ -509,7 +565,7 @@ impl Style {
 
 #[cfg(test)]
 mod tests {
-    use crate::component::DEFAULT_STYLE;
+    use crate::{component::DEFAULT_STYLE, events::ClickAction};
 
     use super::*;
 
@@ -591,6 +647,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_events_round_trip() {
+        let json = serde_json::json!({
+            "color": "red",
+            "clickEvent": {"action": "suggest_command", "value": "/msg Steve "},
+            "hoverEvent": {"action": "show_text", "contents": {"text": "Click to reply"}},
+            "insertion": "Steve",
+            "font": "minecraft:uniform",
+        });
+        let style = Style::deserialize(&json);
+        assert_eq!(
+            style.click_event,
+            Some(ClickEvent::new(ClickAction::SuggestCommand, "/msg Steve "))
+        );
+        assert_eq!(style.insertion.as_deref(), Some("Steve"));
+        assert_eq!(style.font.as_deref(), Some("minecraft:uniform"));
+        assert_eq!(serde_json::to_value(&style).unwrap(), json);
+    }
+
+    #[test]
+    fn test_apply_inherits_events() {
+        let mut style = Style {
+            click_event: Some(ClickEvent::new(ClickAction::RunCommand, "/tpaccept")),
+            insertion: Some("a".to_string()),
+            ..Style::default()
+        };
+        style.apply(&Style {
+            insertion: Some("b".to_string()),
+            font: Some("minecraft:alt".to_string()),
+            ..Style::default()
+        });
+        assert_eq!(
+            style.click_event,
+            Some(ClickEvent::new(ClickAction::RunCommand, "/tpaccept"))
+        );
+        assert_eq!(style.insertion.as_deref(), Some("b"));
+        assert_eq!(style.font.as_deref(), Some("minecraft:alt"));
+    }
+
     #[test]
     fn test_apply_formatting() {
         let mut style = Style::default();
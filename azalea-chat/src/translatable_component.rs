@@ -3,8 +3,12 @@ use std::fmt::{self, Display, Formatter};
 use crate::{
     base_component::BaseComponent, style::Style, text_component::TextComponent, FormattedText,
 };
+use azalea_language::Language;
 use serde::{ser::SerializeMap, Serialize, Serializer, __private::ser::FlatMapSerializer};
 
+// styles with click and hover events make components much bigger than
+// strings, but boxing them would make matching on this annoying
+#[allow(clippy::large_enum_variant)]
 #[derive(Clone, Debug, PartialEq, Serialize)]
 #[serde(untagged)]
 pub enum StringOrComponent {
@@ -28,7 +32,12 @@ impl Serialize for TranslatableComponent {
         let mut state = serializer.serialize_map(None)?;
         state.serialize_entry("translate", &self.key)?;
         Serialize::serialize(&self.base, FlatMapSerializer(&mut state))?;
-        state.serialize_entry("with", &self.args)?;
+        if !self.args.is_empty() {
+            state.serialize_entry("with", &self.args)?;
+        }
+        if !self.base.siblings.is_empty() {
+            state.serialize_entry("extra", &self.base.siblings)?;
+        }
         state.end()
     }
 }
@@ -42,9 +51,24 @@ impl TranslatableComponent {
         }
     }
 
-    /// Convert the key and args to a FormattedText.
+    /// The translation of the key, or the key itself if there isn't one.
+    pub(crate) fn template<'a>(&'a self, language: &'a Language) -> &'a str {
+        language.get(&self.key).unwrap_or(&self.key)
+    }
+
+    /// Convert the key and args to a FormattedText, using the
+    /// [default language](azalea_language::default_language).
     pub fn read(&self) -> Result<TextComponent, fmt::Error> {
-        let template = azalea_language::get(&self.key).unwrap_or(&self.key);
+        self.read_with_language(&azalea_language::default_language())
+    }
+
+    /// Convert the key and args to a FormattedText with the translations from
+    /// a [`Language`].
+    ///
+    /// Translatable components in the arguments aren't translated yet, use
+    /// [`FormattedText::to_plain_with_language`] to translate everything.
+    pub fn read_with_language(&self, language: &Language) -> Result<TextComponent, fmt::Error> {
+        let template = self.template(language);
         // decode the % things
 
         let mut i = 0;
@@ -73,9 +97,9 @@ impl TranslatableComponent {
                             .cloned()
                             .unwrap_or_else(|| StringOrComponent::String("".to_string()));
 
-                        components.push(TextComponent::new(built_text.clone()));
+                        components.push(TextComponent::new(built_text.clone()).get());
                         built_text.clear();
-                        components.push(TextComponent::from(arg_component));
+                        components.push(FormattedText::from(arg_component));
                         matched += 1;
                     }
                     _ => {
@@ -85,13 +109,14 @@ impl TranslatableComponent {
                             if let Some('$') = template.chars().nth(i + 1) {
                                 if let Some('s') = template.chars().nth(i + 2) {
                                     i += 2;
-                                    built_text.push_str(
-                                        &self
-                                            .args
-                                            .get((d - 1) as usize)
-                                            .unwrap_or(&StringOrComponent::String("".to_string()))
-                                            .to_string(),
-                                    );
+                                    let arg_component =
+                                        self.args.get((d - 1) as usize).cloned().unwrap_or_else(
+                                            || StringOrComponent::String("".to_string()),
+                                        );
+
+                                    components.push(TextComponent::new(built_text.clone()).get());
+                                    built_text.clear();
+                                    components.push(FormattedText::from(arg_component));
                                 } else {
                                     return Err(fmt::Error);
                                 }
@@ -115,11 +140,11 @@ impl TranslatableComponent {
             return Ok(TextComponent::new(built_text));
         }
 
-        components.push(TextComponent::new(built_text));
+        components.push(TextComponent::new(built_text).get());
 
         Ok(TextComponent {
             base: BaseComponent {
-                siblings: components.into_iter().map(FormattedText::Text).collect(),
+                siblings: components,
                 style: Style::default(),
             },
             text: "".to_string(),
@@ -129,17 +154,7 @@ impl TranslatableComponent {
 
 impl Display for TranslatableComponent {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // this contains the final string will all the ansi escape codes
-        for component in FormattedText::Translatable(self.clone()).into_iter() {
-            let component_text = match &component {
-                FormattedText::Text(c) => c.text.to_string(),
-                FormattedText::Translatable(c) => c.read()?.to_string(),
-            };
-
-            f.write_str(&component_text)?;
-        }
-
-        Ok(())
+        FormattedText::Translatable(self.clone()).fmt(f)
     }
 }
 
@@ -152,6 +167,15 @@ impl Display for StringOrComponent {
     }
 }
 
+impl From<StringOrComponent> for FormattedText {
+    fn from(soc: StringOrComponent) -> Self {
+        match soc {
+            StringOrComponent::String(s) => TextComponent::new(s).get(),
+            StringOrComponent::FormattedText(c) => c,
+        }
+    }
+}
+
 impl From<StringOrComponent> for TextComponent {
     fn from(soc: StringOrComponent) -> Self {
         match soc {
@@ -227,4 +251,28 @@ mod tests {
         );
         assert_eq!(c.read().unwrap().to_string(), "hi %  s".to_string());
     }
+    #[test]
+    fn test_language() {
+        let language =
+            Language::from_bytes(br#"{"death.attack.fall": "%1$s fiel zu tief"}"#).unwrap();
+        let c = TranslatableComponent::new(
+            "death.attack.fall".to_string(),
+            vec![StringOrComponent::String("Steve".to_string())],
+        );
+        assert_eq!(
+            c.read_with_language(&language).unwrap().to_string(),
+            "Steve fiel zu tief"
+        );
+        assert_eq!(
+            c.read().unwrap().to_string(),
+            "Steve hit the ground too hard"
+        );
+
+        // keys that the language doesn't have fall back to en_us
+        let c = TranslatableComponent::new("translation.test.none".to_string(), vec![]);
+        assert_eq!(
+            c.read_with_language(&language).unwrap().to_string(),
+            "Hello, world!"
+        );
+    }
 }
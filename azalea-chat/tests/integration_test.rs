@@ -73,3 +73,131 @@ fn component_from_string() {
     let component = FormattedText::deserialize(&j).unwrap();
     assert_eq!(component.to_ansi(), "foo");
 }
+
+#[test]
+fn score_component() {
+    let j: Value = serde_json::from_str(
+        r#"{"score": {"name": "Steve", "objective": "kills"}, "color": "red", "extra": [" kills"]}"#,
+    )
+    .unwrap();
+    let component = FormattedText::deserialize(&j).unwrap();
+    // scores are resolved by the server, so there's nothing to show
+    assert_eq!(component.to_string(), " kills");
+    assert_eq!(
+        serde_json::to_value(&component).unwrap()["score"],
+        serde_json::json!({"name": "Steve", "objective": "kills"})
+    );
+
+    let j: Value =
+        serde_json::from_str(r#"{"score": {"name": "Steve", "objective": "kills", "value": "3"}}"#)
+            .unwrap();
+    assert_eq!(FormattedText::deserialize(&j).unwrap().to_string(), "3");
+
+    let j: Value = serde_json::from_str(r#"{"score": {"name": "Steve"}}"#).unwrap();
+    assert!(FormattedText::deserialize(&j).is_err());
+}
+
+#[test]
+fn selector_component() {
+    let j: Value =
+        serde_json::from_str(r#"{"selector": "@e[type=pig]", "separator": {"text": " | "}}"#)
+            .unwrap();
+    let component = FormattedText::deserialize(&j).unwrap();
+    assert_eq!(component.to_string(), "@e[type=pig]");
+    assert_eq!(serde_json::to_value(&component).unwrap(), j);
+}
+
+#[test]
+fn keybind_component() {
+    let j: Value =
+        serde_json::from_str(r#"["Press ", {"keybind": "key.jump", "bold": true}]"#).unwrap();
+    let component = FormattedText::deserialize(&j).unwrap();
+    assert_eq!(component.to_string(), "Press Space");
+    assert_eq!(
+        component.to_ansi(),
+        format!(
+            "Press {bold}Space{reset}",
+            bold = Ansi::BOLD,
+            reset = Ansi::RESET
+        )
+    );
+}
+
+#[test]
+fn nbt_component() {
+    let j: Value =
+        serde_json::from_str(r#"{"nbt": "Inventory[0].id", "interpret": false, "entity": "@s"}"#)
+            .unwrap();
+    let component = FormattedText::deserialize(&j).unwrap();
+    assert_eq!(component.to_string(), "Inventory[0].id");
+    assert_eq!(serde_json::to_value(&component).unwrap(), j);
+
+    let j: Value = serde_json::from_str(r#"{"nbt": "Items"}"#).unwrap();
+    assert!(
+        FormattedText::deserialize(&j).is_err(),
+        "nbt components need a block, entity or storage"
+    );
+}
+
+#[test]
+fn unresolved_component_in_sibling() {
+    // one of these used to make the whole message fail to parse
+    let j: Value = serde_json::from_str(
+        r#"{"text": "Score: ", "extra": [{"score": {"name": "*", "objective": "o"}}]}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        FormattedText::deserialize(&j).unwrap().to_string(),
+        "Score: "
+    );
+}
+
+#[test]
+fn to_plain_resolves_translations() {
+    let j: Value = serde_json::from_str(
+        r#"{
+    "translate": "chat.type.advancement.task",
+    "with": [
+        {"text": "Steve", "color": "yellow"},
+        {"translate": "advancements.story.mine_stone.title", "color": "green"}
+    ],
+    "extra": ["!"]
+}"#,
+    )
+    .unwrap();
+    let component = FormattedText::deserialize(&j).unwrap();
+    assert_eq!(
+        component.to_plain(),
+        "Steve has made the advancement Stone Age!"
+    );
+    assert_eq!(component.to_plain(), component.to_string());
+
+    // invalid translations are shown untranslated instead of failing
+    let j: Value = serde_json::from_str(r#"{"translate": "100%1 sure", "with": ["a"]}"#).unwrap();
+    let component = FormattedText::deserialize(&j).unwrap();
+    assert_eq!(component.to_plain(), "100%1 sure");
+}
+
+#[test]
+fn translation_arguments_keep_their_styles() {
+    let j: Value = serde_json::from_str(
+        r#"{"translate": "chat.type.text", "with": [{"text": "Steve", "color": "yellow"}, "hi"]}"#,
+    )
+    .unwrap();
+    let component = FormattedText::deserialize(&j).unwrap();
+    let mut styled = Vec::new();
+    component.visit(|text, style| {
+        if !text.is_empty() {
+            styled.push((text.to_string(), style.color.as_ref().map(|c| c.value)));
+        }
+    });
+    assert_eq!(
+        styled,
+        vec![
+            ("<".to_string(), None),
+            ("Steve".to_string(), ChatFormatting::Yellow.color()),
+            ("> ".to_string(), None),
+            ("hi".to_string(), None),
+        ]
+    );
+}
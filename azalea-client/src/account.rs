@@ -3,6 +3,8 @@
 use std::sync::Arc;
 
 use crate::get_mc_dir;
+use azalea_auth::certs::{Certificates, FetchCertificatesError};
+use azalea_protocol::proxy::Proxy;
 use parking_lot::Mutex;
 use uuid::Uuid;
 
@@ -42,6 +44,15 @@ pub struct Account {
     /// "Invalid Session" errors. If you don't need that feature (like in
     /// offline mode), then you can set this to `AuthOpts::default()`.
     pub auth_opts: AuthOpts,
+
+    /// The proxy that this account connects to servers through, if any. Set
+    /// this with [`Self::with_proxy`].
+    pub proxy: Option<Proxy>,
+
+    /// The key pair that chat messages are signed with. This is fetched when
+    /// joining a server if the account is online-mode, and shared between
+    /// clones of the account.
+    pub certs: Arc<Mutex<Option<Certificates>>>,
 }
 
 /// The parameters that were passed for creating the associated [`Account`].
@@ -64,6 +75,8 @@ impl Account {
             auth_opts: AuthOpts::Offline {
                 username: username.to_string(),
             },
+            proxy: None,
+            certs: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -93,9 +106,40 @@ impl Account {
             auth_opts: AuthOpts::Microsoft {
                 email: email.to_string(),
             },
+            proxy: None,
+            certs: Arc::new(Mutex::new(None)),
         })
     }
 
+    /// Connect to servers through the given proxy. This is useful for swarms,
+    /// since each account can have its own proxy.
+    ///
+    /// ```rust,no_run
+    /// use azalea_client::Account;
+    /// use azalea_protocol::proxy::Proxy;
+    ///
+    /// let account = Account::offline("bot")
+    ///     .with_proxy(Proxy::socks5("127.0.0.1:1080".parse().unwrap()));
+    /// ```
+    #[must_use]
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Fetch a new key pair for signing chat messages, replacing the one in
+    /// [`Self::certs`]. Offline accounts can't sign messages, so this doesn't
+    /// do anything for them.
+    pub async fn request_certs(&self) -> Result<(), FetchCertificatesError> {
+        let Some(access_token) = &self.access_token else {
+            return Ok(());
+        };
+        let access_token = access_token.lock().clone();
+        let certs = azalea_auth::certs::fetch_certificates(&access_token).await?;
+        *self.certs.lock() = Some(certs);
+        Ok(())
+    }
+
     /// Refresh the access_token for this account to be valid again.
     ///
     /// This requires the `auth_opts` field to be set correctly (which is done
@@ -1,19 +1,28 @@
 //! Implementations of chat-related features.
 
+use azalea_auth::certs::Certificates;
 use azalea_chat::FormattedText;
+use azalea_core::FixedBitSet;
+use azalea_crypto::{MessageSignature, RsaPublicKey, SignedMessageBody, SignedMessageLink};
 use azalea_protocol::packets::game::{
-    clientbound_player_chat_packet::ClientboundPlayerChatPacket,
+    clientbound_player_chat_packet::{ClientboundPlayerChatPacket, PackedMessageSignature},
     clientbound_system_chat_packet::ClientboundSystemChatPacket,
-    serverbound_chat_command_packet::ServerboundChatCommandPacket,
+    serverbound_chat_command_packet::{ArgumentSignature, ServerboundChatCommandPacket},
     serverbound_chat_packet::{LastSeenMessagesUpdate, ServerboundChatPacket},
+    serverbound_chat_session_update_packet::{ProfilePublicKeyData, RemoteChatSessionData},
 };
 use bevy_app::{App, Plugin};
 use bevy_ecs::{
+    component::Component,
     entity::Entity,
     event::{EventReader, EventWriter},
     schedule::{IntoSystemConfig, IntoSystemConfigs},
+    system::Query,
 };
+use derive_more::Deref;
+use log::warn;
 use std::{
+    collections::{HashMap, VecDeque},
     sync::Arc,
     time::{SystemTime, UNIX_EPOCH},
 };
@@ -21,6 +30,7 @@ use uuid::Uuid;
 
 use crate::{
     client::Client,
+    commands::CommandTree,
     local_player::{handle_send_packet_event, SendPacketEvent},
 };
 
@@ -28,7 +38,33 @@ use crate::{
 #[derive(Debug, Clone, PartialEq)]
 pub enum ChatPacket {
     System(Arc<ClientboundSystemChatPacket>),
-    Player(Arc<ClientboundPlayerChatPacket>),
+    Player(Arc<PlayerChatPacket>),
+}
+
+/// A player chat packet, along with whether it was really sent by the player
+/// it says it's from. This derefs to the [`ClientboundPlayerChatPacket`].
+#[derive(Debug, Clone, PartialEq, Deref)]
+pub struct PlayerChatPacket {
+    #[deref]
+    pub packet: ClientboundPlayerChatPacket,
+    /// See [`ChatPacket::verification`].
+    pub verification: ChatVerification,
+}
+
+/// Whether a chat message was really sent by the player it says it's from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatVerification {
+    /// The message was signed by the sender and the server didn't change it.
+    Verified,
+    /// The message wasn't signed, or the sender doesn't have a chat session
+    /// that we could check it with. System messages are always unsigned.
+    Unsigned,
+    /// The message was signed by the sender, but the server changed what's
+    /// shown in chat.
+    Modified,
+    /// The signature is wrong or the message doesn't follow the sender's
+    /// previous message, so the message was forged or tampered with.
+    Invalid,
 }
 
 macro_rules! regex {
@@ -93,6 +129,25 @@ impl ChatPacket {
         }
     }
 
+    /// Whether the message was really sent by the player it says it's from.
+    /// This is checked when the message is received, so it depends on the
+    /// messages that the player sent before it.
+    pub fn verification(&self) -> ChatVerification {
+        match self {
+            ChatPacket::System(_) => ChatVerification::Unsigned,
+            ChatPacket::Player(m) => m.verification,
+        }
+    }
+
+    /// The signature of the message, if it's a signed player chat message.
+    /// The server uses this to delete messages, see [`ChatDeletedEvent`].
+    pub fn signature(&self) -> Option<&MessageSignature> {
+        match self {
+            ChatPacket::System(_) => None,
+            ChatPacket::Player(m) => m.signature.as_ref(),
+        }
+    }
+
     /// Get the content part of the message as a string. This does not preserve
     /// formatting codes. If it's not a player-sent chat message or the sender
     /// couldn't be determined, this will contain the entire message.
@@ -159,6 +214,7 @@ impl Plugin for ChatPlugin {
         app.add_event::<SendChatEvent>()
             .add_event::<SendChatKindEvent>()
             .add_event::<ChatReceivedEvent>()
+            .add_event::<ChatDeletedEvent>()
             .add_systems(
                 (
                     handle_send_chat_event,
@@ -176,6 +232,14 @@ pub struct ChatReceivedEvent {
     pub packet: ChatPacket,
 }
 
+/// The server wants a chat message to be hidden, usually because a moderator
+/// deleted it. The message has the same [`ChatPacket::signature`].
+#[derive(Debug, Clone)]
+pub struct ChatDeletedEvent {
+    pub entity: Entity,
+    pub signature: MessageSignature,
+}
+
 /// Send a chat message (or command, if it starts with a slash) to the server.
 pub struct SendChatEvent {
     pub entity: Entity,
@@ -227,41 +291,79 @@ pub enum ChatPacketKind {
 fn handle_send_chat_kind_event(
     mut events: EventReader<SendChatKindEvent>,
     mut send_packet_events: EventWriter<SendPacketEvent>,
+    mut query: Query<(
+        &mut LastSeenMessagesTracker,
+        Option<&mut ChatSigningSession>,
+        &CommandTree,
+    )>,
 ) {
     for event in events.iter() {
+        let Ok((mut last_seen_messages, mut signing_session, command_tree)) =
+            query.get_mut(event.entity)
+        else {
+            warn!("Tried to send a chat packet for an entity that isn't in the game");
+            continue;
+        };
+
         let content = event
             .content
             .chars()
             .filter(|c| !matches!(c, '\x00'..='\x1F' | '\x7F' | '§'))
             .take(256)
             .collect::<String>();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time shouldn't be before epoch")
+            .as_millis()
+            .try_into()
+            .expect("Instant should fit into a u64");
+        let salt = azalea_crypto::make_salt();
+        let (last_seen, last_seen_messages) = last_seen_messages.generate_and_apply_update();
+
         let packet = match event.kind {
-            ChatPacketKind::Message => ServerboundChatPacket {
-                message: content,
-                timestamp: SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .expect("Time shouldn't be before epoch")
-                    .as_millis()
-                    .try_into()
-                    .expect("Instant should fit into a u64"),
-                salt: azalea_crypto::make_salt(),
-                signature: None,
-                last_seen_messages: LastSeenMessagesUpdate::default(),
+            ChatPacketKind::Message => {
+                let signature = signing_session.as_mut().and_then(|session| {
+                    session.sign(&SignedMessageBody {
+                        content: &content,
+                        timestamp,
+                        salt,
+                        last_seen: &last_seen,
+                    })
+                });
+                ServerboundChatPacket {
+                    message: content,
+                    timestamp,
+                    salt,
+                    signature,
+                    last_seen_messages,
+                }
+                .get()
             }
-            .get(),
             ChatPacketKind::Command => {
-                // TODO: chat signing
+                // only the arguments that the command tree says are signable
+                // (like the message in /msg) get signed
+                let argument_signatures = match signing_session.as_mut() {
+                    Some(session) => command_tree
+                        .signable_arguments(&content)
+                        .into_iter()
+                        .filter_map(|(name, value)| {
+                            let signature = session.sign(&SignedMessageBody {
+                                content: &value,
+                                timestamp,
+                                salt,
+                                last_seen: &last_seen,
+                            })?;
+                            Some(ArgumentSignature { name, signature })
+                        })
+                        .collect(),
+                    None => vec![],
+                };
                 ServerboundChatCommandPacket {
                     command: content,
-                    timestamp: SystemTime::now()
-                        .duration_since(UNIX_EPOCH)
-                        .expect("Time shouldn't be before epoch")
-                        .as_millis()
-                        .try_into()
-                        .expect("Instant should fit into a u64"),
-                    salt: azalea_crypto::make_salt(),
-                    argument_signatures: vec![],
-                    last_seen_messages: LastSeenMessagesUpdate::default(),
+                    timestamp,
+                    salt,
+                    argument_signatures,
+                    last_seen_messages,
                 }
                 .get()
             }
@@ -274,8 +376,510 @@ fn handle_send_chat_kind_event(
     }
 }
 
-// TODO
-// MessageSigner, ChatMessageContent, LastSeenMessages
-// fn sign_message() -> MessageSignature {
-//     MessageSignature::default()
-// }
+/// The key pair and chain of messages that a client signs its chat messages
+/// with. Only clients with online-mode accounts have this.
+#[derive(Component, Clone, Debug)]
+pub struct ChatSigningSession {
+    pub session_id: Uuid,
+    pub certs: Certificates,
+    /// The link for the next message we send, or `None` if the chain is
+    /// broken and we can't sign any more messages.
+    next_link: Option<SignedMessageLink>,
+}
+
+impl ChatSigningSession {
+    /// Start a new session with a random id.
+    pub fn new(sender: Uuid, certs: Certificates) -> Self {
+        let session_id = Uuid::new_v4();
+        Self {
+            session_id,
+            certs,
+            next_link: Some(SignedMessageLink::root(sender, session_id)),
+        }
+    }
+
+    /// The session that's sent to the server so it (and other players) can
+    /// verify our messages.
+    pub fn session_data(&self) -> RemoteChatSessionData {
+        RemoteChatSessionData {
+            session_id: self.session_id,
+            profile_public_key: ProfilePublicKeyData {
+                expires_at: self.certs.expires_at,
+                key: self.certs.public_key_der.clone(),
+                key_signature: self.certs.signature_v2.clone(),
+            },
+        }
+    }
+
+    /// Sign the next message in the chain.
+    pub fn sign(&mut self, body: &SignedMessageBody) -> Option<MessageSignature> {
+        let link = self.next_link?;
+        self.next_link = link.advance();
+        Some(azalea_crypto::sign_message(
+            &self.certs.private_key,
+            &link,
+            body,
+        ))
+    }
+}
+
+/// The number of messages that can be acknowledged in a chat packet.
+const LAST_SEEN_MESSAGES_SIZE: usize = 20;
+
+/// The signed messages that we've received and haven't told the server we
+/// saw yet, like vanilla's `LastSeenMessagesTracker`.
+#[derive(Component, Clone, Debug, Default)]
+pub struct LastSeenMessagesTracker {
+    /// A ring buffer of the signatures of the last messages we received.
+    /// Messages that weren't displayed are `None`.
+    tracked: [Option<MessageSignature>; LAST_SEEN_MESSAGES_SIZE],
+    tail: usize,
+    offset: u32,
+    last_tracked: Option<MessageSignature>,
+}
+
+impl LastSeenMessagesTracker {
+    /// Track a message that we received. Returns false if it was the same as
+    /// the last message, in which case it's ignored.
+    pub fn add_pending(&mut self, signature: MessageSignature, displayed: bool) -> bool {
+        if self.last_tracked.as_ref() == Some(&signature) {
+            return false;
+        }
+        self.last_tracked = Some(signature.clone());
+
+        self.tracked[self.tail] = displayed.then_some(signature);
+        self.tail = (self.tail + 1) % LAST_SEEN_MESSAGES_SIZE;
+        self.offset += 1;
+        true
+    }
+
+    /// The number of messages we've received since we last acknowledged
+    /// messages.
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    pub fn get_and_clear_offset(&mut self) -> u32 {
+        std::mem::take(&mut self.offset)
+    }
+
+    /// Get the signatures of the messages we've seen (in order from oldest to
+    /// newest) and the update that acknowledges them.
+    pub fn generate_and_apply_update(&mut self) -> (Vec<MessageSignature>, LastSeenMessagesUpdate) {
+        let messages = self.get_and_clear_offset();
+        let mut acknowledged = FixedBitSet::<LAST_SEEN_MESSAGES_SIZE>::new();
+        let mut last_seen = Vec::new();
+
+        for i in 0..LAST_SEEN_MESSAGES_SIZE {
+            let index = (self.tail + i) % LAST_SEEN_MESSAGES_SIZE;
+            if let Some(signature) = &self.tracked[index] {
+                acknowledged.set(i);
+                last_seen.push(signature.clone());
+            }
+        }
+
+        (
+            last_seen,
+            LastSeenMessagesUpdate {
+                messages,
+                acknowledged,
+            },
+        )
+    }
+}
+
+/// The number of signatures that the server can refer to by index.
+const MESSAGE_SIGNATURE_CACHE_SIZE: usize = 128;
+
+/// The signatures of recent messages. The server refers to these by index
+/// instead of sending the whole signature again, like vanilla's
+/// `MessageSignatureCache`.
+#[derive(Clone, Debug)]
+pub struct MessageSignatureCache {
+    entries: Vec<Option<MessageSignature>>,
+}
+
+impl Default for MessageSignatureCache {
+    fn default() -> Self {
+        Self {
+            entries: vec![None; MESSAGE_SIGNATURE_CACHE_SIZE],
+        }
+    }
+}
+
+impl MessageSignatureCache {
+    /// Get the full signature from a signature that was sent by the server.
+    /// Returns `None` if it refers to a signature that isn't in the cache.
+    pub fn unpack(&self, packed: &PackedMessageSignature) -> Option<MessageSignature> {
+        match packed {
+            PackedMessageSignature::Signature(signature) => Some((**signature).clone()),
+            PackedMessageSignature::Id(id) => self.entries.get(*id as usize)?.clone(),
+        }
+    }
+
+    /// Move the signatures of a message and the ones it saw to the front of
+    /// the cache. This has to be done the same way as the server, or we won't
+    /// agree on the indexes.
+    pub fn push(&mut self, last_seen: &[MessageSignature], signature: Option<&MessageSignature>) {
+        let pushed = last_seen
+            .iter()
+            .chain(signature)
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut queue = pushed.iter().cloned().collect::<VecDeque<_>>();
+
+        for entry in &mut self.entries {
+            let Some(signature) = queue.pop_back() else {
+                break;
+            };
+            if let Some(old) = entry.replace(signature) {
+                if !pushed.contains(&old) {
+                    queue.push_front(old);
+                }
+            }
+        }
+    }
+}
+
+/// Checks the signatures of the chat messages that we receive, using the chat
+/// sessions from the tab list.
+#[derive(Component, Clone, Debug, Default)]
+pub struct ChatVerifier {
+    pub signature_cache: MessageSignatureCache,
+    chains: HashMap<Uuid, MessageChain>,
+}
+
+/// The messages that a player sent in a chat session.
+#[derive(Clone, Debug)]
+struct MessageChain {
+    session_id: Uuid,
+    public_key: RsaPublicKey,
+    last_link: Option<SignedMessageLink>,
+    /// Whether a message in the chain was invalid or unsigned, which means
+    /// none of the messages after it can be trusted either.
+    broken: bool,
+}
+
+impl ChatVerifier {
+    /// Start checking a player's messages with a new chat session, or stop if
+    /// they don't have one anymore.
+    pub fn set_session(&mut self, sender: Uuid, session: Option<&RemoteChatSessionData>) {
+        let Some(session) = session else {
+            self.chains.remove(&sender);
+            return;
+        };
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time shouldn't be before epoch")
+            .as_millis() as u64;
+        if session.profile_public_key.expires_at <= now {
+            self.chains.remove(&sender);
+            return;
+        }
+        match azalea_crypto::public_key_from_der(&session.profile_public_key.key) {
+            Ok(public_key) => {
+                self.chains.insert(
+                    sender,
+                    MessageChain {
+                        session_id: session.session_id,
+                        public_key,
+                        last_link: None,
+                        broken: false,
+                    },
+                );
+            }
+            Err(e) => {
+                warn!("Invalid chat session key for {sender}: {e}");
+                self.chains.remove(&sender);
+            }
+        }
+    }
+
+    pub fn remove_player(&mut self, sender: &Uuid) {
+        self.chains.remove(sender);
+    }
+
+    /// Check a chat message and remember it for checking the ones after it.
+    pub fn verify(&mut self, packet: &ClientboundPlayerChatPacket) -> ChatVerification {
+        let last_seen = packet
+            .body
+            .last_seen
+            .entries
+            .iter()
+            .map(|signature| self.signature_cache.unpack(signature))
+            .collect::<Option<Vec<_>>>();
+        let Some(last_seen) = last_seen else {
+            // the server referred to a message we don't know about
+            return ChatVerification::Invalid;
+        };
+
+        let verification = self.verify_signature(packet, &last_seen);
+        self.signature_cache
+            .push(&last_seen, packet.signature.as_ref());
+        verification
+    }
+
+    fn verify_signature(
+        &mut self,
+        packet: &ClientboundPlayerChatPacket,
+        last_seen: &[MessageSignature],
+    ) -> ChatVerification {
+        let Some(chain) = self.chains.get_mut(&packet.sender) else {
+            return ChatVerification::Unsigned;
+        };
+        let Some(signature) = &packet.signature else {
+            // they have a chat session so they should've signed it, vanilla
+            // stops trusting their messages when this happens
+            chain.broken = true;
+            return ChatVerification::Unsigned;
+        };
+        if chain.broken {
+            return ChatVerification::Invalid;
+        }
+
+        let link = SignedMessageLink {
+            index: packet.index,
+            sender: packet.sender,
+            session_id: chain.session_id,
+        };
+        let body = SignedMessageBody {
+            content: &packet.body.content,
+            timestamp: packet.body.timestamp,
+            salt: packet.body.salt,
+            last_seen,
+        };
+        let follows_last = chain
+            .last_link
+            .map_or(true, |last_link| link.is_descendant_of(&last_link));
+        if !follows_last
+            || !azalea_crypto::verify_message(&chain.public_key, &link, &body, signature)
+        {
+            chain.broken = true;
+            return ChatVerification::Invalid;
+        }
+        chain.last_link = Some(link);
+
+        if packet.unsigned_content.is_some() {
+            ChatVerification::Modified
+        } else {
+            ChatVerification::Verified
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signature(n: u8) -> MessageSignature {
+        MessageSignature { bytes: [n; 256] }
+    }
+
+    #[test]
+    fn test_last_seen_messages() {
+        let mut tracker = LastSeenMessagesTracker::default();
+        assert!(tracker.add_pending(signature(1), true));
+        assert!(tracker.add_pending(signature(2), false));
+        assert!(tracker.add_pending(signature(3), true));
+        // duplicates are ignored
+        assert!(!tracker.add_pending(signature(3), true));
+        assert_eq!(tracker.offset(), 3);
+
+        let (last_seen, update) = tracker.generate_and_apply_update();
+        assert_eq!(last_seen, vec![signature(1), signature(3)]);
+        assert_eq!(update.messages, 3);
+        // the oldest slot is the one after the tail
+        let acknowledged = (0..LAST_SEEN_MESSAGES_SIZE)
+            .filter(|&i| update.acknowledged.index(i))
+            .collect::<Vec<_>>();
+        assert_eq!(acknowledged, vec![17, 19]);
+
+        // the messages are still seen, but the offset is reset
+        let (last_seen, update) = tracker.generate_and_apply_update();
+        assert_eq!(last_seen.len(), 2);
+        assert_eq!(update.messages, 0);
+    }
+
+    #[test]
+    fn test_last_seen_messages_wrap() {
+        let mut tracker = LastSeenMessagesTracker::default();
+        for i in 0..25 {
+            tracker.add_pending(signature(i), true);
+        }
+        let (last_seen, update) = tracker.generate_and_apply_update();
+        assert_eq!(update.messages, 25);
+        assert_eq!(last_seen, (5..25).map(signature).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_message_signature_cache() {
+        let mut cache = MessageSignatureCache::default();
+        cache.push(&[signature(1), signature(2)], Some(&signature(3)));
+        // the newest signature is first
+        let unpack =
+            |cache: &MessageSignatureCache, id| cache.unpack(&PackedMessageSignature::Id(id));
+        assert_eq!(unpack(&cache, 0), Some(signature(3)));
+        assert_eq!(unpack(&cache, 1), Some(signature(2)));
+        assert_eq!(unpack(&cache, 2), Some(signature(1)));
+        assert_eq!(unpack(&cache, 3), None);
+        assert_eq!(unpack(&cache, 1000), None);
+
+        // signatures that were pushed again move to the front instead of
+        // being duplicated
+        cache.push(&[signature(1)], Some(&signature(4)));
+        assert_eq!(unpack(&cache, 0), Some(signature(4)));
+        assert_eq!(unpack(&cache, 1), Some(signature(1)));
+        assert_eq!(unpack(&cache, 2), Some(signature(3)));
+        assert_eq!(unpack(&cache, 3), Some(signature(2)));
+        assert_eq!(unpack(&cache, 4), None);
+    }
+
+    mod verification {
+        use super::*;
+        use azalea_protocol::packets::game::clientbound_player_chat_packet::{
+            ChatType, ChatTypeBound, FilterMask, PackedLastSeenMessages, PackedSignedMessageBody,
+        };
+
+        const SENDER: Uuid = Uuid::from_u128(1);
+        const SESSION_ID: Uuid = Uuid::from_u128(2);
+
+        fn session() -> RemoteChatSessionData {
+            RemoteChatSessionData {
+                session_id: SESSION_ID,
+                profile_public_key: ProfilePublicKeyData {
+                    expires_at: u64::MAX,
+                    key: azalea_crypto::pem_to_der(include_str!(
+                        "../../azalea-crypto/src/test_key.pub.pem"
+                    ))
+                    .unwrap(),
+                    key_signature: vec![],
+                },
+            }
+        }
+
+        fn chat_packet(
+            index: u32,
+            content: &str,
+            last_seen: Vec<PackedMessageSignature>,
+        ) -> ClientboundPlayerChatPacket {
+            let private_key = azalea_crypto::private_key_from_pem(include_str!(
+                "../../azalea-crypto/src/test_key.pem"
+            ))
+            .unwrap();
+            let link = SignedMessageLink {
+                index,
+                sender: SENDER,
+                session_id: SESSION_ID,
+            };
+            let body = PackedSignedMessageBody {
+                content: content.to_string(),
+                timestamp: 1_680_000_000_000,
+                salt: index as u64,
+                last_seen: PackedLastSeenMessages { entries: last_seen },
+            };
+            ClientboundPlayerChatPacket {
+                sender: SENDER,
+                index,
+                signature: Some(azalea_crypto::sign_message(
+                    &private_key,
+                    &link,
+                    &SignedMessageBody {
+                        content: &body.content,
+                        timestamp: body.timestamp,
+                        salt: body.salt,
+                        last_seen: &[],
+                    },
+                )),
+                body,
+                unsigned_content: None,
+                filter_mask: FilterMask::PassThrough,
+                chat_type: ChatTypeBound {
+                    chat_type: ChatType::Chat,
+                    name: FormattedText::from("bot0"),
+                    target_name: None,
+                },
+            }
+        }
+
+        #[test]
+        fn test_verified() {
+            let mut verifier = ChatVerifier::default();
+            verifier.set_session(SENDER, Some(&session()));
+            assert_eq!(
+                verifier.verify(&chat_packet(0, "hi", vec![])),
+                ChatVerification::Verified
+            );
+            assert_eq!(
+                verifier.verify(&chat_packet(1, "hello", vec![])),
+                ChatVerification::Verified
+            );
+
+            let mut modified = chat_packet(2, "hey", vec![]);
+            modified.unsigned_content = Some(FormattedText::from("hey!!!"));
+            assert_eq!(verifier.verify(&modified), ChatVerification::Modified);
+        }
+
+        #[test]
+        fn test_unsigned() {
+            let mut verifier = ChatVerifier::default();
+            // we don't know their session
+            assert_eq!(
+                verifier.verify(&chat_packet(0, "hi", vec![])),
+                ChatVerification::Unsigned
+            );
+
+            verifier.set_session(SENDER, Some(&session()));
+            let mut unsigned = chat_packet(1, "hi", vec![]);
+            unsigned.signature = None;
+            assert_eq!(verifier.verify(&unsigned), ChatVerification::Unsigned);
+            // they should've signed it, so the chain is broken now
+            assert_eq!(
+                verifier.verify(&chat_packet(2, "hi", vec![])),
+                ChatVerification::Invalid
+            );
+
+            assert_eq!(
+                ChatPacket::new("hi").verification(),
+                ChatVerification::Unsigned
+            );
+        }
+
+        #[test]
+        fn test_invalid() {
+            let mut verifier = ChatVerifier::default();
+            verifier.set_session(SENDER, Some(&session()));
+
+            let mut forged = chat_packet(0, "hi", vec![]);
+            forged.body.content = "i love cheating".to_string();
+            assert_eq!(verifier.verify(&forged), ChatVerification::Invalid);
+            // the chain is broken now, so even valid messages can't be trusted
+            assert_eq!(
+                verifier.verify(&chat_packet(1, "hi", vec![])),
+                ChatVerification::Invalid
+            );
+
+            // a new session starts a new chain
+            verifier.set_session(SENDER, Some(&session()));
+            assert_eq!(
+                verifier.verify(&chat_packet(5, "hi", vec![])),
+                ChatVerification::Verified
+            );
+            // replayed messages don't follow the last one
+            assert_eq!(
+                verifier.verify(&chat_packet(5, "hi", vec![])),
+                ChatVerification::Invalid
+            );
+        }
+
+        #[test]
+        fn test_unknown_last_seen() {
+            let mut verifier = ChatVerifier::default();
+            verifier.set_session(SENDER, Some(&session()));
+            assert_eq!(
+                verifier.verify(&chat_packet(0, "hi", vec![PackedMessageSignature::Id(0)])),
+                ChatVerification::Invalid
+            );
+        }
+    }
+}
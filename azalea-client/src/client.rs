@@ -1,19 +1,23 @@
 use crate::{
-    chat::ChatPlugin,
+    chat::{ChatPlugin, ChatSigningSession, ChatVerifier, LastSeenMessagesTracker},
+    commands::CommandTree,
     disconnect::{DisconnectEvent, DisconnectPlugin},
     events::{Event, EventPlugin, LocalPlayerEvents},
     local_player::{
         death_event, handle_send_packet_event, update_in_loaded_chunk, GameProfileComponent,
-        LocalPlayer, PhysicsState, SendPacketEvent,
+        LocalPlayer, PhysicsState, SendPacketEvent, WorldBorder,
     },
     movement::PlayerMovePlugin,
     packet_handling::{self, PacketHandlerPlugin, PacketReceiver},
     player::retroactively_add_game_profile_component,
+    plugin_channels::{PluginChannels, PluginChannelsPlugin},
+    replay::RecordPackets,
     task_pool::TaskPoolPlugin,
     Account, PlayerInfo,
 };
 
 use azalea_auth::{game_profile::GameProfile, sessionserver::ClientSessionServerError};
+use azalea_buf::UnsizedByteArray;
 use azalea_chat::FormattedText;
 use azalea_physics::{PhysicsPlugin, PhysicsSet};
 use azalea_protocol::{
@@ -52,11 +56,11 @@ use bevy_ecs::{
 use bevy_log::LogPlugin;
 use bevy_time::{prelude::FixedTime, TimePlugin};
 use derive_more::{Deref, DerefMut};
-use log::{debug, error};
+use log::{debug, error, warn};
 use parking_lot::{Mutex, RwLock};
 use std::{collections::HashMap, fmt::Debug, io, net::SocketAddr, sync::Arc, time::Duration};
 use thiserror::Error;
-use tokio::{sync::mpsc, time};
+use tokio::{sync::mpsc, task::JoinHandle, time};
 use uuid::Uuid;
 
 /// `Client` has the things that a user interacting with the library will want.
@@ -169,7 +173,8 @@ impl Client {
         address: impl TryInto<ServerAddress>,
     ) -> Result<(Self, mpsc::UnboundedReceiver<Event>), JoinError> {
         let address: ServerAddress = address.try_into().map_err(|_| JoinError::InvalidAddress)?;
-        let resolved_address = resolver::resolve_address(&address).await?;
+        let resolved_address =
+            resolver::resolve_address_unless_proxied(&address, account.proxy.as_ref()).await?;
 
         // An event that causes the schedule to run. This is only used internally.
         let (run_schedule_sender, run_schedule_receiver) = mpsc::unbounded_channel();
@@ -180,7 +185,7 @@ impl Client {
             ecs_lock,
             account,
             &address,
-            &resolved_address,
+            resolved_address.as_ref(),
             run_schedule_sender,
         )
         .await
@@ -188,17 +193,82 @@ impl Client {
 
     /// Create a [`Client`] when you already have the ECS made with
     /// [`start_ecs`]. You'd usually want to use [`Self::join`] instead.
+    ///
+    /// If the account has a [`proxy`](Account::proxy), the connection goes
+    /// through it.
+    ///
+    /// `resolved_address` can be `None` if the address hasn't been looked up,
+    /// see [`resolver::resolve_address_unless_proxied`].
     pub async fn start_client(
         ecs_lock: Arc<Mutex<World>>,
         account: &Account,
         address: &ServerAddress,
-        resolved_address: &SocketAddr,
+        resolved_address: Option<&SocketAddr>,
         run_schedule_sender: mpsc::UnboundedSender<()>,
     ) -> Result<(Self, mpsc::UnboundedReceiver<Event>), JoinError> {
-        let conn = Connection::new(resolved_address).await?;
-        let (conn, game_profile) = Self::handshake(conn, account, address).await?;
+        // get a key pair for signing chat messages if we don't have a usable
+        // one, but joining without one is fine
+        let needs_certs = account.access_token.is_some()
+            && account
+                .certs
+                .lock()
+                .as_ref()
+                .map_or(true, |certs| certs.should_refresh());
+        if needs_certs {
+            if let Err(e) = account.request_certs().await {
+                warn!("Couldn't get a key pair for signing chat messages: {e}");
+            }
+        }
+
+        let mut conn =
+            Connection::new_maybe_with_proxy(address, resolved_address, account.proxy.as_ref())
+                .await?;
+        let plugin_channels = {
+            let ecs = ecs_lock.lock();
+            if let Some(RecordPackets(recorder)) = ecs.get_resource::<RecordPackets>() {
+                conn.record(recorder.clone());
+            }
+            ecs.get_resource::<PluginChannels>()
+                .cloned()
+                .unwrap_or_default()
+        };
+        let (conn, game_profile) =
+            Self::handshake(conn, account, address, &plugin_channels).await?;
         let (read_conn, write_conn) = conn.into_split();
 
+        // the session is sent to the server when we get the login packet
+        let certs = account.certs.lock().clone();
+        let chat_signing_session = certs
+            .filter(|certs| !certs.is_expired())
+            .map(|certs| ChatSigningSession::new(game_profile.uuid, certs));
+
+        Ok(Self::add_local_player(
+            ecs_lock,
+            game_profile,
+            chat_signing_session,
+            run_schedule_sender,
+            |packet_receiver, packet_writer_receiver| {
+                let read_packets_task = tokio::spawn(packet_receiver.clone().read_task(read_conn));
+                let write_packets_task =
+                    tokio::spawn(packet_receiver.write_task(write_conn, packet_writer_receiver));
+                (read_packets_task, write_packets_task)
+            },
+        ))
+    }
+
+    /// Spawn the entity for a client that's already in the game state.
+    /// `spawn_tasks` should start the tasks that put packets into the
+    /// [`PacketReceiver`] and write packets from the receiver.
+    pub(crate) fn add_local_player(
+        ecs_lock: Arc<Mutex<World>>,
+        game_profile: GameProfile,
+        chat_signing_session: Option<ChatSigningSession>,
+        run_schedule_sender: mpsc::UnboundedSender<()>,
+        spawn_tasks: impl FnOnce(
+            PacketReceiver,
+            mpsc::UnboundedReceiver<ServerboundGamePacket>,
+        ) -> (JoinHandle<()>, JoinHandle<()>),
+    ) -> (Self, mpsc::UnboundedReceiver<Event>) {
         let (tx, rx) = mpsc::unbounded_channel();
 
         let mut ecs = ecs_lock.lock();
@@ -223,12 +293,8 @@ impl Client {
             run_schedule_sender: run_schedule_sender.clone(),
         };
 
-        let read_packets_task = tokio::spawn(packet_receiver.clone().read_task(read_conn));
-        let write_packets_task = tokio::spawn(
-            packet_receiver
-                .clone()
-                .write_task(write_conn, packet_writer_receiver),
-        );
+        let (read_packets_task, write_packets_task) =
+            spawn_tasks(packet_receiver.clone(), packet_writer_receiver);
 
         let local_player = crate::local_player::LocalPlayer::new(
             entity,
@@ -248,21 +314,30 @@ impl Client {
             local_player_events: LocalPlayerEvents(tx),
             client_information: ClientInformation::default(),
             tab_list: TabList::default(),
+            world_border: WorldBorder::default(),
+            last_seen_messages: LastSeenMessagesTracker::default(),
+            chat_verifier: ChatVerifier::default(),
+            command_tree: CommandTree::default(),
             _local: Local,
         });
+        if let Some(chat_signing_session) = chat_signing_session {
+            ecs.entity_mut(entity).insert(chat_signing_session);
+        }
 
-        Ok((client, rx))
+        (client, rx)
     }
 
     /// Do a handshake with the server and get to the game state from the
     /// initial handshake state.
     ///
     /// This will also automatically refresh the account's access token if
-    /// it's expired.
+    /// it's expired. Login queries from the server are answered with the
+    /// handlers in `plugin_channels`.
     pub async fn handshake(
         mut conn: Connection<ClientboundHandshakePacket, ServerboundHandshakePacket>,
         account: &Account,
         address: &ServerAddress,
+        plugin_channels: &PluginChannels,
     ) -> Result<
         (
             Connection<ClientboundGamePacket, ServerboundGamePacket>,
@@ -365,7 +440,7 @@ impl Client {
                     conn.write(
                         ServerboundCustomQueryPacket {
                             transaction_id: p.transaction_id,
-                            data: None,
+                            data: plugin_channels.answer_login_query(&p).map(UnsizedByteArray),
                         }
                         .get(),
                     )
@@ -489,6 +564,10 @@ pub struct JoinedClientBundle {
     pub local_player_events: LocalPlayerEvents,
     pub client_information: ClientInformation,
     pub tab_list: TabList,
+    pub world_border: WorldBorder,
+    pub last_seen_messages: LastSeenMessagesTracker,
+    pub chat_verifier: ChatVerifier,
+    pub command_tree: CommandTree,
     pub _local: Local,
 }
 
@@ -617,5 +696,6 @@ impl PluginGroup for DefaultPlugins {
             .add(ChatPlugin)
             .add(DisconnectPlugin)
             .add(PlayerMovePlugin)
+            .add(PluginChannelsPlugin)
     }
 }
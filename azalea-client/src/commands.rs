@@ -0,0 +1,503 @@
+//! Parse commands with the command tree that the server sent us, so they can
+//! be checked and completed without asking the server.
+
+use azalea_brigadier::{
+    arguments::{
+        bool_argument_type::Boolean, double_argument_type::Double, float_argument_type::Float,
+        integer_argument_type::Integer, long_argument_type::Long,
+        string_argument_type::StringArgument, ArgumentType,
+    },
+    builder::{
+        argument_builder::ArgumentBuilderType, literal_argument_builder::literal,
+        required_argument_builder::argument,
+    },
+    command_dispatcher::CommandDispatcher,
+    context::CommandContextBuilder,
+    exceptions::{BuiltInExceptions, CommandSyntaxException},
+    string_reader::StringReader,
+    suggestion::{Suggestions, SuggestionsBuilder},
+    tree::CommandNode,
+};
+use azalea_protocol::packets::game::clientbound_commands_packet::{
+    BrigadierParser, BrigadierString, ClientboundCommandsPacket, NodeType,
+};
+use bevy_ecs::component::Component;
+use std::{any::Any, cell::RefCell, rc::Rc, sync::Arc};
+
+use crate::client::Client;
+
+/// The commands that the server told us about, from the last
+/// [`ClientboundCommandsPacket`].
+#[derive(Component, Clone, Debug, Default)]
+pub struct CommandTree {
+    packet: Option<Arc<ClientboundCommandsPacket>>,
+}
+
+impl CommandTree {
+    pub fn new(packet: ClientboundCommandsPacket) -> Self {
+        Self {
+            packet: Some(Arc::new(packet)),
+        }
+    }
+
+    /// Whether the server has sent us its commands yet.
+    pub fn is_empty(&self) -> bool {
+        self.packet.is_none()
+    }
+
+    /// Rebuild the server's command tree as a [`CommandDispatcher`] and run
+    /// the function with it.
+    ///
+    /// Redirects usually point back up the tree, so they're removed after
+    /// the function returns to let the nodes be freed. This means the
+    /// dispatcher and its nodes shouldn't be kept around.
+    ///
+    /// Executing a command with the dispatcher does nothing, since only the
+    /// server knows what the commands do.
+    pub fn with_dispatcher<R>(&self, f: impl FnOnce(&CommandDispatcher<()>) -> R) -> R {
+        let mut dispatcher = CommandDispatcher::new();
+        let Some(packet) = &self.packet else {
+            return f(&dispatcher);
+        };
+
+        let nodes = packet
+            .entries
+            .iter()
+            .map(|entry| {
+                let builder = match &entry.node_type {
+                    NodeType::Root => return Rc::new(RefCell::new(CommandNode::default())),
+                    NodeType::Literal { name } => literal(name),
+                    NodeType::Argument { name, parser, .. } => {
+                        argument(name, ServerArgument(parser.clone()))
+                    }
+                };
+                let mut node = builder.build();
+                if entry.is_executable {
+                    node.command = Some(Rc::new(|_| 0));
+                }
+                Rc::new(RefCell::new(node))
+            })
+            .collect::<Vec<_>>();
+        for (entry, node) in packet.entries.iter().zip(&nodes) {
+            for &child in &entry.children {
+                if let Some(child) = nodes.get(child as usize) {
+                    node.borrow_mut().add_child(child);
+                }
+            }
+            if let Some(redirect) = entry.redirect_node {
+                node.borrow_mut().redirect = nodes.get(redirect as usize).cloned();
+            }
+        }
+        if let Some(root) = nodes.get(packet.root_index as usize) {
+            dispatcher.root = root.clone();
+        }
+
+        let result = f(&dispatcher);
+
+        for node in &nodes {
+            node.borrow_mut().redirect = None;
+        }
+        result
+    }
+
+    /// Check whether a command (without the slash) is valid and complete.
+    /// Commands are always valid if the server hasn't sent its commands yet.
+    pub fn validate(&self, command: &str) -> Result<(), CommandSyntaxException> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        self.with_dispatcher(|dispatcher| {
+            let parse = dispatcher.parse(command.into(), Rc::new(()));
+            if parse.reader.can_read() {
+                if parse.exceptions.len() == 1 {
+                    return Err(parse.exceptions.into_values().next().unwrap());
+                }
+                if parse.context.range.is_empty() {
+                    return Err(BuiltInExceptions::DispatcherUnknownCommand
+                        .create_with_context(&parse.reader));
+                }
+                return Err(
+                    BuiltInExceptions::DispatcherUnknownArgument.create_with_context(&parse.reader)
+                );
+            }
+
+            let mut context = &parse.context;
+            while let Some(child) = &context.child {
+                context = child;
+            }
+            if context.command.is_none() {
+                return Err(
+                    BuiltInExceptions::DispatcherUnknownCommand.create_with_context(&parse.reader)
+                );
+            }
+            Ok(())
+        })
+    }
+
+    /// Get the suggestions for the end of a command (without the slash).
+    ///
+    /// Arguments that the server completes itself, like player names, don't
+    /// have any suggestions.
+    pub fn suggestions(&self, command: &str) -> Suggestions {
+        self.with_dispatcher(|dispatcher| {
+            dispatcher.get_completion_suggestions(dispatcher.parse(command.into(), Rc::new(())))
+        })
+    }
+
+    /// The names and contents of the arguments in a command that should be
+    /// signed, like the message in `/msg`. This is empty if the server hasn't
+    /// sent its commands yet.
+    pub fn signable_arguments(&self, command: &str) -> Vec<(String, String)> {
+        self.with_dispatcher(|dispatcher| {
+            let parse = dispatcher.parse(command.into(), Rc::new(()));
+
+            // like vanilla's SignableCommand, follow redirects until they go
+            // back to the root
+            let mut arguments = Vec::new();
+            let mut context = &parse.context;
+            collect_signable_arguments(command, context, &mut arguments);
+            while let Some(child) = &context.child {
+                if Rc::ptr_eq(&child.root, &parse.context.root) {
+                    break;
+                }
+                collect_signable_arguments(command, child, &mut arguments);
+                context = child;
+            }
+            arguments
+        })
+    }
+}
+
+fn collect_signable_arguments(
+    command: &str,
+    context: &CommandContextBuilder<()>,
+    arguments: &mut Vec<(String, String)>,
+) {
+    for parsed_node in &context.nodes {
+        let node = parsed_node.node.borrow();
+        let ArgumentBuilderType::Argument(node_argument) = &node.value else {
+            continue;
+        };
+        let Some(parsed) = context.arguments.get(&node_argument.name) else {
+            continue;
+        };
+        if parsed.result.is::<SignedArgument>() {
+            arguments.push((
+                node_argument.name.clone(),
+                parsed.range.get(command).to_string(),
+            ));
+        }
+    }
+}
+
+/// The result of parsing an argument that the client has to sign.
+struct SignedArgument;
+
+/// An argument type for a [`BrigadierParser`].
+///
+/// The types from Brigadier are parsed with the ones in `azalea_brigadier`. The
+/// rest are just read as one or more words so commands can be split into
+/// arguments, and invalid values are left for the server to reject.
+struct ServerArgument(BrigadierParser);
+
+impl ArgumentType for ServerArgument {
+    fn parse(&self, reader: &mut StringReader) -> Result<Rc<dyn Any>, CommandSyntaxException> {
+        Ok(match &self.0 {
+            BrigadierParser::Bool => Boolean.parse(reader)?,
+            BrigadierParser::Float(range) => Float {
+                minimum: range.min,
+                maximum: range.max,
+            }
+            .parse(reader)?,
+            BrigadierParser::Double(range) => Double {
+                minimum: range.min,
+                maximum: range.max,
+            }
+            .parse(reader)?,
+            BrigadierParser::Integer(range) => Integer {
+                minimum: range.min,
+                maximum: range.max,
+            }
+            .parse(reader)?,
+            BrigadierParser::Long(range) => Long {
+                minimum: range.min,
+                maximum: range.max,
+            }
+            .parse(reader)?,
+            BrigadierParser::String(BrigadierString::SingleWord) => {
+                StringArgument::SingleWord.parse(reader)?
+            }
+            BrigadierParser::String(BrigadierString::QuotablePhrase) => {
+                StringArgument::QuotablePhrase.parse(reader)?
+            }
+            BrigadierParser::String(BrigadierString::GreedyPhrase) => {
+                StringArgument::GreedyPhrase.parse(reader)?
+            }
+            BrigadierParser::Message => {
+                StringArgument::GreedyPhrase.parse(reader)?;
+                Rc::new(SignedArgument)
+            }
+            BrigadierParser::BlockPos | BrigadierParser::Vec3 => Rc::new(read_words(reader, 3)?),
+            BrigadierParser::ColumnPos | BrigadierParser::Vec2 | BrigadierParser::Rotation => {
+                Rc::new(read_words(reader, 2)?)
+            }
+            _ => Rc::new(read_words(reader, 1)?),
+        })
+    }
+
+    fn list_suggestions(&self, builder: SuggestionsBuilder) -> Suggestions {
+        match self.0 {
+            BrigadierParser::Bool => Boolean.list_suggestions(builder),
+            _ => Suggestions::default(),
+        }
+    }
+}
+
+/// Read some words separated by spaces. Spaces inside quotes or brackets
+/// don't end a word, so selectors like `@e[type=cow, limit=1]` and NBT are
+/// read as one word.
+fn read_words(reader: &mut StringReader, count: usize) -> Result<String, CommandSyntaxException> {
+    let start = reader.cursor;
+    for i in 0..count {
+        if i > 0 {
+            if !reader.can_read() || reader.peek() != ' ' {
+                return Err(BuiltInExceptions::DispatcherParseException {
+                    message: format!("Incomplete (expected {count} values)"),
+                }
+                .create_with_context(reader));
+            }
+            reader.skip();
+        }
+        let word_start = reader.cursor;
+        let mut depth = 0usize;
+        let mut quote = None;
+        let mut escaped = false;
+        while reader.can_read() {
+            let c = reader.peek();
+            if let Some(q) = quote {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+            } else {
+                match c {
+                    ' ' if depth == 0 => break,
+                    '"' | '\'' => quote = Some(c),
+                    '[' | '{' | '(' => depth += 1,
+                    ']' | '}' | ')' => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+            }
+            reader.skip();
+        }
+        if reader.cursor == word_start {
+            return Err(BuiltInExceptions::DispatcherParseException {
+                message: "Expected value".to_string(),
+            }
+            .create_with_context(reader));
+        }
+    }
+    Ok(reader.string()[start..reader.cursor].to_string())
+}
+
+impl Client {
+    /// Check whether a command (without the slash) is valid with the
+    /// commands that the server sent, so syntax errors can be caught before
+    /// it's sent.
+    ///
+    /// ```rust,no_run
+    /// # fn example(bot: &azalea_client::Client) {
+    /// if let Err(err) = bot.validate_command("gamemode creativ") {
+    ///     println!("{}", err.message());
+    /// }
+    /// # }
+    /// ```
+    pub fn validate_command(&self, command: &str) -> Result<(), CommandSyntaxException> {
+        self.component::<CommandTree>().validate(command)
+    }
+
+    /// Get the suggestions for what could be typed at the end of a command
+    /// (without the slash), like when pressing tab in vanilla.
+    pub fn command_suggestions(&self, command: &str) -> Suggestions {
+        self.component::<CommandTree>().suggestions(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azalea_protocol::packets::game::clientbound_commands_packet::{
+        BrigadierNodeStub, BrigadierNumber, EntityParser,
+    };
+
+    fn node(children: Vec<u32>, node_type: NodeType) -> BrigadierNodeStub {
+        BrigadierNodeStub {
+            is_executable: false,
+            children,
+            redirect_node: None,
+            node_type,
+        }
+    }
+    fn literal_node(name: &str, children: Vec<u32>) -> BrigadierNodeStub {
+        node(
+            children,
+            NodeType::Literal {
+                name: name.to_string(),
+            },
+        )
+    }
+    fn argument_node(name: &str, parser: BrigadierParser, children: Vec<u32>) -> BrigadierNodeStub {
+        node(
+            children,
+            NodeType::Argument {
+                name: name.to_string(),
+                parser,
+                suggestions_type: None,
+            },
+        )
+    }
+    fn executable(mut node: BrigadierNodeStub) -> BrigadierNodeStub {
+        node.is_executable = true;
+        node
+    }
+
+    fn command_tree() -> CommandTree {
+        let mut execute_run = literal_node("run", vec![]);
+        execute_run.redirect_node = Some(0);
+        CommandTree::new(ClientboundCommandsPacket {
+            entries: vec![
+                // 0
+                node(vec![1, 4, 6, 8, 10], NodeType::Root),
+                literal_node("msg", vec![2]),
+                argument_node(
+                    "targets",
+                    BrigadierParser::Entity(EntityParser {
+                        single: false,
+                        players_only: true,
+                    }),
+                    vec![3],
+                ),
+                executable(argument_node("message", BrigadierParser::Message, vec![])),
+                // 4
+                literal_node("tp", vec![5]),
+                executable(argument_node("location", BrigadierParser::Vec3, vec![])),
+                // 6
+                literal_node("time", vec![7]),
+                executable(argument_node(
+                    "time",
+                    BrigadierParser::Integer(BrigadierNumber::new(Some(0), None)),
+                    vec![],
+                )),
+                // 8
+                literal_node("execute", vec![9]),
+                execute_run,
+                // 10
+                literal_node("gamerule", vec![11]),
+                literal_node("keepInventory", vec![12]),
+                executable(argument_node("value", BrigadierParser::Bool, vec![])),
+            ],
+            root_index: 0,
+        })
+    }
+
+    #[test]
+    fn test_validate() {
+        let command_tree = command_tree();
+        assert!(command_tree
+            .validate("msg @a[name=\"a b\", limit=1] hi")
+            .is_ok());
+        assert!(command_tree.validate("tp ~ ~1 ^").is_ok());
+        assert!(command_tree.validate("msg é hi").is_ok());
+        assert!(command_tree.validate("tp ~ ~ é").is_ok());
+        assert!(command_tree.validate("time 5").is_ok());
+        assert!(command_tree.validate("execute run time 5").is_ok());
+        assert!(command_tree.validate("gamerule keepInventory true").is_ok());
+
+        assert_eq!(
+            command_tree.validate("foo").unwrap_err().type_,
+            BuiltInExceptions::DispatcherUnknownCommand
+        );
+        // incomplete
+        assert_eq!(
+            command_tree.validate("msg Steve").unwrap_err().type_,
+            BuiltInExceptions::DispatcherUnknownCommand
+        );
+        assert_eq!(
+            command_tree.validate("execute run").unwrap_err().type_,
+            BuiltInExceptions::DispatcherUnknownCommand
+        );
+        assert!(matches!(
+            command_tree.validate("tp ~ ~").unwrap_err().type_,
+            BuiltInExceptions::DispatcherParseException { .. }
+        ));
+        assert!(matches!(
+            command_tree.validate("time -1").unwrap_err().type_,
+            BuiltInExceptions::DispatcherParseException { .. }
+        ));
+        assert!(matches!(
+            command_tree
+                .validate("gamerule keepInventory yes")
+                .unwrap_err()
+                .type_,
+            BuiltInExceptions::DispatcherParseException { .. }
+        ));
+
+        // there's nothing to check commands against yet
+        assert!(CommandTree::default().validate("foo").is_ok());
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let command_tree = command_tree();
+        let suggestions = command_tree.suggestions("t");
+        assert_eq!(
+            suggestions
+                .suggestions
+                .iter()
+                .map(|s| s.text.as_str())
+                .collect::<Vec<_>>(),
+            vec!["time", "tp"]
+        );
+
+        let suggestions = command_tree.suggestions("gamerule keepInventory ");
+        assert_eq!(
+            suggestions
+                .suggestions
+                .iter()
+                .map(|s| s.text.as_str())
+                .collect::<Vec<_>>(),
+            vec!["false", "true"]
+        );
+
+        let suggestions = command_tree.suggestions("execute run ti");
+        assert_eq!(suggestions.suggestions[0].text, "time");
+        assert_eq!(suggestions.range.start(), 12);
+    }
+
+    #[test]
+    fn test_signable_arguments() {
+        let command_tree = command_tree();
+        assert_eq!(
+            command_tree.signable_arguments("msg Steve hello there"),
+            vec![("message".to_string(), "hello there".to_string())]
+        );
+        assert_eq!(
+            command_tree.signable_arguments("msg é héllo"),
+            vec![("message".to_string(), "héllo".to_string())]
+        );
+        assert_eq!(command_tree.signable_arguments("time 5"), vec![]);
+        // like vanilla, arguments after a redirect back to the root aren't
+        // signed
+        assert_eq!(
+            command_tree.signable_arguments("execute run msg Steve hi"),
+            vec![]
+        );
+        assert_eq!(
+            CommandTree::default().signable_arguments("msg Steve hi"),
+            vec![]
+        );
+    }
+}
@@ -3,6 +3,7 @@
 
 use std::sync::Arc;
 
+use azalea_crypto::MessageSignature;
 use azalea_protocol::packets::game::{
     clientbound_player_combat_kill_packet::ClientboundPlayerCombatKillPacket, ClientboundGamePacket,
 };
@@ -13,7 +14,7 @@ use derive_more::{Deref, DerefMut};
 use tokio::sync::mpsc;
 
 use crate::{
-    chat::{ChatPacket, ChatReceivedEvent},
+    chat::{ChatDeletedEvent, ChatPacket, ChatReceivedEvent},
     packet_handling::{
         AddPlayerEvent, DeathEvent, KeepAliveEvent, PacketEvent, RemovePlayerEvent,
         UpdatePlayerEvent,
@@ -54,6 +55,9 @@ pub enum Event {
     Login,
     /// A chat message was sent in the game chat.
     Chat(ChatPacket),
+    /// The server deleted a chat message, which has this
+    /// [`ChatPacket::signature`].
+    ChatDeleted(Box<MessageSignature>),
     /// Happens 20 times per second, but only when the world is loaded.
     Tick,
     /// We received a packet from the server.
@@ -101,6 +105,7 @@ pub struct EventPlugin;
 impl Plugin for EventPlugin {
     fn build(&self, app: &mut App) {
         app.add_system(chat_listener)
+            .add_system(chat_deleted_listener)
             .add_system(login_listener)
             .add_system(init_listener)
             .add_system(packet_listener)
@@ -138,6 +143,20 @@ fn chat_listener(query: Query<&LocalPlayerEvents>, mut events: EventReader<ChatR
     }
 }
 
+fn chat_deleted_listener(
+    query: Query<&LocalPlayerEvents>,
+    mut events: EventReader<ChatDeletedEvent>,
+) {
+    for event in events.iter() {
+        let local_player_events = query
+            .get(event.entity)
+            .expect("Non-localplayer entities shouldn't be able to receive chat events");
+        local_player_events
+            .send(Event::ChatDeleted(Box::new(event.signature.clone())))
+            .unwrap();
+    }
+}
+
 fn tick_listener(query: Query<&LocalPlayerEvents>) {
     for local_player_events in &query {
         local_player_events.send(Event::Tick).unwrap();
@@ -0,0 +1,185 @@
+//! Find worlds that were opened to LAN.
+//!
+//! When a world is opened to LAN, the game announces it every 1.5 seconds by
+//! sending `[MOTD]<motd>[/MOTD][AD]<port>[/AD]` to the 224.0.2.60:4445
+//! multicast group.
+
+use futures::Stream;
+use log::{debug, trace};
+use std::{
+    io,
+    net::{Ipv4Addr, SocketAddr},
+};
+use tokio::net::UdpSocket;
+
+/// The multicast group that LAN worlds are announced to.
+pub const LAN_MULTICAST_ADDRESS: Ipv4Addr = Ipv4Addr::new(224, 0, 2, 60);
+/// The port that LAN worlds are announced on.
+pub const LAN_MULTICAST_PORT: u16 = 4445;
+
+/// A world that was announced on the local network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanServer {
+    /// The name of the world, or "missing no" if the announcement didn't
+    /// have one (like in vanilla).
+    pub motd: String,
+    /// The address to join the world at. This is the address the
+    /// announcement came from with the port from the announcement.
+    pub address: SocketAddr,
+}
+
+/// Listens for LAN world announcements.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use azalea_client::lan::LanDiscovery;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let mut discovery = LanDiscovery::new().await.unwrap();
+///     loop {
+///         let server = discovery.next_server().await.unwrap();
+///         println!("{} at {}", server.motd, server.address);
+///     }
+/// }
+/// ```
+pub struct LanDiscovery {
+    socket: UdpSocket,
+}
+
+impl LanDiscovery {
+    /// Join the LAN multicast group.
+    ///
+    /// This binds to port 4445, so it'll fail if something else (like the
+    /// game) is already listening for LAN worlds on this machine.
+    pub async fn new() -> io::Result<Self> {
+        let socket = UdpSocket::bind(SocketAddr::from((
+            Ipv4Addr::UNSPECIFIED,
+            LAN_MULTICAST_PORT,
+        )))
+        .await?;
+        socket.join_multicast_v4(LAN_MULTICAST_ADDRESS, Ipv4Addr::UNSPECIFIED)?;
+        Ok(Self { socket })
+    }
+
+    /// Listen for announcements on a specific address without joining the
+    /// multicast group. This is mostly useful for testing.
+    pub async fn bind(address: SocketAddr) -> io::Result<Self> {
+        let socket = UdpSocket::bind(address).await?;
+        Ok(Self { socket })
+    }
+
+    /// The address we're listening on.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Wait for the next announcement. Announcements that can't be parsed
+    /// are skipped.
+    ///
+    /// Worlds are announced repeatedly, so you'll get the same world more
+    /// than once.
+    pub async fn next_server(&mut self) -> io::Result<LanServer> {
+        let mut buf = [0; 1024];
+        loop {
+            let (len, from) = self.socket.recv_from(&mut buf).await?;
+            let announcement = String::from_utf8_lossy(&buf[..len]);
+            trace!("Got LAN announcement from {from}: {announcement}");
+            match parse_announcement(&announcement, from) {
+                Some(server) => return Ok(server),
+                None => debug!("Ignoring invalid LAN announcement from {from}: {announcement}"),
+            }
+        }
+    }
+
+    /// Turn this into a stream of announced worlds.
+    pub fn into_stream(self) -> impl Stream<Item = io::Result<LanServer>> {
+        futures::stream::unfold(self, |mut discovery| async move {
+            let server = discovery.next_server().await;
+            Some((server, discovery))
+        })
+    }
+}
+
+/// Make the announcement that the game sends for a world.
+pub fn make_announcement(motd: &str, port: u16) -> String {
+    format!("[MOTD]{motd}[/MOTD][AD]{port}[/AD]")
+}
+
+/// Parse an announcement that was sent from `from`. This returns `None` if
+/// it doesn't have a valid port.
+pub fn parse_announcement(announcement: &str, from: SocketAddr) -> Option<LanServer> {
+    let motd = between(announcement, "[MOTD]", "[/MOTD]").unwrap_or("missing no");
+
+    // the address always comes after the motd
+    let after_motd = &announcement[announcement.find("[/MOTD]")? + "[/MOTD]".len()..];
+    let port = between(after_motd, "[AD]", "[/AD]")?;
+    // the game only sends the port, but older versions sent host:port
+    let port = port.rsplit(':').next()?.trim().parse().ok()?;
+
+    Some(LanServer {
+        motd: motd.to_string(),
+        address: SocketAddr::new(from.ip(), port),
+    })
+}
+
+fn between<'a>(s: &'a str, start: &str, end: &str) -> Option<&'a str> {
+    let s = &s[s.find(start)? + start.len()..];
+    Some(&s[..s.find(end)?])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_announcement() {
+        let from = "192.168.1.5:50000".parse().unwrap();
+        assert_eq!(
+            parse_announcement(&make_announcement("Steve - New World", 41234), from),
+            Some(LanServer {
+                motd: "Steve - New World".to_string(),
+                address: "192.168.1.5:41234".parse().unwrap(),
+            })
+        );
+        assert_eq!(
+            parse_announcement("[MOTD]world[/MOTD][AD]0.0.0.0:25565[/AD]", from)
+                .unwrap()
+                .address,
+            "192.168.1.5:25565".parse().unwrap()
+        );
+        assert_eq!(
+            parse_announcement("[MOTD]world[/MOTD]", from),
+            None,
+            "announcements without an address should be ignored"
+        );
+        assert_eq!(
+            parse_announcement("[MOTD]world[/MOTD][AD]abc[/AD]", from),
+            None
+        );
+        assert_eq!(
+            parse_announcement("[AD]25565[/AD]", from),
+            None,
+            "the address has to come after the motd"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_discovery() {
+        let mut discovery = LanDiscovery::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let announcer = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let to = discovery.local_addr().unwrap();
+        announcer.send_to(b"garbage", to).await.unwrap();
+        announcer
+            .send_to(make_announcement("My World", 25566).as_bytes(), to)
+            .await
+            .unwrap();
+
+        let server = discovery.next_server().await.unwrap();
+        assert_eq!(server.motd, "My World");
+        assert_eq!(server.address, "127.0.0.1:25566".parse().unwrap());
+    }
+}
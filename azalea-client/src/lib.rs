@@ -14,15 +14,19 @@
 mod account;
 pub mod chat;
 mod client;
+pub mod commands;
 pub mod disconnect;
 mod entity_query;
 mod events;
 mod get_mc_dir;
+pub mod lan;
 mod local_player;
 mod movement;
 pub mod packet_handling;
 pub mod ping;
 mod player;
+pub mod plugin_channels;
+pub mod replay;
 pub mod task_pool;
 
 pub use account::Account;
@@ -30,6 +34,6 @@ pub use client::{
     init_ecs_app, start_ecs, Client, ClientInformation, JoinError, JoinedClientBundle, TabList,
 };
 pub use events::Event;
-pub use local_player::{GameProfileComponent, LocalPlayer};
+pub use local_player::{GameProfileComponent, LocalPlayer, WorldBorder};
 pub use movement::{SprintDirection, StartSprintEvent, StartWalkEvent, WalkDirection};
 pub use player::PlayerInfo;
@@ -1,4 +1,8 @@
-use std::{io, sync::Arc};
+use std::{
+    io,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use azalea_auth::game_profile::GameProfile;
 use azalea_core::ChunkPos;
@@ -73,6 +77,72 @@ pub struct PhysicsState {
 #[derive(Component, Clone, Debug, Deref, DerefMut)]
 pub struct GameProfileComponent(pub GameProfile);
 
+/// The world border that the server told us about. This is updated from the
+/// border packets, and is inserted for every [`LocalPlayer`].
+#[derive(Component, Clone, Debug)]
+pub struct WorldBorder {
+    pub center_x: f64,
+    pub center_z: f64,
+    /// The length of one side of the border. If the border is currently
+    /// shrinking or growing, this is the smaller of its old and new size, so
+    /// nothing that's about to be outside of it is treated as safe.
+    pub size: f64,
+    /// The size the border is moving to and when it gets there, if it's
+    /// currently shrinking or growing.
+    pub lerp_target: Option<(f64, Instant)>,
+}
+impl Default for WorldBorder {
+    fn default() -> Self {
+        // the vanilla default
+        Self {
+            center_x: 0.,
+            center_z: 0.,
+            size: 59999968.,
+            lerp_target: None,
+        }
+    }
+}
+impl WorldBorder {
+    /// Start moving the border from `old_size` to `new_size` over
+    /// `lerp_time` milliseconds. If the lerp time is too long to represent,
+    /// the border is treated as never finishing, so it stays at the smaller
+    /// of the two sizes.
+    pub fn lerp_size(&mut self, old_size: f64, new_size: f64, lerp_time: u64) {
+        if lerp_time == 0 {
+            self.size = new_size;
+            self.lerp_target = None;
+        } else {
+            self.size = old_size.min(new_size);
+            self.lerp_target = Instant::now()
+                .checked_add(Duration::from_millis(lerp_time))
+                .map(|end| (new_size, end));
+        }
+    }
+
+    /// The size of the border right now. This is the same as [`Self::size`]
+    /// unless the border has finished moving since it started.
+    pub fn current_size(&self) -> f64 {
+        match self.lerp_target {
+            Some((target, end)) if Instant::now() >= end => target,
+            _ => self.size,
+        }
+    }
+
+    /// How far the given horizontal position is from the closest edge of the
+    /// border. This is negative if the position is outside of the border.
+    pub fn distance_to_edge(&self, x: f64, z: f64) -> f64 {
+        let half_size = self.current_size() / 2.;
+        let distance_x = half_size - (x - self.center_x).abs();
+        let distance_z = half_size - (z - self.center_z).abs();
+        distance_x.min(distance_z)
+    }
+
+    /// Whether the given horizontal position is inside of the border.
+    pub fn contains(&self, x: f64, z: f64) -> bool {
+        self.distance_to_edge(x, z) > 0.
+    }
+}
+
 /// Marks a [`LocalPlayer`] that's in a loaded chunk. This is updated at the
 /// beginning of every tick.
 #[derive(Component)]
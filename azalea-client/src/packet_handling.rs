@@ -6,7 +6,8 @@ use azalea_protocol::{
     packets::game::{
         clientbound_player_combat_kill_packet::ClientboundPlayerCombatKillPacket,
         serverbound_accept_teleportation_packet::ServerboundAcceptTeleportationPacket,
-        serverbound_custom_payload_packet::ServerboundCustomPayloadPacket,
+        serverbound_chat_ack_packet::ServerboundChatAckPacket,
+        serverbound_chat_session_update_packet::ServerboundChatSessionUpdatePacket,
         serverbound_keep_alive_packet::ServerboundKeepAlivePacket,
         serverbound_move_player_pos_rot_packet::ServerboundMovePlayerPosRotPacket,
         ClientboundGamePacket, ServerboundGamePacket,
@@ -28,7 +29,7 @@ use bevy_ecs::{
     entity::Entity,
     event::{EventReader, EventWriter, Events},
     schedule::IntoSystemConfig,
-    system::{Commands, Query, ResMut, SystemState},
+    system::{Commands, Query, Res, ResMut, SystemState},
     world::World,
 };
 use log::{debug, error, trace, warn};
@@ -36,10 +37,15 @@ use parking_lot::Mutex;
 use tokio::sync::mpsc;
 
 use crate::{
-    chat::{ChatPacket, ChatReceivedEvent},
+    chat::{
+        ChatDeletedEvent, ChatPacket, ChatReceivedEvent, ChatSigningSession, ChatVerification,
+        ChatVerifier, LastSeenMessagesTracker, PlayerChatPacket,
+    },
     client::TabList,
+    commands::CommandTree,
     disconnect::DisconnectEvent,
-    local_player::{GameProfileComponent, LocalPlayer},
+    local_player::{GameProfileComponent, LocalPlayer, WorldBorder},
+    plugin_channels::{PluginChannels, PluginMessageEvent},
     ClientInformation, PlayerInfo,
 };
 
@@ -88,7 +94,8 @@ impl Plugin for PacketHandlerPlugin {
             .add_event::<UpdatePlayerEvent>()
             .add_event::<ChatReceivedEvent>()
             .add_event::<DeathEvent>()
-            .add_event::<KeepAliveEvent>();
+            .add_event::<KeepAliveEvent>()
+            .add_event::<BundleReceivedEvent>();
     }
 }
 
@@ -136,6 +143,23 @@ pub struct KeepAliveEvent {
     pub id: u64,
 }
 
+/// The server sent a group of packets that are meant to be applied together,
+/// like an entity being spawned along with its metadata and equipment.
+///
+/// The packets were all handled in the same update, so systems never see
+/// only part of the bundle. They're also sent as [`PacketEvent`]s like usual,
+/// including the [`ClientboundGamePacket::Bundle`] delimiters around them.
+#[derive(Debug, Clone)]
+pub struct BundleReceivedEvent {
+    /// The local player entity that received the bundle.
+    pub entity: Entity,
+    /// The packets in the bundle, not including the delimiters.
+    pub packets: Vec<ClientboundGamePacket>,
+}
+
+/// The most packets that can be in a bundle, which is the same as vanilla.
+pub const MAX_BUNDLE_SIZE: usize = 4096;
+
 /// Something that receives packets from the server.
 #[derive(Component, Clone)]
 pub struct PacketReceiver {
@@ -146,6 +170,7 @@ pub struct PacketReceiver {
 pub fn send_packet_events(
     query: Query<(Entity, &PacketReceiver)>,
     mut packet_events: ResMut<Events<PacketEvent>>,
+    mut bundle_events: EventWriter<BundleReceivedEvent>,
 ) {
     // we manually clear and send the events at the beginning of each update
     // since otherwise it'd cause issues with events in process_packet_events
@@ -154,7 +179,21 @@ pub fn send_packet_events(
     for (player_entity, packet_receiver) in &query {
         let mut packets = packet_receiver.packets.lock();
         if !packets.is_empty() {
+            // the read task only queues whole bundles, so they always end in
+            // this update
+            let mut bundle: Option<Vec<ClientboundGamePacket>> = None;
             for packet in packets.iter() {
+                if let ClientboundGamePacket::Bundle(_) = packet {
+                    match bundle.take() {
+                        Some(packets) => bundle_events.send(BundleReceivedEvent {
+                            entity: player_entity,
+                            packets,
+                        }),
+                        None => bundle = Some(Vec::new()),
+                    }
+                } else if let Some(bundle) = &mut bundle {
+                    bundle.push(packet.clone());
+                }
                 packet_events.send(PacketEvent {
                     entity: player_entity,
                     packet: packet.clone(),
@@ -191,12 +230,20 @@ fn process_packet_events(ecs: &mut World) {
                         Option<&mut WorldName>,
                         &GameProfileComponent,
                         &ClientInformation,
+                        Option<&ChatSigningSession>,
                     )>,
                     ResMut<InstanceContainer>,
+                    Option<Res<PluginChannels>>,
                 )> = SystemState::new(ecs);
-                let (mut commands, mut query, mut world_container) = system_state.get_mut(ecs);
-                let (mut local_player, world_name, game_profile, client_information) =
-                    query.get_mut(player_entity).unwrap();
+                let (mut commands, mut query, mut world_container, plugin_channels) =
+                    system_state.get_mut(ecs);
+                let (
+                    mut local_player,
+                    world_name,
+                    game_profile,
+                    client_information,
+                    chat_signing_session,
+                ) = query.get_mut(player_entity).unwrap();
 
                 {
                     let dimension = &p
@@ -262,15 +309,24 @@ fn process_packet_events(ecs: &mut World) {
                 );
                 local_player.write_packet(client_information.clone().get());
 
-                // brand
-                local_player.write_packet(
-                    ServerboundCustomPayloadPacket {
-                        identifier: ResourceLocation::new("brand"),
-                        // they don't have to know :)
-                        data: "vanilla".into(),
-                    }
-                    .get(),
-                );
+                // brand and the channels we're listening on
+                let join_packets = match plugin_channels {
+                    Some(plugin_channels) => plugin_channels.join_packets(),
+                    None => PluginChannels::default().join_packets(),
+                };
+                for packet in join_packets {
+                    local_player.write_packet(packet.get());
+                }
+
+                // tell the server which key our chat messages are signed with
+                if let Some(chat_signing_session) = chat_signing_session {
+                    local_player.write_packet(
+                        ServerboundChatSessionUpdatePacket {
+                            chat_session: chat_signing_session.session_data(),
+                        }
+                        .get(),
+                    );
+                }
 
                 system_state.apply(ecs);
             }
@@ -279,12 +335,27 @@ fn process_packet_events(ecs: &mut World) {
             }
             ClientboundGamePacket::CustomPayload(p) => {
                 debug!("Got custom payload packet {:?}", p);
+
+                let mut system_state: SystemState<EventWriter<PluginMessageEvent>> =
+                    SystemState::new(ecs);
+                let mut plugin_message_events = system_state.get_mut(ecs);
+                plugin_message_events.send(PluginMessageEvent {
+                    entity: player_entity,
+                    channel: p.identifier,
+                    data: p.data.0,
+                });
             }
             ClientboundGamePacket::ChangeDifficulty(p) => {
                 debug!("Got difficulty packet {:?}", p);
             }
-            ClientboundGamePacket::Commands(_p) => {
+            ClientboundGamePacket::Commands(p) => {
                 debug!("Got declare commands packet");
+
+                let mut system_state: SystemState<Query<&mut CommandTree>> = SystemState::new(ecs);
+                let mut query = system_state.get_mut(ecs);
+                if let Ok(mut command_tree) = query.get_mut(player_entity) {
+                    *command_tree = CommandTree::new(p);
+                }
             }
             ClientboundGamePacket::PlayerAbilities(p) => {
                 debug!("Got player abilities packet {:?}", p);
@@ -329,9 +400,10 @@ fn process_packet_events(ecs: &mut World) {
                 > = SystemState::new(ecs);
                 let mut query = system_state.get_mut(ecs);
                 let Ok((mut local_player, mut physics, mut position, mut last_sent_position)) =
-                        query.get_mut(player_entity) else {
-                            continue;
-                        };
+                    query.get_mut(player_entity)
+                else {
+                    continue;
+                };
 
                 let delta_movement = physics.delta;
 
@@ -406,14 +478,15 @@ fn process_packet_events(ecs: &mut World) {
             ClientboundGamePacket::PlayerInfoUpdate(p) => {
                 debug!("Got player info packet {:?}", p);
 
+                #[allow(clippy::type_complexity)]
                 let mut system_state: SystemState<(
-                    Query<&mut TabList>,
+                    Query<(&mut TabList, &mut ChatVerifier)>,
                     EventWriter<AddPlayerEvent>,
                     EventWriter<UpdatePlayerEvent>,
                 )> = SystemState::new(ecs);
                 let (mut query, mut add_player_events, mut update_player_events) =
                     system_state.get_mut(ecs);
-                let mut tab_list = query.get_mut(player_entity).unwrap();
+                let (mut tab_list, mut chat_verifier) = query.get_mut(player_entity).unwrap();
 
                 for updated_info in &p.entries {
                     // add the new player maybe
@@ -424,7 +497,9 @@ fn process_packet_events(ecs: &mut World) {
                             gamemode: updated_info.game_mode,
                             latency: updated_info.latency,
                             display_name: updated_info.display_name.clone(),
+                            chat_session: updated_info.chat_session.clone(),
                         };
+                        chat_verifier.set_session(info.uuid, info.chat_session.as_ref());
                         tab_list.insert(updated_info.profile.uuid, info.clone());
                         add_player_events.send(AddPlayerEvent {
                             entity: player_entity,
@@ -433,6 +508,10 @@ fn process_packet_events(ecs: &mut World) {
                     } else if let Some(info) = tab_list.get_mut(&updated_info.profile.uuid) {
                         // `else if` because the block for add_player above
                         // already sets all the fields
+                        if p.actions.initialize_chat {
+                            info.chat_session = updated_info.chat_session.clone();
+                            chat_verifier.set_session(info.uuid, info.chat_session.as_ref());
+                        }
                         if p.actions.update_game_mode {
                             info.gamemode = updated_info.game_mode;
                         }
@@ -455,14 +534,16 @@ fn process_packet_events(ecs: &mut World) {
                 }
             }
             ClientboundGamePacket::PlayerInfoRemove(p) => {
+                #[allow(clippy::type_complexity)]
                 let mut system_state: SystemState<(
-                    Query<&mut TabList>,
+                    Query<(&mut TabList, &mut ChatVerifier)>,
                     EventWriter<RemovePlayerEvent>,
                 )> = SystemState::new(ecs);
                 let (mut query, mut remove_player_events) = system_state.get_mut(ecs);
-                let mut tab_list = query.get_mut(player_entity).unwrap();
+                let (mut tab_list, mut chat_verifier) = query.get_mut(player_entity).unwrap();
 
                 for uuid in &p.profile_ids {
+                    chat_verifier.remove_player(uuid);
                     if let Some(info) = tab_list.remove(uuid) {
                         remove_player_events.send(RemovePlayerEvent {
                             entity: player_entity,
@@ -540,6 +621,7 @@ fn process_packet_events(ecs: &mut World) {
                 let (mut commands, mut query) = system_state.get_mut(ecs);
                 let world_name = query.get_mut(player_entity).unwrap();
 
+                let mut spawned = None;
                 if let Some(WorldName(world_name)) = world_name {
                     let bundle = p.as_entity_bundle(world_name.clone());
                     let mut entity_commands = commands.spawn((
@@ -550,11 +632,15 @@ fn process_packet_events(ecs: &mut World) {
                     // the bundle doesn't include the default entity metadata so we add that
                     // separately
                     p.apply_metadata(&mut entity_commands);
+                    spawned = Some((world_name.clone(), entity_commands.id()));
                 } else {
                     warn!("got add player packet but we haven't gotten a login packet yet");
                 }
 
                 system_state.apply(ecs);
+                if let Some((world_name, entity)) = spawned {
+                    index_new_entity(ecs, &world_name, MinecraftEntityId(p.id), entity);
+                }
             }
             ClientboundGamePacket::SetEntityData(p) => {
                 debug!("Got set entity data packet {:?}", p);
@@ -607,6 +693,7 @@ fn process_packet_events(ecs: &mut World) {
                 let (mut commands, mut query) = system_state.get_mut(ecs);
                 let (tab_list, world_name) = query.get_mut(player_entity).unwrap();
 
+                let mut spawned_entity = None;
                 if let Some(WorldName(world_name)) = world_name {
                     let bundle = p.as_player_bundle(world_name.clone());
                     let mut spawned = commands.spawn((
@@ -618,14 +705,26 @@ fn process_packet_events(ecs: &mut World) {
                     if let Some(player_info) = tab_list.get(&p.uuid) {
                         spawned.insert(GameProfileComponent(player_info.profile.clone()));
                     }
+                    spawned_entity = Some((world_name.clone(), spawned.id()));
                 } else {
                     warn!("got add player packet but we haven't gotten a login packet yet");
                 }
 
                 system_state.apply(ecs);
+                if let Some((world_name, entity)) = spawned_entity {
+                    index_new_entity(ecs, &world_name, MinecraftEntityId(p.id), entity);
+                }
             }
             ClientboundGamePacket::InitializeBorder(p) => {
                 debug!("Got initialize border packet {:?}", p);
+
+                let mut system_state: SystemState<Query<&mut WorldBorder>> = SystemState::new(ecs);
+                let mut query = system_state.get_mut(ecs);
+                if let Ok(mut world_border) = query.get_mut(player_entity) {
+                    world_border.center_x = p.new_center_x;
+                    world_border.center_z = p.new_center_z;
+                    world_border.lerp_size(p.old_size, p.new_size, p.lerp_time);
+                }
             }
             ClientboundGamePacket::SetTime(_p) => {
                 // debug!("Got set time packet {:?}", p);
@@ -775,13 +874,48 @@ fn process_packet_events(ecs: &mut World) {
             ClientboundGamePacket::PlayerChat(p) => {
                 debug!("Got player chat packet {:?}", p);
 
-                let mut system_state: SystemState<EventWriter<ChatReceivedEvent>> =
-                    SystemState::new(ecs);
-                let mut chat_events = system_state.get_mut(ecs);
+                #[allow(clippy::type_complexity)]
+                let mut system_state: SystemState<(
+                    Query<(
+                        &mut LastSeenMessagesTracker,
+                        &mut ChatVerifier,
+                        &mut LocalPlayer,
+                    )>,
+                    EventWriter<ChatReceivedEvent>,
+                )> = SystemState::new(ecs);
+                let (mut query, mut chat_events) = system_state.get_mut(ecs);
+                let (mut last_seen_messages, mut chat_verifier, mut local_player) =
+                    query.get_mut(player_entity).unwrap();
+
+                let verification = chat_verifier.verify(&p);
+                if verification == ChatVerification::Invalid {
+                    warn!(
+                        "Got a chat message from {} with an invalid signature",
+                        p.sender
+                    );
+                }
+
+                if let Some(signature) = &p.signature {
+                    // the server kicks us if we let too many messages go
+                    // unacknowledged
+                    if last_seen_messages.add_pending(signature.clone(), true)
+                        && last_seen_messages.offset() > 64
+                    {
+                        local_player.write_packet(
+                            ServerboundChatAckPacket {
+                                messages: last_seen_messages.get_and_clear_offset(),
+                            }
+                            .get(),
+                        );
+                    }
+                }
 
                 chat_events.send(ChatReceivedEvent {
                     entity: player_entity,
-                    packet: ChatPacket::Player(Arc::new(p.clone())),
+                    packet: ChatPacket::Player(Arc::new(PlayerChatPacket {
+                        packet: p.clone(),
+                        verification,
+                    })),
                 });
             }
             ClientboundGamePacket::SystemChat(p) => {
@@ -859,7 +993,24 @@ fn process_packet_events(ecs: &mut World) {
             ClientboundGamePacket::ContainerSetSlot(_) => {}
             ClientboundGamePacket::Cooldown(_) => {}
             ClientboundGamePacket::CustomChatCompletions(_) => {}
-            ClientboundGamePacket::DeleteChat(_) => {}
+            ClientboundGamePacket::DeleteChat(p) => {
+                debug!("Got delete chat packet {:?}", p);
+
+                let mut system_state: SystemState<(
+                    Query<&ChatVerifier>,
+                    EventWriter<ChatDeletedEvent>,
+                )> = SystemState::new(ecs);
+                let (query, mut chat_deleted_events) = system_state.get_mut(ecs);
+                let chat_verifier = query.get(player_entity).unwrap();
+
+                match chat_verifier.signature_cache.unpack(&p.signature) {
+                    Some(signature) => chat_deleted_events.send(ChatDeletedEvent {
+                        entity: player_entity,
+                        signature,
+                    }),
+                    None => warn!("Server tried to delete a chat message we don't know about"),
+                }
+            }
             ClientboundGamePacket::Explode(_) => {}
             ClientboundGamePacket::ForgetLevelChunk(_) => {}
             ClientboundGamePacket::HorseScreenOpen(_) => {}
@@ -912,9 +1063,28 @@ fn process_packet_events(ecs: &mut World) {
 
             ClientboundGamePacket::SelectAdvancementsTab(_) => {}
             ClientboundGamePacket::SetActionBarText(_) => {}
-            ClientboundGamePacket::SetBorderCenter(_) => {}
-            ClientboundGamePacket::SetBorderLerpSize(_) => {}
-            ClientboundGamePacket::SetBorderSize(_) => {}
+            ClientboundGamePacket::SetBorderCenter(p) => {
+                let mut system_state: SystemState<Query<&mut WorldBorder>> = SystemState::new(ecs);
+                let mut query = system_state.get_mut(ecs);
+                if let Ok(mut world_border) = query.get_mut(player_entity) {
+                    world_border.center_x = p.new_center_x;
+                    world_border.center_z = p.new_center_z;
+                }
+            }
+            ClientboundGamePacket::SetBorderLerpSize(p) => {
+                let mut system_state: SystemState<Query<&mut WorldBorder>> = SystemState::new(ecs);
+                let mut query = system_state.get_mut(ecs);
+                if let Ok(mut world_border) = query.get_mut(player_entity) {
+                    world_border.lerp_size(p.old_size, p.new_size, p.lerp_time);
+                }
+            }
+            ClientboundGamePacket::SetBorderSize(p) => {
+                let mut system_state: SystemState<Query<&mut WorldBorder>> = SystemState::new(ecs);
+                let mut query = system_state.get_mut(ecs);
+                if let Ok(mut world_border) = query.get_mut(player_entity) {
+                    world_border.lerp_size(p.size, p.size, 0);
+                }
+            }
             ClientboundGamePacket::SetBorderWarningDelay(_) => {}
             ClientboundGamePacket::SetBorderWarningDistance(_) => {}
             ClientboundGamePacket::SetCamera(_) => {}
@@ -943,16 +1113,69 @@ fn process_packet_events(ecs: &mut World) {
     }
 }
 
+/// Add an entity that was just spawned to the world's id index right away,
+/// so packets that are handled later in the same update (like the rest of a
+/// bundle) can find it. Otherwise it'd only be indexed in
+/// [`EntityUpdateSet::Index`].
+fn index_new_entity(
+    ecs: &World,
+    world_name: &ResourceLocation,
+    id: MinecraftEntityId,
+    entity: Entity,
+) {
+    let Some(world) = ecs.resource::<InstanceContainer>().get(world_name) else {
+        return;
+    };
+    world.write().entity_by_id.entry(id).or_insert(entity);
+}
+
 impl PacketReceiver {
+    /// Queue a packet to be handled in the next update, and tell the client
+    /// to run the schedule. Packets in a bundle are held in `bundle` until
+    /// the bundle ends, so they're all handled in the same update.
+    ///
+    /// Returns `false` if we should stop reading, either because the client
+    /// was dropped or the server sent a bundle that's too big.
+    pub(crate) fn receive(
+        &self,
+        packet: ClientboundGamePacket,
+        bundle: &mut Option<Vec<ClientboundGamePacket>>,
+    ) -> bool {
+        let is_delimiter = matches!(packet, ClientboundGamePacket::Bundle(_));
+        match bundle.take() {
+            None if is_delimiter => {
+                *bundle = Some(vec![packet]);
+                return true;
+            }
+            None => self.packets.lock().push(packet),
+            Some(mut packets) => {
+                packets.push(packet);
+                if !is_delimiter {
+                    // the opening delimiter is in there too
+                    if packets.len() > MAX_BUNDLE_SIZE + 1 {
+                        error!("The server sent more than {MAX_BUNDLE_SIZE} packets in a bundle");
+                        return false;
+                    }
+                    *bundle = Some(packets);
+                    return true;
+                }
+                self.packets.lock().extend(packets);
+            }
+        }
+        // tell the client to run all the systems
+        self.run_schedule_sender.send(()).is_ok()
+    }
+
     /// Loop that reads from the connection and adds the packets to the queue +
     /// runs the schedule.
     pub async fn read_task(self, mut read_conn: ReadConnection<ClientboundGamePacket>) {
+        let mut bundle = None;
         loop {
             match read_conn.read().await {
                 Ok(packet) => {
-                    self.packets.lock().push(packet);
-                    // tell the client to run all the systems
-                    self.run_schedule_sender.send(()).unwrap();
+                    if !self.receive(packet, &mut bundle) {
+                        break;
+                    }
                 }
                 Err(error) => {
                     if !matches!(*error, ReadPacketError::ConnectionClosed) {
@@ -981,3 +1204,59 @@ impl PacketReceiver {
         // receiver is automatically closed when it's dropped
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azalea_protocol::packets::game::{
+        clientbound_bundle_packet::ClientboundBundlePacket,
+        clientbound_keep_alive_packet::ClientboundKeepAlivePacket,
+    };
+
+    fn packet_receiver() -> (PacketReceiver, mpsc::UnboundedReceiver<()>) {
+        let (run_schedule_sender, run_schedule_receiver) = mpsc::unbounded_channel();
+        let packet_receiver = PacketReceiver {
+            packets: Arc::new(Mutex::new(Vec::new())),
+            run_schedule_sender,
+        };
+        (packet_receiver, run_schedule_receiver)
+    }
+
+    fn keep_alive(id: u64) -> ClientboundGamePacket {
+        ClientboundKeepAlivePacket { id }.get()
+    }
+
+    #[test]
+    fn test_bundle_is_held_back() {
+        let (packet_receiver, mut run_schedule_receiver) = packet_receiver();
+        let mut bundle = None;
+
+        assert!(packet_receiver.receive(keep_alive(1), &mut bundle));
+        assert!(packet_receiver.receive(ClientboundBundlePacket {}.get(), &mut bundle));
+        assert!(packet_receiver.receive(keep_alive(2), &mut bundle));
+        assert!(packet_receiver.receive(keep_alive(3), &mut bundle));
+        // only the packet before the bundle can be handled so far
+        assert_eq!(packet_receiver.packets.lock().len(), 1);
+        assert!(run_schedule_receiver.try_recv().is_ok());
+        assert!(run_schedule_receiver.try_recv().is_err());
+
+        assert!(packet_receiver.receive(ClientboundBundlePacket {}.get(), &mut bundle));
+        assert!(bundle.is_none());
+        assert_eq!(packet_receiver.packets.lock().len(), 5);
+        assert!(run_schedule_receiver.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_bundle_too_big() {
+        let (packet_receiver, _run_schedule_receiver) = packet_receiver();
+        let mut bundle = None;
+
+        assert!(packet_receiver.receive(ClientboundBundlePacket {}.get(), &mut bundle));
+        // like vanilla, exactly MAX_BUNDLE_SIZE packets are fine
+        for i in 0..MAX_BUNDLE_SIZE as u64 {
+            assert!(packet_receiver.receive(keep_alive(i), &mut bundle));
+        }
+        assert!(!packet_receiver.receive(keep_alive(0), &mut bundle));
+        assert!(packet_receiver.packets.lock().is_empty());
+    }
+}
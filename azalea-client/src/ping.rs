@@ -11,10 +11,15 @@ use azalea_protocol::{
         },
         ConnectionProtocol, PROTOCOL_VERSION,
     },
+    proxy::Proxy,
     resolver, ServerAddress,
 };
 use std::io;
 use thiserror::Error;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
 
 #[derive(Error, Debug)]
 pub enum PingError {
@@ -28,6 +33,8 @@ pub enum PingError {
     WritePacket(#[from] io::Error),
     #[error("The given address could not be parsed into a ServerAddress")]
     InvalidAddress,
+    #[error("The server sent an invalid legacy ping response: {0:?}")]
+    InvalidLegacyResponse(String),
 }
 
 /// Ping a Minecraft server.
@@ -45,12 +52,44 @@ pub enum PingError {
 /// ```
 pub async fn ping_server(
     address: impl TryInto<ServerAddress>,
+) -> Result<ClientboundStatusResponsePacket, PingError> {
+    ping_server_inner(address, None).await
+}
+
+/// Ping a Minecraft server through a proxy.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use azalea_client::ping;
+/// use azalea_protocol::proxy::Proxy;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let proxy = Proxy::socks5("127.0.0.1:1080".parse().unwrap()).with_remote_dns();
+///     let response = ping::ping_server_with_proxy("play.hypixel.net", &proxy)
+///         .await
+///         .unwrap();
+///     println!("{}", response.description.to_ansi());
+/// }
+/// ```
+pub async fn ping_server_with_proxy(
+    address: impl TryInto<ServerAddress>,
+    proxy: &Proxy,
+) -> Result<ClientboundStatusResponsePacket, PingError> {
+    ping_server_inner(address, Some(proxy)).await
+}
+
+async fn ping_server_inner(
+    address: impl TryInto<ServerAddress>,
+    proxy: Option<&Proxy>,
 ) -> Result<ClientboundStatusResponsePacket, PingError> {
     let address: ServerAddress = address.try_into().map_err(|_| PingError::InvalidAddress)?;
 
-    let resolved_address = resolver::resolve_address(&address).await?;
+    let resolved_address = resolver::resolve_address_unless_proxied(&address, proxy).await?;
 
-    let mut conn = Connection::new(&resolved_address).await?;
+    let mut conn =
+        Connection::new_maybe_with_proxy(&address, resolved_address.as_ref(), proxy).await?;
 
     // send the client intention packet and switch to the status state
     conn.write(
@@ -79,3 +118,209 @@ pub async fn ping_server(
         }
     }
 }
+
+/// The protocol version we say we're on in legacy pings, which is 1.6.4's.
+const LEGACY_PROTOCOL_VERSION: u8 = 78;
+
+/// A server's response to a [`ping_server_legacy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegacyPingResponse {
+    /// The server's protocol version. This is `None` for servers older than
+    /// 1.4, which don't send it.
+    pub protocol_version: Option<i32>,
+    /// The name of the server's version, like "1.6.4". This is `None` for
+    /// servers older than 1.4.
+    pub server_version: Option<String>,
+    /// The server's MOTD, which can have legacy formatting codes in it.
+    pub motd: String,
+    pub online_players: i32,
+    pub max_players: i32,
+}
+
+/// Ping a server with the server list ping from before 1.7.
+///
+/// This works for old servers that don't understand [`ping_server`], and
+/// newer servers still answer it.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use azalea_client::ping;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let response = ping::ping_server_legacy("localhost").await.unwrap();
+///     println!("{}/{}", response.online_players, response.max_players);
+/// }
+/// ```
+pub async fn ping_server_legacy(
+    address: impl TryInto<ServerAddress>,
+) -> Result<LegacyPingResponse, PingError> {
+    let address: ServerAddress = address.try_into().map_err(|_| PingError::InvalidAddress)?;
+    let resolved_address = resolver::resolve_address(&address).await?;
+
+    let mut stream = TcpStream::connect(resolved_address).await?;
+    stream.write_all(&legacy_ping_request(&address)).await?;
+
+    // the response is a kick packet with the info in the reason
+    let packet_id = stream.read_u8().await?;
+    if packet_id != 0xff {
+        return Err(PingError::InvalidLegacyResponse(format!(
+            "expected a kick packet (0xff), got {packet_id:#04x}"
+        )));
+    }
+    let len = stream.read_u16().await?;
+    let mut reason = Vec::with_capacity(len as usize);
+    for _ in 0..len {
+        reason.push(stream.read_u16().await?);
+    }
+    let reason = String::from_utf16_lossy(&reason);
+
+    parse_legacy_response(&reason).ok_or(PingError::InvalidLegacyResponse(reason))
+}
+
+/// The request that 1.6 clients send, which is understood by every server
+/// since 1.4.
+fn legacy_ping_request(address: &ServerAddress) -> Vec<u8> {
+    fn write_utf16(buf: &mut Vec<u8>, s: &str) {
+        let chars = s.encode_utf16().collect::<Vec<_>>();
+        buf.extend((chars.len() as u16).to_be_bytes());
+        for c in chars {
+            buf.extend(c.to_be_bytes());
+        }
+    }
+
+    // server list ping and the plugin message packet
+    let mut buf = vec![0xfe, 0x01, 0xfa];
+    write_utf16(&mut buf, "MC|PingHost");
+
+    let mut data = vec![LEGACY_PROTOCOL_VERSION];
+    write_utf16(&mut data, &address.host);
+    data.extend((address.port as i32).to_be_bytes());
+
+    buf.extend((data.len() as u16).to_be_bytes());
+    buf.extend(data);
+    buf
+}
+
+fn parse_legacy_response(reason: &str) -> Option<LegacyPingResponse> {
+    if let Some(reason) = reason.strip_prefix("§1\0") {
+        // 1.4 and newer: §1, protocol, version, motd, online and max separated
+        // by null characters
+        let mut parts = reason.split('\0');
+        let response = LegacyPingResponse {
+            protocol_version: Some(parts.next()?.parse().ok()?),
+            server_version: Some(parts.next()?.to_string()),
+            motd: parts.next()?.to_string(),
+            online_players: parts.next()?.parse().ok()?,
+            max_players: parts.next()?.parse().ok()?,
+        };
+        return Some(response);
+    }
+
+    // older servers send motd§online§max, and the motd can't have § in it
+    // but we're lenient about it anyways
+    let mut parts = reason.rsplitn(3, '§');
+    let max_players = parts.next()?.parse().ok()?;
+    let online_players = parts.next()?.parse().ok()?;
+    let motd = parts.next()?.to_string();
+    Some(LegacyPingResponse {
+        protocol_version: None,
+        server_version: None,
+        motd,
+        online_players,
+        max_players,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_parse_legacy_response() {
+        assert_eq!(
+            parse_legacy_response("§1\0127\01.19.4\0A Minecraft Server\03\020"),
+            Some(LegacyPingResponse {
+                protocol_version: Some(127),
+                server_version: Some("1.19.4".to_string()),
+                motd: "A Minecraft Server".to_string(),
+                online_players: 3,
+                max_players: 20,
+            })
+        );
+        assert_eq!(
+            parse_legacy_response("A Minecraft Server§0§10"),
+            Some(LegacyPingResponse {
+                protocol_version: None,
+                server_version: None,
+                motd: "A Minecraft Server".to_string(),
+                online_players: 0,
+                max_players: 10,
+            })
+        );
+        assert_eq!(parse_legacy_response("§1\0127\01.19.4"), None);
+        assert_eq!(parse_legacy_response("You are banned"), None);
+    }
+
+    #[test]
+    fn test_legacy_ping_request() {
+        let request = legacy_ping_request(&ServerAddress {
+            host: "localhost".to_string(),
+            port: 25565,
+        });
+        let mut expected = vec![0xfe, 0x01, 0xfa, 0x00, 0x0b];
+        for c in "MC|PingHost".encode_utf16() {
+            expected.extend(c.to_be_bytes());
+        }
+        // 1 + 2 + 9 * 2 + 4
+        expected.extend([0x00, 0x19, 78, 0x00, 0x09]);
+        for c in "localhost".encode_utf16() {
+            expected.extend(c.to_be_bytes());
+        }
+        expected.extend(25565i32.to_be_bytes());
+        assert_eq!(request, expected);
+    }
+
+    #[tokio::test]
+    async fn test_ping_server_legacy() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        // a stand-in for an old server
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            assert_eq!(stream.read_u8().await.unwrap(), 0xfe);
+            assert_eq!(stream.read_u8().await.unwrap(), 0x01);
+            // read the rest of the request so closing doesn't reset the
+            // connection
+            assert_eq!(stream.read_u8().await.unwrap(), 0xfa);
+            let channel_len = stream.read_u16().await.unwrap() as usize;
+            stream
+                .read_exact(&mut vec![0; channel_len * 2])
+                .await
+                .unwrap();
+            let data_len = stream.read_u16().await.unwrap() as usize;
+            stream.read_exact(&mut vec![0; data_len]).await.unwrap();
+
+            let reason = "§1\078\01.6.4\0Old §aserver\01\08"
+                .encode_utf16()
+                .collect::<Vec<_>>();
+            let mut response = vec![0xff];
+            response.extend((reason.len() as u16).to_be_bytes());
+            for c in reason {
+                response.extend(c.to_be_bytes());
+            }
+            stream.write_all(&response).await.unwrap();
+        });
+
+        let response = ping_server_legacy(address).await.unwrap();
+        assert_eq!(response.protocol_version, Some(78));
+        assert_eq!(response.server_version.as_deref(), Some("1.6.4"));
+        assert_eq!(response.motd, "Old §aserver");
+        assert_eq!(response.online_players, 1);
+        assert_eq!(response.max_players, 8);
+        server.await.unwrap();
+    }
+}
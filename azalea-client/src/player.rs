@@ -1,6 +1,7 @@
 use azalea_auth::game_profile::GameProfile;
 use azalea_chat::FormattedText;
 use azalea_core::GameType;
+use azalea_protocol::packets::game::serverbound_chat_session_update_packet::RemoteChatSessionData;
 use azalea_world::entity::EntityInfos;
 use bevy_ecs::{
     event::EventReader,
@@ -24,6 +25,9 @@ pub struct PlayerInfo {
     /// from the player's normal username. Use `player_info.profile.name` to get
     /// the player's actual username.
     pub display_name: Option<FormattedText>,
+    /// The session that the player's chat messages are signed with, if they
+    /// have one.
+    pub chat_session: Option<RemoteChatSessionData>,
 }
 
 /// Add a [`GameProfileComponent`] when an [`AddPlayerEvent`] is received.
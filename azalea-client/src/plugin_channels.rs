@@ -0,0 +1,453 @@
+//! Plugin channels (custom payloads), which servers and mods use to send
+//! their own data.
+//!
+//! Channels are registered on the [`App`] with [`PluginChannelsAppExt`], and
+//! messages on them are decoded and sent as [`PluginMessage`] events. Every
+//! message is also sent as a raw [`PluginMessageEvent`], even if the channel
+//! wasn't registered.
+//!
+//! BungeeCord (and a lot of plugins) write their strings with Java's
+//! `DataOutput.writeUTF`, so use [`JavaUtf`] for those instead of [`String`].
+//!
+//! ```
+//! # use azalea_buf::McBuf;
+//! # use azalea_client::plugin_channels::{JavaUtf, PluginChannelsAppExt, PluginMessage};
+//! # use bevy_app::App;
+//! # use bevy_ecs::event::EventReader;
+//! #[derive(Clone, Debug, McBuf)]
+//! struct GetServer {
+//!     subchannel: JavaUtf,
+//!     server: JavaUtf,
+//! }
+//!
+//! fn handle_get_server(mut events: EventReader<PluginMessage<GetServer>>) {
+//!     for event in events.iter() {
+//!         println!("We're on {}", *event.payload.server);
+//!     }
+//! }
+//!
+//! # fn build(app: &mut App) {
+//! app.add_plugin_channel::<GetServer>("bungeecord:main")
+//!     .add_system(handle_get_server);
+//! # }
+//! ```
+
+use std::{
+    collections::HashMap,
+    io::{self, Cursor, Read, Write},
+    sync::Arc,
+};
+
+use azalea_buf::{BufReadError, McBufReadable, McBufWritable, UnsizedByteArray};
+use azalea_core::ResourceLocation;
+use azalea_protocol::packets::{
+    game::serverbound_custom_payload_packet::ServerboundCustomPayloadPacket,
+    login::clientbound_custom_query_packet::ClientboundCustomQueryPacket,
+};
+use bevy_app::{App, Plugin};
+use bevy_ecs::{
+    entity::Entity,
+    event::{EventReader, EventWriter},
+    system::Resource,
+};
+use derive_more::{Deref, DerefMut};
+use log::warn;
+
+use crate::Client;
+
+/// The brand we send to servers unless it's changed in [`PluginChannels`].
+pub const DEFAULT_BRAND: &str = "vanilla";
+
+/// The server sent a message on a plugin channel.
+#[derive(Debug, Clone)]
+pub struct PluginMessageEvent {
+    /// The local player entity that received the message.
+    pub entity: Entity,
+    pub channel: ResourceLocation,
+    pub data: Vec<u8>,
+}
+
+/// The server sent a message on a channel that was registered with
+/// [`PluginChannelsAppExt::add_plugin_channel`].
+#[derive(Debug, Clone)]
+pub struct PluginMessage<T> {
+    /// The local player entity that received the message.
+    pub entity: Entity,
+    pub channel: ResourceLocation,
+    pub payload: T,
+}
+
+/// A string that's written like Java's `DataOutput.writeUTF`, with a u16
+/// length and modified UTF-8 instead of the varint length that Minecraft
+/// uses. This is what BungeeCord's plugin messages use.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Deref, DerefMut)]
+pub struct JavaUtf(pub String);
+
+impl From<&str> for JavaUtf {
+    fn from(s: &str) -> Self {
+        Self(s.to_string())
+    }
+}
+
+impl McBufReadable for JavaUtf {
+    fn read_from(buf: &mut Cursor<&[u8]>) -> Result<Self, BufReadError> {
+        let length = u16::read_from(buf)? as usize;
+        let mut bytes = vec![0; length];
+        buf.read_exact(&mut bytes)?;
+
+        // modified utf-8 is utf-8 for each utf-16 code unit (so surrogate
+        // pairs are encoded separately), with null encoded as two bytes
+        let invalid = || BufReadError::InvalidUtf8 {
+            bytes: bytes.clone(),
+            lossy: String::from_utf8_lossy(&bytes).to_string(),
+        };
+        let continuation = |i: usize| match bytes.get(i) {
+            Some(b) if b & 0xc0 == 0x80 => Ok((b & 0x3f) as u16),
+            _ => Err(invalid()),
+        };
+        let mut units = Vec::with_capacity(length);
+        let mut i = 0;
+        while i < bytes.len() {
+            let b = bytes[i] as u16;
+            let (unit, unit_length) = match b {
+                0x00..=0x7f => (b, 1),
+                0xc0..=0xdf => (((b & 0x1f) << 6) | continuation(i + 1)?, 2),
+                0xe0..=0xef => (
+                    ((b & 0x0f) << 12) | (continuation(i + 1)? << 6) | continuation(i + 2)?,
+                    3,
+                ),
+                _ => return Err(invalid()),
+            };
+            units.push(unit);
+            i += unit_length;
+        }
+        String::from_utf16(&units).map(Self).map_err(|_| invalid())
+    }
+}
+
+impl McBufWritable for JavaUtf {
+    fn write_into(&self, buf: &mut impl Write) -> Result<(), io::Error> {
+        let mut bytes = Vec::with_capacity(self.len());
+        for unit in self.encode_utf16() {
+            match unit {
+                0x01..=0x7f => bytes.push(unit as u8),
+                0x00 | 0x80..=0x7ff => {
+                    bytes.extend([0xc0 | (unit >> 6) as u8, 0x80 | (unit & 0x3f) as u8])
+                }
+                _ => bytes.extend([
+                    0xe0 | (unit >> 12) as u8,
+                    0x80 | ((unit >> 6) & 0x3f) as u8,
+                    0x80 | (unit & 0x3f) as u8,
+                ]),
+            }
+        }
+        let length = u16::try_from(bytes.len()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("String is too long to write as UTF ({} bytes)", bytes.len()),
+            )
+        })?;
+        length.write_into(buf)?;
+        buf.write_all(&bytes)
+    }
+}
+
+type LoginQueryHandler =
+    Arc<dyn Fn(&mut Cursor<&[u8]>) -> Result<Option<Vec<u8>>, BufReadError> + Send + Sync>;
+
+/// The plugin channels that clients know about.
+///
+/// This is a resource so it can be changed before clients join, and it's
+/// read when they log in.
+#[derive(Resource, Clone)]
+pub struct PluginChannels {
+    /// The brand that's sent on `minecraft:brand` after joining.
+    pub brand: String,
+    /// The channels that are sent in `minecraft:register` after joining, in
+    /// the order they were registered.
+    channels: Vec<ResourceLocation>,
+    login_queries: HashMap<ResourceLocation, LoginQueryHandler>,
+}
+
+impl Default for PluginChannels {
+    fn default() -> Self {
+        Self {
+            brand: DEFAULT_BRAND.to_string(),
+            channels: Vec::new(),
+            login_queries: HashMap::new(),
+        }
+    }
+}
+
+impl PluginChannels {
+    /// Tell the server we're listening on this channel when we join.
+    pub fn register(&mut self, channel: ResourceLocation) {
+        if !self.channels.contains(&channel) {
+            self.channels.push(channel);
+        }
+    }
+
+    /// The channels that will be sent in `minecraft:register`.
+    pub fn channels(&self) -> impl Iterator<Item = &ResourceLocation> {
+        self.channels.iter()
+    }
+
+    /// Answer login queries on the channel with `handler`. If the handler
+    /// returns `None`, the server is told we don't understand the query.
+    pub fn add_login_query_handler<Q, R>(
+        &mut self,
+        channel: ResourceLocation,
+        handler: impl Fn(Q) -> Option<R> + Send + Sync + 'static,
+    ) where
+        Q: McBufReadable + 'static,
+        R: McBufWritable + 'static,
+    {
+        self.login_queries.insert(
+            channel,
+            Arc::new(move |buf| {
+                let Some(reply) = handler(Q::read_from(buf)?) else {
+                    return Ok(None);
+                };
+                let mut data = Vec::new();
+                reply.write_into(&mut data)?;
+                Ok(Some(data))
+            }),
+        );
+    }
+
+    /// The data to reply to a login query with, or `None` if we don't
+    /// understand it.
+    pub fn answer_login_query(&self, query: &ClientboundCustomQueryPacket) -> Option<Vec<u8>> {
+        let handler = self.login_queries.get(&query.identifier)?;
+        match handler(&mut Cursor::new(&query.data[..])) {
+            Ok(reply) => reply,
+            Err(e) => {
+                warn!("Couldn't read login query on {}: {e}", query.identifier);
+                None
+            }
+        }
+    }
+
+    /// The packets that tell the server our brand and which channels we're
+    /// listening on, which are sent right after joining.
+    pub fn join_packets(&self) -> Vec<ServerboundCustomPayloadPacket> {
+        let mut brand = Vec::new();
+        self.brand
+            .write_into(&mut brand)
+            .expect("Writing to a Vec can't fail");
+        let mut packets = vec![ServerboundCustomPayloadPacket {
+            identifier: ResourceLocation::new("brand"),
+            data: brand.into(),
+        }];
+        if !self.channels.is_empty() {
+            // the channel names are separated by null bytes
+            let channels = self
+                .channels
+                .iter()
+                .map(|channel| channel.to_string())
+                .collect::<Vec<_>>()
+                .join("\0");
+            packets.push(ServerboundCustomPayloadPacket {
+                identifier: ResourceLocation::new("register"),
+                data: channels.as_str().into(),
+            });
+        }
+        packets
+    }
+}
+
+/// Functions for registering plugin channels on an [`App`].
+pub trait PluginChannelsAppExt {
+    /// Decode messages on the channel as `T` and send them as
+    /// [`PluginMessage<T>`] events. The channel is also sent to the server
+    /// in `minecraft:register`.
+    fn add_plugin_channel<T>(&mut self, channel: &str) -> &mut Self
+    where
+        T: McBufReadable + Send + Sync + 'static;
+
+    /// Answer login queries on the channel with `handler`, see
+    /// [`PluginChannels::add_login_query_handler`].
+    fn add_login_query_handler<Q, R>(
+        &mut self,
+        channel: &str,
+        handler: impl Fn(Q) -> Option<R> + Send + Sync + 'static,
+    ) -> &mut Self
+    where
+        Q: McBufReadable + 'static,
+        R: McBufWritable + 'static;
+}
+
+impl PluginChannelsAppExt for App {
+    fn add_plugin_channel<T>(&mut self, channel: &str) -> &mut Self
+    where
+        T: McBufReadable + Send + Sync + 'static,
+    {
+        let channel = ResourceLocation::new(channel);
+        self.init_resource::<PluginChannels>()
+            .world
+            .resource_mut::<PluginChannels>()
+            .register(channel.clone());
+        self.add_event::<PluginMessage<T>>().add_system(
+            move |mut events: EventReader<PluginMessageEvent>,
+                  mut typed_events: EventWriter<PluginMessage<T>>| {
+                for event in events.iter().filter(|event| event.channel == channel) {
+                    match T::read_from(&mut Cursor::new(&event.data[..])) {
+                        Ok(payload) => typed_events.send(PluginMessage {
+                            entity: event.entity,
+                            channel: event.channel.clone(),
+                            payload,
+                        }),
+                        Err(e) => warn!("Couldn't read plugin message on {channel}: {e}"),
+                    }
+                }
+            },
+        )
+    }
+
+    fn add_login_query_handler<Q, R>(
+        &mut self,
+        channel: &str,
+        handler: impl Fn(Q) -> Option<R> + Send + Sync + 'static,
+    ) -> &mut Self
+    where
+        Q: McBufReadable + 'static,
+        R: McBufWritable + 'static,
+    {
+        self.init_resource::<PluginChannels>()
+            .world
+            .resource_mut::<PluginChannels>()
+            .add_login_query_handler(ResourceLocation::new(channel), handler);
+        self
+    }
+}
+
+pub struct PluginChannelsPlugin;
+impl Plugin for PluginChannelsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PluginChannels>()
+            .add_event::<PluginMessageEvent>();
+    }
+}
+
+impl Client {
+    /// Send a message to the server on a plugin channel.
+    ///
+    /// ```rust,no_run
+    /// # use azalea_client::plugin_channels::JavaUtf;
+    /// # fn example(bot: &azalea_client::Client) {
+    /// // ask BungeeCord which server we're on
+    /// bot.send_plugin_message("bungeecord:main", &JavaUtf::from("GetServer"));
+    /// # }
+    /// ```
+    pub fn send_plugin_message(&self, channel: &str, payload: &impl McBufWritable) {
+        let mut data = Vec::new();
+        payload
+            .write_into(&mut data)
+            .expect("Writing to a Vec can't fail");
+        self.write_packet(
+            ServerboundCustomPayloadPacket {
+                identifier: ResourceLocation::new(channel),
+                data: UnsizedByteArray(data),
+            }
+            .get(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azalea_buf::McBuf;
+
+    #[derive(McBuf)]
+    struct Add {
+        a: u32,
+        b: u32,
+    }
+
+    #[test]
+    fn test_join_packets() {
+        let mut plugin_channels = PluginChannels::default();
+        plugin_channels.register(ResourceLocation::new("bungeecord:main"));
+        plugin_channels.register(ResourceLocation::new("example:test"));
+        plugin_channels.register(ResourceLocation::new("bungeecord:main"));
+
+        let packets = plugin_channels.join_packets();
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[0].identifier, ResourceLocation::new("brand"));
+        assert_eq!(
+            String::read_from(&mut Cursor::new(&packets[0].data[..])).unwrap(),
+            "vanilla"
+        );
+        assert_eq!(packets[1].identifier, ResourceLocation::new("register"));
+        assert_eq!(&packets[1].data[..], b"bungeecord:main\0example:test");
+    }
+
+    #[test]
+    fn test_java_utf() {
+        let mut data = Vec::new();
+        JavaUtf::from("GetServer").write_into(&mut data).unwrap();
+        assert_eq!(&data[..], b"\x00\x09GetServer");
+
+        // null is two bytes, and characters outside of the bmp are written as
+        // two surrogates
+        let mut data = Vec::new();
+        JavaUtf::from("é\0😀").write_into(&mut data).unwrap();
+        assert_eq!(
+            &data[..],
+            b"\x00\x0a\xc3\xa9\xc0\x80\xed\xa0\xbd\xed\xb8\x80"
+        );
+        assert_eq!(
+            JavaUtf::read_from(&mut Cursor::new(&data[..])).unwrap(),
+            JavaUtf::from("é\0😀")
+        );
+
+        assert!(JavaUtf::read_from(&mut Cursor::new(&b"\x00\x01\xff"[..])).is_err());
+        assert!(JavaUtf::read_from(&mut Cursor::new(&b"\x00\x05hi"[..])).is_err());
+    }
+
+    #[test]
+    fn test_no_register_without_channels() {
+        let packets = PluginChannels::default().join_packets();
+        assert_eq!(packets.len(), 1);
+        assert_eq!(packets[0].identifier, ResourceLocation::new("brand"));
+    }
+
+    #[test]
+    fn test_answer_login_query() {
+        let mut plugin_channels = PluginChannels::default();
+        plugin_channels
+            .add_login_query_handler(ResourceLocation::new("example:add"), |Add { a, b }: Add| {
+                Some(a + b)
+            });
+        plugin_channels
+            .add_login_query_handler(ResourceLocation::new("example:never"), |_: u8| None::<u8>);
+
+        let query = |channel: &str, data: Vec<u8>| ClientboundCustomQueryPacket {
+            transaction_id: 0,
+            identifier: ResourceLocation::new(channel),
+            data: data.into(),
+        };
+
+        let mut data = Vec::new();
+        Add { a: 2, b: 3 }.write_into(&mut data).unwrap();
+        let reply = plugin_channels
+            .answer_login_query(&query("example:add", data))
+            .unwrap();
+        assert_eq!(u32::read_from(&mut Cursor::new(&reply[..])).unwrap(), 5);
+
+        assert_eq!(
+            plugin_channels.answer_login_query(&query("example:never", vec![1])),
+            None
+        );
+        // the payload is too short
+        assert_eq!(
+            plugin_channels.answer_login_query(&query("example:add", vec![0])),
+            None
+        );
+        assert_eq!(
+            plugin_channels.answer_login_query(&query("example:unknown", vec![])),
+            None
+        );
+    }
+}
@@ -0,0 +1,228 @@
+//! Record a client's packets, and play recordings back into the ECS as if
+//! they were coming from a live server.
+//!
+//! This is useful for reproducing bugs from a recording someone sent, for
+//! testing packet handling without a server, and for benchmarking.
+
+use crate::{
+    client::{init_ecs_app, start_ecs},
+    packet_handling::PacketReceiver,
+    Client, Event,
+};
+use azalea_auth::game_profile::GameProfile;
+use azalea_protocol::{
+    capture::{CaptureError, CaptureReader, PacketRecorder},
+    packets::{
+        game::{ClientboundGamePacket, ServerboundGamePacket},
+        login::ClientboundLoginPacket,
+    },
+    read::ReadPacketError,
+};
+use bevy_ecs::system::Resource;
+use log::trace;
+use parking_lot::Mutex;
+use std::{path::Path, sync::Arc, time::Duration};
+use thiserror::Error;
+use tokio::{sync::mpsc, time::Instant};
+
+/// Insert this resource into the ECS to record the packets of every client
+/// that joins from now on. You should only have one client connected while
+/// recording, since their packets will be mixed together otherwise.
+#[derive(Resource, Clone)]
+pub struct RecordPackets(pub PacketRecorder);
+
+#[derive(Error, Debug)]
+pub enum ReplayError {
+    #[error("{0}")]
+    Capture(#[from] CaptureError),
+    #[error("{0}")]
+    ReadPacket(#[from] Box<ReadPacketError>),
+    #[error("The capture doesn't have a game profile packet, so it's not from a client that joined a server")]
+    NoGameProfile,
+}
+
+/// The clientbound game packets from a capture, which can be played back with
+/// [`Client::replay`].
+pub struct ReplayConnection {
+    profile: GameProfile,
+    /// The packets and how long after the start of the game state they were
+    /// received.
+    packets: Vec<(Duration, ClientboundGamePacket)>,
+    /// Whether to wait between packets like the server did. If this is false,
+    /// all of the packets are sent as fast as possible.
+    pub realtime: bool,
+    /// Packets that the client sent while replaying.
+    sent_packets: Arc<Mutex<Vec<ServerboundGamePacket>>>,
+}
+
+impl ReplayConnection {
+    /// Read a capture that was made with [`RecordPackets`] or
+    /// [`Connection::record`](azalea_protocol::connect::Connection::record).
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        Self::new(CaptureReader::open(path)?)
+    }
+
+    pub fn new(capture: CaptureReader) -> Result<Self, ReplayError> {
+        let mut profile = None;
+        let mut game_start = None;
+        let mut packets = Vec::new();
+        for packet in capture {
+            let packet = packet?;
+            if packet.is::<ClientboundLoginPacket>() {
+                if let ClientboundLoginPacket::GameProfile(p) = packet.decode()? {
+                    profile = Some(p.game_profile);
+                }
+            } else if packet.is::<ClientboundGamePacket>() {
+                let game_start = *game_start.get_or_insert(packet.time);
+                packets.push((packet.time - game_start, packet.decode()?));
+            }
+        }
+        Ok(Self {
+            profile: profile.ok_or(ReplayError::NoGameProfile)?,
+            packets,
+            realtime: true,
+            sent_packets: Arc::new(Mutex::new(Vec::new())),
+        })
+    }
+
+    /// The game profile that the server sent the client in the capture.
+    pub fn profile(&self) -> &GameProfile {
+        &self.profile
+    }
+
+    /// The clientbound game packets that will be replayed.
+    pub fn packets(&self) -> impl Iterator<Item = &ClientboundGamePacket> {
+        self.packets.iter().map(|(_, packet)| packet)
+    }
+
+    /// A handle to the packets that the client sends while replaying, so you
+    /// can check how the client responded.
+    pub fn sent_packets(&self) -> Arc<Mutex<Vec<ServerboundGamePacket>>> {
+        self.sent_packets.clone()
+    }
+
+    async fn read_task(self, packet_receiver: PacketReceiver) {
+        let start = Instant::now();
+        let mut bundle = None;
+        for (time, packet) in self.packets {
+            if self.realtime {
+                tokio::time::sleep_until(start + time).await;
+            }
+            if !packet_receiver.receive(packet, &mut bundle) {
+                return;
+            }
+        }
+        trace!("Finished replaying packets");
+    }
+
+    async fn write_task(
+        sent_packets: Arc<Mutex<Vec<ServerboundGamePacket>>>,
+        mut write_receiver: mpsc::UnboundedReceiver<ServerboundGamePacket>,
+    ) {
+        while let Some(packet) = write_receiver.recv().await {
+            sent_packets.lock().push(packet);
+        }
+    }
+}
+
+impl Client {
+    /// Make a client that receives packets from a recording instead of a
+    /// server. Packets the client sends are kept in
+    /// [`ReplayConnection::sent_packets`] instead of going anywhere.
+    ///
+    /// This must be called from inside a Tokio runtime.
+    ///
+    /// ```rust,no_run
+    /// # use azalea_client::{Client, replay::ReplayConnection};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut replay = ReplayConnection::open("session.azcap")?;
+    /// replay.realtime = false;
+    /// let (client, mut rx) = Client::replay(replay);
+    /// while let Some(event) = rx.recv().await {
+    ///     // ...
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn replay(replay: ReplayConnection) -> (Self, mpsc::UnboundedReceiver<Event>) {
+        let (run_schedule_sender, run_schedule_receiver) = mpsc::unbounded_channel();
+        let app = init_ecs_app();
+        let ecs_lock = start_ecs(app, run_schedule_receiver, run_schedule_sender.clone());
+
+        let profile = replay.profile.clone();
+        Self::add_local_player(
+            ecs_lock,
+            profile,
+            // replays never sign messages
+            None,
+            run_schedule_sender,
+            |packet_receiver, packet_writer_receiver| {
+                let sent_packets = replay.sent_packets();
+                let read_packets_task = tokio::spawn(replay.read_task(packet_receiver));
+                let write_packets_task = tokio::spawn(ReplayConnection::write_task(
+                    sent_packets,
+                    packet_writer_receiver,
+                ));
+                (read_packets_task, write_packets_task)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azalea_protocol::{
+        capture::CaptureWriter,
+        packets::{
+            game::clientbound_keep_alive_packet::ClientboundKeepAlivePacket,
+            login::clientbound_game_profile_packet::ClientboundGameProfilePacket,
+        },
+    };
+    use uuid::Uuid;
+
+    fn capture() -> CaptureReader {
+        let mut buf = Vec::new();
+        let mut writer = CaptureWriter::new(&mut buf).unwrap();
+        writer
+            .write_packet(
+                &ClientboundGameProfilePacket {
+                    game_profile: GameProfile::new(Uuid::nil(), "bot".to_string()),
+                }
+                .get(),
+            )
+            .unwrap();
+        writer
+            .write_packet(&ClientboundKeepAlivePacket { id: 42 }.get())
+            .unwrap();
+        drop(writer);
+        CaptureReader::new(buf).unwrap()
+    }
+
+    #[test]
+    fn test_replay_connection() {
+        let replay = ReplayConnection::new(capture()).unwrap();
+        assert_eq!(replay.profile().name, "bot");
+        assert_eq!(replay.packets().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_replay_keep_alive() {
+        let mut replay = ReplayConnection::new(capture()).unwrap();
+        replay.realtime = false;
+        let sent_packets = replay.sent_packets();
+        let (_client, _rx) = Client::replay(replay);
+
+        for _ in 0..100 {
+            if !sent_packets.lock().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        match sent_packets.lock().first() {
+            Some(ServerboundGamePacket::KeepAlive(p)) => assert_eq!(p.id, 42),
+            p => panic!("Expected the client to respond with a keep alive, got {p:?}"),
+        }
+    }
+}
@@ -1,11 +1,147 @@
 #![doc = include_str!("../README.md")]
 
 use once_cell::sync::Lazy;
-use std::collections::HashMap;
+use parking_lot::RwLock;
+use std::{collections::HashMap, fs, io, path::Path, sync::Arc};
+use thiserror::Error;
 
+/// The translations from the embedded `en_us.json`.
 pub static STORAGE: Lazy<HashMap<String, String>> =
     Lazy::new(|| serde_json::from_str(include_str!("en_us.json")).unwrap());
 
-pub fn get(key: &str) -> Option<&str> {
-    STORAGE.get(key).map(|s| s.as_str())
+static EN_US: Lazy<Arc<Language>> =
+    Lazy::new(|| Arc::new(Language::from_bytes(include_bytes!("en_us.json")).unwrap()));
+
+/// Languages that are made the default are never freed, so [`get`] can return
+/// translations that borrow from them.
+static DEFAULT_LANGUAGE: Lazy<RwLock<&'static Arc<Language>>> = Lazy::new(|| RwLock::new(&*EN_US));
+
+#[derive(Error, Debug)]
+pub enum LanguageError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid language file: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// A set of translations, like the ones in a resource pack's
+/// `assets/minecraft/lang/de_de.json`.
+///
+/// Keys that a language doesn't have are looked up in `en_us` instead, like
+/// in vanilla.
+///
+/// ```
+/// use azalea_language::Language;
+///
+/// let language = Language::from_bytes(br#"{"death.attack.fall": "%1$s fiel zu tief"}"#).unwrap();
+/// assert_eq!(language.get("death.attack.fall"), Some("%1$s fiel zu tief"));
+/// assert_eq!(language.get("translation.test.none"), Some("Hello, world!"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Language {
+    translations: HashMap<String, String>,
+}
+
+impl Language {
+    /// The `en_us` language that's embedded in this crate.
+    pub fn en_us() -> &'static Language {
+        &EN_US
+    }
+
+    /// The same as [`Self::en_us`], but as an `Arc` so it can be made the
+    /// default language again without copying it.
+    pub fn en_us_arc() -> Arc<Language> {
+        Arc::clone(&*EN_US)
+    }
+
+    /// Read a language from the contents of a language JSON file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Language, LanguageError> {
+        // some language files are saved with a byte order mark
+        let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+        Ok(Language {
+            translations: serde_json::from_slice(bytes)?,
+        })
+    }
+
+    /// Read a language JSON file from disk.
+    pub fn load(path: impl AsRef<Path>) -> Result<Language, LanguageError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    /// Get the translation for a key, falling back to `en_us` if this
+    /// language doesn't have it.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        match self.translations.get(key) {
+            Some(translation) => Some(translation),
+            None if !std::ptr::eq(self, Self::en_us()) => Self::en_us().get(key),
+            None => None,
+        }
+    }
+
+    /// Whether this language has its own translation for the key, without
+    /// falling back to `en_us`.
+    pub fn has(&self, key: &str) -> bool {
+        self.translations.contains_key(key)
+    }
+}
+
+/// The language that [`get`] and text components use when one isn't given.
+/// This is `en_us` unless it was changed with [`set_default_language`].
+pub fn default_language() -> Arc<Language> {
+    Arc::clone(*DEFAULT_LANGUAGE.read())
+}
+
+/// Change the language that's used when one isn't given, for the whole
+/// program.
+///
+/// The language is never freed, since translations from [`get`] can borrow
+/// from it forever. This is meant to be called once when the program starts,
+/// not every time the language changes.
+pub fn set_default_language(language: impl Into<Arc<Language>>) {
+    let language = language.into();
+    if Arc::ptr_eq(&language, &*EN_US) {
+        *DEFAULT_LANGUAGE.write() = &*EN_US;
+        return;
+    }
+    *DEFAULT_LANGUAGE.write() = Box::leak(Box::new(language));
+}
+
+/// Get the translation for a key in the [default language](default_language).
+pub fn get(key: &str) -> Option<&'static str> {
+    let language: &'static Arc<Language> = *DEFAULT_LANGUAGE.read();
+    language.get(key)
+}
+
+/// Like [`get`], but returns an owned `String`.
+pub fn get_owned(key: &str) -> Option<String> {
+    get(key).map(str::to_owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallback() {
+        let language = Language::from_bytes(
+            "\u{feff}{\"language.name\": \"Deutsch\", \"key.jump\": \"Springen\"}".as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(language.get("key.jump"), Some("Springen"));
+        assert_eq!(language.get("key.sneak"), Some("Sneak"));
+        assert!(!language.has("key.sneak"));
+        assert_eq!(language.get("this.key.doesnt.exist"), None);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(matches!(
+            Language::from_bytes(b"[\"not\", \"an\", \"object\"]"),
+            Err(LanguageError::Json(_))
+        ));
+        assert!(matches!(
+            Language::load("this/file/doesnt/exist.json"),
+            Err(LanguageError::Io(_))
+        ));
+    }
 }
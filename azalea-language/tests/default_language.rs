@@ -0,0 +1,21 @@
+//! This changes the default language for the whole program, so it's kept in
+//! its own test binary where it can't affect other tests.
+
+use azalea_language::{default_language, get, get_owned, set_default_language, Language};
+use std::sync::Arc;
+
+#[test]
+fn test_default_language() {
+    assert_eq!(get("language.name"), Some("English"));
+    // the default is the same en_us as the embedded one, not a copy
+    assert!(std::ptr::eq(&*default_language(), Language::en_us()));
+
+    set_default_language(Language::from_bytes(br#"{"language.name": "Deutsch"}"#).unwrap());
+    assert_eq!(get("language.name"), Some("Deutsch"));
+    assert_eq!(get_owned("language.name").as_deref(), Some("Deutsch"));
+    assert_eq!(get("translation.test.none"), Some("Hello, world!"));
+
+    set_default_language(Language::en_us_arc());
+    assert_eq!(get("language.name"), Some("English"));
+    assert!(Arc::ptr_eq(&default_language(), &Language::en_us_arc()));
+}
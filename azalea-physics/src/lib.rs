@@ -56,57 +56,69 @@ fn travel(
         //     return;
         // }
 
-        let gravity: f64 = 0.08;
+        travel_step(&world, &mut physics, &mut position, attributes);
+    }
+}
 
-        // TODO: slow falling effect
-        // let is_falling = self.delta.y <= 0.;
+/// The part of [`travel`] that runs for a single entity. This is public so
+/// other crates can simulate movement without going through the ECS (for
+/// example to check what a move will do before it's executed).
+pub fn travel_step(
+    world: &Instance,
+    physics: &mut Physics,
+    position: &mut Position,
+    attributes: &Attributes,
+) {
+    let gravity: f64 = 0.08;
 
-        // TODO: fluids
+    // TODO: slow falling effect
+    // let is_falling = self.delta.y <= 0.;
 
-        // TODO: elytra
+    // TODO: fluids
 
-        let block_pos_below = get_block_pos_below_that_affects_movement(&position);
+    // TODO: elytra
 
-        let block_state_below = world
-            .chunks
-            .get_block_state(&block_pos_below)
-            .unwrap_or(BlockState::AIR);
-        let block_below: Box<dyn Block> = block_state_below.into();
-        let block_friction = block_below.behavior().friction;
+    let block_pos_below = get_block_pos_below_that_affects_movement(position);
 
-        let inertia = if physics.on_ground {
-            block_friction * 0.91
-        } else {
-            0.91
-        };
+    let block_state_below = world
+        .chunks
+        .get_block_state(&block_pos_below)
+        .unwrap_or(BlockState::AIR);
+    let block_below: Box<dyn Block> = block_state_below.into();
+    let block_friction = block_below.behavior().friction;
 
-        // this applies the current delta
-        let mut movement = handle_relative_friction_and_calculate_movement(
-            block_friction,
-            &world,
-            &mut physics,
-            &mut position,
-            attributes,
-        );
+    let inertia = if physics.on_ground {
+        block_friction * 0.91
+    } else {
+        0.91
+    };
 
-        movement.y -= gravity;
-
-        // if (this.shouldDiscardFriction()) {
-        //     this.setDeltaMovement(movement.x, yMovement, movement.z);
-        // } else {
-        //     this.setDeltaMovement(movement.x * (double)inertia, yMovement *
-        // 0.9800000190734863D, movement.z * (double)inertia); }
-
-        // if should_discard_friction(self) {
-        if false {
-            physics.delta = movement;
-        } else {
-            physics.delta = Vec3 {
-                x: movement.x * inertia as f64,
-                y: movement.y * 0.98f64,
-                z: movement.z * inertia as f64,
-            };
-        }
+    // this applies the current delta
+    let mut movement = handle_relative_friction_and_calculate_movement(
+        block_friction,
+        world,
+        physics,
+        position,
+        attributes,
+    );
+
+    movement.y -= gravity;
+
+    // if (this.shouldDiscardFriction()) {
+    //     this.setDeltaMovement(movement.x, yMovement, movement.z);
+    // } else {
+    //     this.setDeltaMovement(movement.x * (double)inertia, yMovement *
+    // 0.9800000190734863D, movement.z * (double)inertia); }
+
+    // if should_discard_friction(self) {
+    if false {
+        physics.delta = movement;
+    } else {
+        physics.delta = Vec3 {
+            x: movement.x * inertia as f64,
+            y: movement.y * 0.98f64,
+            z: movement.z * inertia as f64,
+        };
     }
 }
 
@@ -126,15 +138,7 @@ pub fn ai_step(
         // vanilla does movement interpolation here, doesn't really matter much for a
         // bot though
 
-        if physics.delta.x.abs() < 0.003 {
-            physics.delta.x = 0.;
-        }
-        if physics.delta.y.abs() < 0.003 {
-            physics.delta.y = 0.;
-        }
-        if physics.delta.z.abs() < 0.003 {
-            physics.delta.z = 0.;
-        }
+        discard_tiny_delta(&mut physics);
 
         if let Some(jumping) = jumping {
             if **jumping {
@@ -169,28 +173,77 @@ pub fn force_jump_listener(
                 .expect("All entities should be in a valid world");
             let world = world_lock.read();
 
-            let jump_power: f64 = jump_power(&world, position) as f64 + jump_boost_power();
-            let old_delta_movement = physics.delta;
-            physics.delta = Vec3 {
-                x: old_delta_movement.x,
-                y: jump_power,
-                z: old_delta_movement.z,
-            };
-            if **sprinting {
-                // sprint jumping gives some extra velocity
-                let y_rot = physics.y_rot * 0.017453292;
-                physics.delta += Vec3 {
-                    x: (-f32::sin(y_rot) * 0.2) as f64,
-                    y: 0.,
-                    z: (f32::cos(y_rot) * 0.2) as f64,
-                };
-            }
-
-            physics.has_impulse = true;
+            jump_from_ground(&world, &mut physics, position, **sprinting);
         }
     }
 }
 
+/// Set the entity's vertical velocity to make it jump, giving it extra
+/// horizontal velocity if it's sprinting.
+fn jump_from_ground(world: &Instance, physics: &mut Physics, position: &Position, sprinting: bool) {
+    let jump_power: f64 = jump_power(world, position) as f64 + jump_boost_power();
+    let old_delta_movement = physics.delta;
+    physics.delta = Vec3 {
+        x: old_delta_movement.x,
+        y: jump_power,
+        z: old_delta_movement.z,
+    };
+    if sprinting {
+        // sprint jumping gives some extra velocity
+        let y_rot = physics.y_rot * 0.017453292;
+        physics.delta += Vec3 {
+            x: (-f32::sin(y_rot) * 0.2) as f64,
+            y: 0.,
+            z: (f32::cos(y_rot) * 0.2) as f64,
+        };
+    }
+
+    physics.has_impulse = true;
+}
+
+/// Vanilla zeroes out very small velocities at the start of every tick so
+/// entities don't drift forever.
+fn discard_tiny_delta(physics: &mut Physics) {
+    if physics.delta.x.abs() < 0.003 {
+        physics.delta.x = 0.;
+    }
+    if physics.delta.y.abs() < 0.003 {
+        physics.delta.y = 0.;
+    }
+    if physics.delta.z.abs() < 0.003 {
+        physics.delta.z = 0.;
+    }
+}
+
+/// Do a full physics tick ([`ai_step`] and [`travel`]) for an entity that
+/// isn't in the ECS.
+///
+/// The entity's `xxa`/`zza` should already be set to the movement impulses,
+/// and `attributes` should include the sprinting modifier if `sprinting` is
+/// true. This is meant for predicting movement, nothing is sent to the server.
+pub fn simulate_tick(
+    world: &Instance,
+    physics: &mut Physics,
+    position: &mut Position,
+    attributes: &Attributes,
+    sprinting: bool,
+    jumping: bool,
+) {
+    discard_tiny_delta(physics);
+
+    if jumping && physics.on_ground {
+        jump_from_ground(world, physics, position, sprinting);
+    }
+
+    physics.xxa *= 0.98;
+    physics.zza *= 0.98;
+
+    travel_step(world, physics, position, attributes);
+
+    // normally update_bounding_box does this when the position changes
+    physics.bounding_box = physics.dimensions.make_bounding_box(position);
+}
+
 fn get_block_pos_below_that_affects_movement(position: &Position) -> BlockPos {
     BlockPos::new(
         position.x.floor() as i32,
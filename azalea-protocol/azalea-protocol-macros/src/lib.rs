@@ -33,6 +33,14 @@ fn as_packet_derive(input: TokenStream, state: proc_macro2::TokenStream) -> Toke
                 use azalea_buf::McBufReadable;
                 Ok(Self::read_from(buf)?.get())
             }
+
+            pub fn read_shared(
+                buf: &mut std::io::Cursor<&[u8]>,
+                shared: &azalea_buf::Bytes,
+            ) -> Result<#state, azalea_buf::BufReadError> {
+                use azalea_buf::McBufReadable;
+                Ok(Self::read_from_shared(buf, shared)?.get())
+            }
         }
     };
 
@@ -191,6 +199,15 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
         Ident::new(&format!("Clientbound{}", input.name), input.name.span());
 
     let state_name_litstr = syn::LitStr::new(&input.name.to_string(), input.name.span());
+    // GamePacket -> ConnectionProtocol::Game
+    let state_variant = Ident::new(
+        input
+            .name
+            .to_string()
+            .strip_suffix("Packet")
+            .expect("State name must end with `Packet`"),
+        input.name.span(),
+    );
 
     let has_serverbound_packets = !input.serverbound.packets.is_empty();
     let has_clientbound_packets = !input.clientbound.packets.is_empty();
@@ -219,7 +236,7 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
         });
         serverbound_read_match_contents.extend(quote! {
             #id => {
-                let data = #module::#name::read(buf).map_err(|e| crate::read::ReadPacketError::Parse {
+                let data = #module::#name::read_shared(buf, shared).map_err(|e| crate::read::ReadPacketError::Parse {
                     source: e,
                     packet_id: #id,
                     backtrace: Box::new(std::backtrace::Backtrace::capture()),
@@ -252,7 +269,7 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
         });
         clientbound_read_match_contents.extend(quote! {
             #id => {
-                let data = #module::#name::read(buf).map_err(|e| crate::read::ReadPacketError::Parse {
+                let data = #module::#name::read_shared(buf, shared).map_err(|e| crate::read::ReadPacketError::Parse {
                     source: e,
                     packet_id: #id,
                     backtrace: Box::new(std::backtrace::Backtrace::capture()),
@@ -315,6 +332,9 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
     contents.extend(quote! {
         #[allow(unreachable_code)]
         impl crate::packets::ProtocolPacket for #serverbound_state_name {
+            const STATE: crate::packets::ConnectionProtocol = crate::packets::ConnectionProtocol::#state_variant;
+            const DIRECTION: crate::packets::PacketDirection = crate::packets::PacketDirection::Serverbound;
+
             fn id(&self) -> u32 {
                 match self {
                     #serverbound_id_match_contents
@@ -328,9 +348,10 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
             }
 
             /// Read a packet by its id, ConnectionProtocol, and flow
-            fn read(
+            fn read_shared(
                 id: u32,
                 buf: &mut std::io::Cursor<&[u8]>,
+                shared: &azalea_buf::Bytes,
             ) -> Result<#serverbound_state_name, Box<crate::read::ReadPacketError>>
             where
                 Self: Sized,
@@ -346,6 +367,9 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
     contents.extend(quote! {
         #[allow(unreachable_code)]
         impl crate::packets::ProtocolPacket for #clientbound_state_name {
+            const STATE: crate::packets::ConnectionProtocol = crate::packets::ConnectionProtocol::#state_variant;
+            const DIRECTION: crate::packets::PacketDirection = crate::packets::PacketDirection::Clientbound;
+
             fn id(&self) -> u32 {
                 match self {
                     #clientbound_id_match_contents
@@ -359,9 +383,10 @@ pub fn declare_state_packets(input: TokenStream) -> TokenStream {
             }
 
             /// Read a packet by its id, ConnectionProtocol, and flow
-            fn read(
+            fn read_shared(
                 id: u32,
                 buf: &mut std::io::Cursor<&[u8]>,
+                shared: &azalea_buf::Bytes,
             ) -> Result<#clientbound_state_name, Box<crate::read::ReadPacketError>>
             where
                 Self: Sized,
@@ -0,0 +1,213 @@
+use azalea_buf::{McBufVarReadable, McBufWritable};
+use azalea_core::{BitSet, BlockPos, ChunkBlockPos};
+use azalea_nbt::{Nbt, NbtCompound};
+use azalea_protocol::{
+    packets::game::{
+        clientbound_keep_alive_packet::ClientboundKeepAlivePacket,
+        clientbound_level_chunk_with_light_packet::{
+            ClientboundLevelChunkPacketData, ClientboundLevelChunkWithLightPacket,
+        },
+        clientbound_light_update_packet::ClientboundLightUpdatePacketData,
+        ClientboundGamePacket,
+    },
+    read::{packet_decoder, read_packet, PacketCodec},
+    write::write_packet,
+};
+use azalea_world::Chunk;
+use bytes::Bytes;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use flate2::read::ZlibDecoder;
+use std::io::{Cursor, Read};
+use tokio::runtime::Runtime;
+use tokio_util::codec::FramedRead;
+
+const KEY: &[u8; 16] = b"0123456789abcdef";
+const COMPRESSION_THRESHOLD: u32 = 256;
+/// How many chunks out from the player the server sends, so every session
+/// has (VIEW_DISTANCE * 2 + 1)^2 chunks.
+const VIEW_DISTANCE: i32 = 5;
+const BOTS: usize = 128;
+
+/// A chunk with some stone and dirt, so it's not trivial to compress.
+fn make_chunk(seed: i32) -> Chunk {
+    let mut chunk = Chunk::default();
+    for x in 0..16 {
+        for z in 0..16 {
+            let height = 60 + (x * 7 + z * 13 + seed).rem_euclid(8);
+            for y in -64..height {
+                let block = if y < height - 3 {
+                    azalea_registry::Block::Stone
+                } else {
+                    azalea_registry::Block::Dirt
+                };
+                chunk.set(
+                    &ChunkBlockPos::from(&BlockPos::new(x, y, z)),
+                    block.into(),
+                    -64,
+                );
+            }
+        }
+    }
+    chunk
+}
+
+/// Encode the packets a server would send to a bot that just joined, the way
+/// they'd be sent over the network (compressed and encrypted).
+async fn make_session() -> Bytes {
+    let (enc_cipher, _) = azalea_crypto::create_cipher(KEY);
+    let mut enc_cipher = Some(enc_cipher);
+    let mut stream = Vec::new();
+    for x in -VIEW_DISTANCE..=VIEW_DISTANCE {
+        for z in -VIEW_DISTANCE..=VIEW_DISTANCE {
+            let mut data = Vec::new();
+            make_chunk(x * 31 + z).write_into(&mut data).unwrap();
+            let packet = ClientboundLevelChunkWithLightPacket {
+                x,
+                z,
+                chunk_data: ClientboundLevelChunkPacketData {
+                    heightmaps: Nbt::Compound(NbtCompound::default()),
+                    data: data.into(),
+                    block_entities: Vec::new(),
+                },
+                light_data: ClientboundLightUpdatePacketData {
+                    trust_edges: true,
+                    sky_y_mask: BitSet::new(26),
+                    block_y_mask: BitSet::new(26),
+                    empty_sky_y_mask: BitSet::new(26),
+                    empty_block_y_mask: BitSet::new(26),
+                    sky_updates: vec![vec![0xff; 2048].into(); 8],
+                    block_updates: Vec::new(),
+                },
+            }
+            .get();
+            write_packet(
+                &packet,
+                &mut stream,
+                Some(COMPRESSION_THRESHOLD),
+                &mut enc_cipher,
+            )
+            .await
+            .unwrap();
+            // small packets that aren't compressed
+            write_packet(
+                &ClientboundKeepAlivePacket { id: 0 }.get(),
+                &mut stream,
+                Some(COMPRESSION_THRESHOLD),
+                &mut enc_cipher,
+            )
+            .await
+            .unwrap();
+        }
+    }
+    stream.into()
+}
+
+/// Read and parse every packet in the session, like a client would.
+async fn read_session(session: Bytes) -> usize {
+    let (_, dec_cipher) = azalea_crypto::create_cipher(KEY);
+    let mut stream = FramedRead::new(
+        Cursor::new(session),
+        PacketCodec {
+            compression_threshold: Some(COMPRESSION_THRESHOLD),
+            cipher: Some(dec_cipher),
+            ..Default::default()
+        },
+    );
+    let mut count = 0;
+    while read_packet::<ClientboundGamePacket, _>(&mut stream)
+        .await
+        .is_ok()
+    {
+        count += 1;
+    }
+    count
+}
+
+/// Read the session the way `read_packet` did before it kept a codec around,
+/// so there's something to compare against. Every frame is copied out of the
+/// stream, compressed packets are inflated into a new buffer, and byte arrays
+/// are copied out of that when the packet is parsed.
+fn read_session_copying(session: &[u8]) -> usize {
+    let (_, mut dec_cipher) = azalea_crypto::create_cipher(KEY);
+    let mut data = session.to_vec();
+    azalea_crypto::decrypt_packet(&mut dec_cipher, &mut data);
+
+    let mut stream = Cursor::new(&data[..]);
+    let mut count = 0;
+    while (stream.position() as usize) < data.len() {
+        let length = u32::var_read_from(&mut stream).unwrap() as usize;
+        let start = stream.position() as usize;
+        let frame = data[start..start + length].to_vec();
+        stream.set_position((start + length) as u64);
+
+        let mut frame = Cursor::new(&frame[..]);
+        let data_length = u32::var_read_from(&mut frame).unwrap() as usize;
+        let mut packet = Vec::with_capacity(data_length);
+        if data_length == 0 {
+            frame.read_to_end(&mut packet).unwrap();
+        } else {
+            ZlibDecoder::new(frame).read_to_end(&mut packet).unwrap();
+        }
+        packet_decoder::<ClientboundGamePacket>(&mut Cursor::new(&packet[..])).unwrap();
+        count += 1;
+    }
+    count
+}
+
+fn bench(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let session = runtime.block_on(make_session());
+    let packet_count = ((VIEW_DISTANCE * 2 + 1) * (VIEW_DISTANCE * 2 + 1) * 2) as usize;
+    assert_eq!(
+        runtime.block_on(read_session(session.clone())),
+        packet_count
+    );
+
+    assert_eq!(read_session_copying(&session), packet_count);
+
+    let mut group = c.benchmark_group("Read chunk session");
+
+    group.throughput(Throughput::Bytes(session.len() as u64));
+    group.bench_function("1 bot (copying)", |b| {
+        b.iter(|| read_session_copying(&session))
+    });
+    group.bench_function("1 bot", |b| {
+        b.iter(|| runtime.block_on(read_session(session.clone())))
+    });
+
+    // every bot reads its own connection at the same time, like a swarm
+    group.throughput(Throughput::Bytes((session.len() * BOTS) as u64));
+    group.sample_size(10);
+    group.bench_function(format!("{BOTS} bots (copying)"), |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                let tasks = (0..BOTS)
+                    .map(|_| {
+                        let session = session.clone();
+                        tokio::spawn(async move { read_session_copying(&session) })
+                    })
+                    .collect::<Vec<_>>();
+                for task in tasks {
+                    assert_eq!(task.await.unwrap(), packet_count);
+                }
+            })
+        })
+    });
+    group.bench_function(format!("{BOTS} bots"), |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                let tasks = (0..BOTS)
+                    .map(|_| tokio::spawn(read_session(session.clone())))
+                    .collect::<Vec<_>>();
+                for task in tasks {
+                    assert_eq!(task.await.unwrap(), packet_count);
+                }
+            })
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -1,5 +1,7 @@
 //! A "simple" server that gets login information and proxies connections.
 //! After login all connections are encrypted and Azalea cannot read them.
+//! See `azalea_protocol::mitm` for a proxy that can read packets after login
+//! on offline-mode servers.
 
 use azalea_protocol::{
     connect::Connection,
@@ -0,0 +1,332 @@
+//! Record the packets sent over a connection to a file, and read them back.
+//!
+//! Every packet is stored decrypted and decompressed, along with when it was
+//! sent, which way it was going, and the state of the connection at the time.
+//! This is useful for reproducing bugs and testing packet handling without a
+//! server.
+//!
+//! ```rust,no_run
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! use azalea_protocol::{capture::PacketRecorder, connect::Connection};
+//! # let resolved_address = "127.0.0.1:25565".parse()?;
+//!
+//! let mut conn = Connection::new(&resolved_address).await?;
+//! conn.record(PacketRecorder::create("session.azcap")?);
+//! // every packet read or written from now on is recorded, even after
+//! // switching states
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    packets::{ConnectionProtocol, PacketDirection, ProtocolPacket, PROTOCOL_VERSION},
+    read::{packet_decoder, ReadPacketError},
+};
+use azalea_buf::{BufReadError, McBufReadable, McBufVarReadable, McBufVarWritable, McBufWritable};
+use log::error;
+use parking_lot::Mutex;
+use std::{
+    fmt::Debug,
+    fs::File,
+    io::{self, BufWriter, Cursor, Read, Write},
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+
+/// The bytes at the start of every capture file.
+pub const MAGIC: &[u8; 5] = b"AZCAP";
+/// The version of the capture format, this is incremented whenever the
+/// format changes.
+pub const FORMAT_VERSION: u8 = 1;
+
+#[derive(Error, Debug)]
+pub enum CaptureError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[error("This isn't a packet capture")]
+    InvalidMagic,
+    #[error("Unsupported capture format version {0}")]
+    UnsupportedFormatVersion(u8),
+    #[error("Couldn't read packet from capture: {0}")]
+    Read(#[from] BufReadError),
+    #[error("Invalid packet state {0}")]
+    InvalidState(u8),
+}
+
+/// A packet that was read from a capture.
+#[derive(Clone, Debug)]
+pub struct CapturedPacket {
+    /// How long after the start of the capture the packet was sent.
+    pub time: Duration,
+    pub direction: PacketDirection,
+    pub state: ConnectionProtocol,
+    /// The packet id followed by the packet's data.
+    pub data: Vec<u8>,
+}
+
+impl CapturedPacket {
+    /// Whether this packet was sent in the state and direction of `P`, so
+    /// [`Self::decode`] can work.
+    pub fn is<P: ProtocolPacket>(&self) -> bool {
+        self.state == P::STATE && self.direction == P::DIRECTION
+    }
+
+    /// Parse the packet. You should check that it's the correct type with
+    /// [`Self::is`] first.
+    pub fn decode<P: ProtocolPacket + Debug>(&self) -> Result<P, Box<ReadPacketError>> {
+        packet_decoder(&mut Cursor::new(&self.data[..]))
+    }
+}
+
+/// The state and direction are packed into one byte, the lowest bit is the
+/// direction and the rest is the state.
+fn pack_flags(direction: PacketDirection, state: ConnectionProtocol) -> u8 {
+    let direction = match direction {
+        PacketDirection::Clientbound => 0,
+        PacketDirection::Serverbound => 1,
+    };
+    // states start at -1 (handshake)
+    (((state as i32 + 1) as u8) << 1) | direction
+}
+
+fn unpack_flags(flags: u8) -> Result<(PacketDirection, ConnectionProtocol), CaptureError> {
+    let direction = if flags & 1 == 0 {
+        PacketDirection::Clientbound
+    } else {
+        PacketDirection::Serverbound
+    };
+    let state = ConnectionProtocol::from_i32((flags >> 1) as i32 - 1)
+        .ok_or(CaptureError::InvalidState(flags >> 1))?;
+    Ok((direction, state))
+}
+
+/// Writes packets in the capture format.
+pub struct CaptureWriter<W: Write> {
+    writer: W,
+    start: Instant,
+    /// The time of the last packet, relative to `start`. Packets only store
+    /// the time since the previous packet to keep the file small.
+    last_time: Duration,
+}
+
+impl<W: Write> CaptureWriter<W> {
+    /// Write the header and start the clock.
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        FORMAT_VERSION.write_into(&mut writer)?;
+        PROTOCOL_VERSION.var_write_into(&mut writer)?;
+        Ok(Self {
+            writer,
+            start: Instant::now(),
+            last_time: Duration::ZERO,
+        })
+    }
+
+    /// Record a packet that's already encoded (starting with the packet id).
+    pub fn write_raw(
+        &mut self,
+        direction: PacketDirection,
+        state: ConnectionProtocol,
+        data: &[u8],
+    ) -> io::Result<()> {
+        let time = self.start.elapsed();
+        let delta = (time - self.last_time).as_millis().min(u32::MAX as u128) as u32;
+        // don't let rounding errors add up
+        self.last_time += Duration::from_millis(delta as u64);
+
+        delta.var_write_into(&mut self.writer)?;
+        pack_flags(direction, state).write_into(&mut self.writer)?;
+        (data.len() as u32).var_write_into(&mut self.writer)?;
+        self.writer.write_all(data)
+    }
+
+    /// Encode a packet and record it.
+    pub fn write_packet<P: ProtocolPacket>(&mut self, packet: &P) -> io::Result<()> {
+        let mut data = Vec::new();
+        packet.id().var_write_into(&mut data)?;
+        packet.write(&mut data)?;
+        self.write_raw(P::DIRECTION, P::STATE, &data)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Reads packets from a capture. This is an iterator of [`CapturedPacket`]s.
+pub struct CaptureReader {
+    data: Cursor<Vec<u8>>,
+    protocol_version: u32,
+    time: Duration,
+}
+
+impl CaptureReader {
+    /// Read a capture from a file.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CaptureError> {
+        let mut data = Vec::new();
+        File::open(path)?.read_to_end(&mut data)?;
+        Self::new(data)
+    }
+
+    /// Read a capture that's already in memory.
+    pub fn new(data: Vec<u8>) -> Result<Self, CaptureError> {
+        let mut data = Cursor::new(data);
+        let mut magic = [0; MAGIC.len()];
+        if data.read_exact(&mut magic).is_err() || &magic != MAGIC {
+            return Err(CaptureError::InvalidMagic);
+        }
+        let mut version = [0];
+        data.read_exact(&mut version)?;
+        if version[0] != FORMAT_VERSION {
+            return Err(CaptureError::UnsupportedFormatVersion(version[0]));
+        }
+        let protocol_version = Self::read_with(&mut data, u32::var_read_from)?;
+        Ok(Self {
+            data,
+            protocol_version,
+            time: Duration::ZERO,
+        })
+    }
+
+    /// The protocol version of the client that made the capture. Packets
+    /// might not parse if it isn't [`PROTOCOL_VERSION`].
+    pub fn protocol_version(&self) -> u32 {
+        self.protocol_version
+    }
+
+    /// Run an azalea-buf reader on the unread part of the data.
+    fn read_with<T>(
+        data: &mut Cursor<Vec<u8>>,
+        f: impl FnOnce(&mut Cursor<&[u8]>) -> Result<T, BufReadError>,
+    ) -> Result<T, BufReadError> {
+        let position = data.position();
+        let mut cursor = Cursor::new(&data.get_ref()[..]);
+        cursor.set_position(position);
+        let value = f(&mut cursor)?;
+        let position = cursor.position();
+        data.set_position(position);
+        Ok(value)
+    }
+
+    fn read_packet(&mut self) -> Result<CapturedPacket, CaptureError> {
+        let delta = Self::read_with(&mut self.data, u32::var_read_from)?;
+        let flags = Self::read_with(&mut self.data, u8::read_from)?;
+        let (direction, state) = unpack_flags(flags)?;
+        let len = Self::read_with(&mut self.data, u32::var_read_from)? as usize;
+        let mut data = vec![0; len];
+        self.data.read_exact(&mut data)?;
+
+        self.time += Duration::from_millis(delta as u64);
+        Ok(CapturedPacket {
+            time: self.time,
+            direction,
+            state,
+            data,
+        })
+    }
+}
+
+impl Iterator for CaptureReader {
+    type Item = Result<CapturedPacket, CaptureError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.position() >= self.data.get_ref().len() as u64 {
+            return None;
+        }
+        Some(self.read_packet())
+    }
+}
+
+/// A cheaply cloneable handle to a capture that's being written, which can be
+/// given to a [`Connection`](crate::connect::Connection) with
+/// [`Connection::record`](crate::connect::Connection::record).
+#[derive(Clone)]
+pub struct PacketRecorder {
+    writer: Arc<Mutex<CaptureWriter<Box<dyn Write + Send>>>>,
+}
+
+impl PacketRecorder {
+    pub fn new(writer: impl Write + Send + 'static) -> io::Result<Self> {
+        Ok(Self {
+            writer: Arc::new(Mutex::new(CaptureWriter::new(Box::new(writer))?)),
+        })
+    }
+
+    /// Create a file to record packets to. The file is flushed when the last
+    /// clone of the recorder is dropped.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+
+    /// Record an encoded packet. Errors are logged instead of returned since
+    /// a broken capture shouldn't break the connection.
+    pub fn record(&self, direction: PacketDirection, state: ConnectionProtocol, data: &[u8]) {
+        let mut writer = self.writer.lock();
+        if let Err(e) = writer.write_raw(direction, state, data) {
+            error!("Couldn't record packet: {e}");
+        }
+    }
+
+    pub fn flush(&self) -> io::Result<()> {
+        self.writer.lock().flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packets::{
+        game::{serverbound_keep_alive_packet::ServerboundKeepAlivePacket, ServerboundGamePacket},
+        handshake::{client_intention_packet::ClientIntentionPacket, ServerboundHandshakePacket},
+    };
+
+    #[test]
+    fn test_write_read_capture() {
+        let mut buf = Vec::new();
+        let mut writer = CaptureWriter::new(&mut buf).unwrap();
+        writer
+            .write_packet(
+                &ClientIntentionPacket {
+                    protocol_version: PROTOCOL_VERSION,
+                    hostname: "localhost".to_string(),
+                    port: 25565,
+                    intention: ConnectionProtocol::Login,
+                }
+                .get(),
+            )
+            .unwrap();
+        writer
+            .write_packet(&ServerboundKeepAlivePacket { id: 123 }.get())
+            .unwrap();
+        drop(writer);
+
+        let mut reader = CaptureReader::new(buf).unwrap();
+        assert_eq!(reader.protocol_version(), PROTOCOL_VERSION);
+
+        let packet = reader.next().unwrap().unwrap();
+        assert!(packet.is::<ServerboundHandshakePacket>());
+        assert!(!packet.is::<ServerboundGamePacket>());
+        let ServerboundHandshakePacket::ClientIntention(intention) = packet.decode().unwrap();
+        assert_eq!(intention.hostname, "localhost");
+
+        let packet = reader.next().unwrap().unwrap();
+        assert_eq!(packet.direction, PacketDirection::Serverbound);
+        assert_eq!(packet.state, ConnectionProtocol::Game);
+        match packet.decode::<ServerboundGamePacket>().unwrap() {
+            ServerboundGamePacket::KeepAlive(p) => assert_eq!(p.id, 123),
+            p => panic!("Wrong packet {p:?}"),
+        }
+
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_invalid_capture() {
+        assert!(matches!(
+            CaptureReader::new(b"not a capture".to_vec()),
+            Err(CaptureError::InvalidMagic)
+        ));
+    }
+}
@@ -1,18 +1,21 @@
 //! Connect to remote servers/clients.
 
+use crate::capture::PacketRecorder;
 use crate::packets::game::{ClientboundGamePacket, ServerboundGamePacket};
 use crate::packets::handshake::{ClientboundHandshakePacket, ServerboundHandshakePacket};
 use crate::packets::login::clientbound_hello_packet::ClientboundHelloPacket;
 use crate::packets::login::{ClientboundLoginPacket, ServerboundLoginPacket};
 use crate::packets::status::{ClientboundStatusPacket, ServerboundStatusPacket};
 use crate::packets::ProtocolPacket;
-use crate::read::{read_packet, ReadPacketError};
-use crate::write::write_packet;
+use crate::proxy::{Proxy, ProxyError};
+use crate::read::{decode_packet, read_raw_packet, PacketCodec, ReadPacketError};
+use crate::resolver::{self, ResolverError};
+use crate::write::{packet_encoder, write_raw_packet};
+use crate::ServerAddress;
 use azalea_auth::game_profile::GameProfile;
 use azalea_auth::sessionserver::{ClientSessionServerError, ServerSessionServerError};
 use azalea_crypto::{Aes128CfbDec, Aes128CfbEnc};
-use bytes::BytesMut;
-use log::{error, info};
+use log::{error, info, trace};
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::net::SocketAddr;
@@ -20,14 +23,16 @@ use thiserror::Error;
 use tokio::io::AsyncWriteExt;
 use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf, ReuniteError};
 use tokio::net::TcpStream;
+use tokio_util::codec::FramedRead;
 use uuid::Uuid;
 
 /// The read half of a connection.
 pub struct ReadConnection<R: ProtocolPacket> {
-    pub read_stream: OwnedReadHalf,
-    pub buffer: BytesMut,
-    pub compression_threshold: Option<u32>,
-    pub dec_cipher: Option<Aes128CfbDec>,
+    /// The stream, and the codec that splits it into packets. The compression
+    /// threshold and cipher are stored in the codec.
+    pub read_stream: FramedRead<OwnedReadHalf, PacketCodec>,
+    /// Where packets that are read get recorded, if anywhere.
+    pub recorder: Option<PacketRecorder>,
     _reading: PhantomData<R>,
 }
 
@@ -36,6 +41,8 @@ pub struct WriteConnection<W: ProtocolPacket> {
     pub write_stream: OwnedWriteHalf,
     pub compression_threshold: Option<u32>,
     pub enc_cipher: Option<Aes128CfbEnc>,
+    /// Where packets that are written get recorded, if anywhere.
+    pub recorder: Option<PacketRecorder>,
     _writing: PhantomData<W>,
 }
 
@@ -132,13 +139,22 @@ where
 {
     /// Read a packet from the stream.
     pub async fn read(&mut self) -> Result<R, Box<ReadPacketError>> {
-        read_packet::<R, _>(
-            &mut self.read_stream,
-            &mut self.buffer,
-            self.compression_threshold,
-            &mut self.dec_cipher,
-        )
-        .await
+        let buf = read_raw_packet(&mut self.read_stream).await?;
+        if let Some(recorder) = &self.recorder {
+            recorder.record(R::DIRECTION, R::STATE, &buf);
+        }
+        decode_packet(&buf)
+    }
+
+    /// Set the compression threshold for packets we read. `None` means
+    /// packets aren't compressed.
+    pub fn set_compression_threshold(&mut self, threshold: Option<u32>) {
+        self.read_stream.decoder_mut().compression_threshold = threshold;
+    }
+
+    /// Decrypt everything we read from now on with the given cipher.
+    pub fn set_cipher(&mut self, cipher: Aes128CfbDec) {
+        self.read_stream.decoder_mut().cipher = Some(cipher);
     }
 }
 impl<W> WriteConnection<W>
@@ -147,8 +163,13 @@ where
 {
     /// Write a packet to the server.
     pub async fn write(&mut self, packet: W) -> std::io::Result<()> {
-        if let Err(e) = write_packet(
-            &packet,
+        trace!("Sending packet: {:?}", packet);
+        let buf = packet_encoder(&packet).unwrap();
+        if let Some(recorder) = &self.recorder {
+            recorder.record(W::DIRECTION, W::STATE, &buf);
+        }
+        if let Err(e) = write_raw_packet(
+            buf,
             &mut self.write_stream,
             self.compression_threshold,
             &mut self.enc_cipher,
@@ -188,6 +209,13 @@ where
         self.writer.write(packet).await
     }
 
+    /// Record every packet that's read or written from now on, including
+    /// after changing states.
+    pub fn record(&mut self, recorder: PacketRecorder) {
+        self.reader.recorder = Some(recorder.clone());
+        self.writer.recorder = Some(recorder);
+    }
+
     /// Split the reader and writer into two objects. This doesn't allocate.
     #[must_use]
     pub fn into_split(self) -> (ReadConnection<R>, WriteConnection<W>) {
@@ -199,6 +227,10 @@ where
 pub enum ConnectionError {
     #[error("{0}")]
     Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Proxy(#[from] ProxyError),
+    #[error("{0}")]
+    Resolver(#[from] ResolverError),
 }
 
 impl Connection<ClientboundHandshakePacket, ServerboundHandshakePacket> {
@@ -209,23 +241,52 @@ impl Connection<ClientboundHandshakePacket, ServerboundHandshakePacket> {
         // enable tcp_nodelay
         stream.set_nodelay(true)?;
 
-        let (read_stream, write_stream) = stream.into_split();
+        Ok(Connection::wrap(stream))
+    }
 
-        Ok(Connection {
-            reader: ReadConnection {
-                read_stream,
-                buffer: BytesMut::new(),
-                compression_threshold: None,
-                dec_cipher: None,
-                _reading: PhantomData,
-            },
-            writer: WriteConnection {
-                write_stream,
-                compression_threshold: None,
-                enc_cipher: None,
-                _writing: PhantomData,
-            },
-        })
+    /// Create a new connection to a server through a proxy.
+    ///
+    /// If the proxy does its own DNS lookups ([`Proxy::remote_dns`]), it's
+    /// sent the host and port from `address` and `resolved_address` is
+    /// ignored, so it can be `None`. Otherwise the proxy is asked to connect
+    /// to `resolved_address`, which is looked up here if it's `None`.
+    pub async fn new_with_proxy(
+        address: &ServerAddress,
+        resolved_address: Option<&SocketAddr>,
+        proxy: &Proxy,
+    ) -> Result<Self, ConnectionError> {
+        let stream = if proxy.remote_dns {
+            proxy.connect(&address.host, address.port).await?
+        } else {
+            let resolved_address = match resolved_address {
+                Some(resolved_address) => *resolved_address,
+                None => resolver::resolve_address(address).await?,
+            };
+            proxy
+                .connect(&resolved_address.ip().to_string(), resolved_address.port())
+                .await?
+        };
+
+        stream.set_nodelay(true)?;
+
+        Ok(Connection::wrap(stream))
+    }
+
+    /// Create a new connection to a server, through the proxy if there is
+    /// one. `resolved_address` is what `address` resolves to, or `None` if it
+    /// hasn't been looked up yet (see
+    /// [`resolver::resolve_address_unless_proxied`]). It's only looked up here
+    /// if it's needed.
+    pub async fn new_maybe_with_proxy(
+        address: &ServerAddress,
+        resolved_address: Option<&SocketAddr>,
+        proxy: Option<&Proxy>,
+    ) -> Result<Self, ConnectionError> {
+        match (proxy, resolved_address) {
+            (Some(proxy), _) => Self::new_with_proxy(address, resolved_address, proxy).await,
+            (None, Some(resolved_address)) => Self::new(resolved_address).await,
+            (None, None) => Self::new(&resolver::resolve_address(address).await?).await,
+        }
     }
 
     /// Change our state from handshake to login. This is the state that is used
@@ -250,10 +311,11 @@ impl Connection<ClientboundLoginPacket, ServerboundLoginPacket> {
     pub fn set_compression_threshold(&mut self, threshold: i32) {
         // if you pass a threshold of less than 0, compression is disabled
         if threshold >= 0 {
-            self.reader.compression_threshold = Some(threshold as u32);
+            self.reader
+                .set_compression_threshold(Some(threshold as u32));
             self.writer.compression_threshold = Some(threshold as u32);
         } else {
-            self.reader.compression_threshold = None;
+            self.reader.set_compression_threshold(None);
             self.writer.compression_threshold = None;
         }
     }
@@ -262,7 +324,7 @@ impl Connection<ClientboundLoginPacket, ServerboundLoginPacket> {
     /// the same for both reading and writing.
     pub fn set_encryption_key(&mut self, key: [u8; 16]) {
         let (enc_cipher, dec_cipher) = azalea_crypto::create_cipher(&key);
-        self.reader.dec_cipher = Some(dec_cipher);
+        self.reader.set_cipher(dec_cipher);
         self.writer.enc_cipher = Some(enc_cipher);
     }
 
@@ -367,10 +429,11 @@ impl Connection<ServerboundLoginPacket, ClientboundLoginPacket> {
     pub fn set_compression_threshold(&mut self, threshold: i32) {
         // if you pass a threshold of less than 0, compression is disabled
         if threshold >= 0 {
-            self.reader.compression_threshold = Some(threshold as u32);
+            self.reader
+                .set_compression_threshold(Some(threshold as u32));
             self.writer.compression_threshold = Some(threshold as u32);
         } else {
-            self.reader.compression_threshold = None;
+            self.reader.set_compression_threshold(None);
             self.writer.compression_threshold = None;
         }
     }
@@ -379,7 +442,7 @@ impl Connection<ServerboundLoginPacket, ClientboundLoginPacket> {
     /// the same for both reading and writing.
     pub fn set_encryption_key(&mut self, key: [u8; 16]) {
         let (enc_cipher, dec_cipher) = azalea_crypto::create_cipher(&key);
-        self.reader.dec_cipher = Some(dec_cipher);
+        self.reader.set_cipher(dec_cipher);
         self.writer.enc_cipher = Some(enc_cipher);
     }
 
@@ -422,15 +485,14 @@ where
         Connection {
             reader: ReadConnection {
                 read_stream: connection.reader.read_stream,
-                buffer: connection.reader.buffer,
-                compression_threshold: connection.reader.compression_threshold,
-                dec_cipher: connection.reader.dec_cipher,
+                recorder: connection.reader.recorder,
                 _reading: PhantomData,
             },
             writer: WriteConnection {
                 compression_threshold: connection.writer.compression_threshold,
                 write_stream: connection.writer.write_stream,
                 enc_cipher: connection.writer.enc_cipher,
+                recorder: connection.writer.recorder,
                 _writing: PhantomData,
             },
         }
@@ -442,16 +504,15 @@ where
 
         Connection {
             reader: ReadConnection {
-                read_stream,
-                buffer: BytesMut::new(),
-                compression_threshold: None,
-                dec_cipher: None,
+                read_stream: FramedRead::new(read_stream, PacketCodec::default()),
+                recorder: None,
                 _reading: PhantomData,
             },
             writer: WriteConnection {
                 write_stream,
                 compression_threshold: None,
                 enc_cipher: None,
+                recorder: None,
                 _writing: PhantomData,
             },
         }
@@ -459,6 +520,9 @@ where
 
     /// Convert from a `Connection` into a `TcpStream`. Useful for servers.
     pub fn unwrap(self) -> Result<TcpStream, ReuniteError> {
-        self.reader.read_stream.reunite(self.writer.write_stream)
+        self.reader
+            .read_stream
+            .into_inner()
+            .reunite(self.writer.write_stream)
     }
 }
@@ -15,10 +15,18 @@
 
 use std::{fmt::Display, net::SocketAddr, str::FromStr};
 
+#[cfg(feature = "packets")]
+pub mod capture;
 #[cfg(feature = "connecting")]
 pub mod connect;
+#[cfg(feature = "connecting")]
+pub mod mitm;
 #[cfg(feature = "packets")]
 pub mod packets;
+#[cfg(feature = "connecting")]
+pub mod proxy;
+pub mod query;
+pub mod rcon;
 pub mod read;
 pub mod resolver;
 pub mod write;
@@ -83,13 +91,24 @@ mod tests {
 
     use crate::{
         packets::{
-            game::serverbound_chat_packet::{LastSeenMessagesUpdate, ServerboundChatPacket},
+            game::{
+                clientbound_level_chunk_with_light_packet::{
+                    ClientboundLevelChunkPacketData, ClientboundLevelChunkWithLightPacket,
+                },
+                clientbound_light_update_packet::ClientboundLightUpdatePacketData,
+                clientbound_update_tags_packet::{ClientboundUpdateTagsPacket, TagMap, Tags},
+                serverbound_chat_packet::{LastSeenMessagesUpdate, ServerboundChatPacket},
+                ClientboundGamePacket, ServerboundGamePacket,
+            },
             login::{serverbound_hello_packet::ServerboundHelloPacket, ServerboundLoginPacket},
         },
-        read::{compression_decoder, read_packet},
-        write::{compression_encoder, packet_encoder, write_packet},
+        read::{decode_packet, read_packet, read_raw_packet, PacketCodec},
+        write::write_packet,
     };
-    use bytes::BytesMut;
+    use azalea_core::BitSet;
+    use azalea_nbt::{Nbt, NbtCompound};
+    use tokio::io::AsyncWriteExt;
+    use tokio_util::codec::FramedRead;
     use uuid::Uuid;
 
     #[tokio::test]
@@ -104,16 +123,11 @@ mod tests {
             .await
             .unwrap();
 
-        let mut stream = Cursor::new(stream);
+        let mut stream = FramedRead::new(Cursor::new(stream), PacketCodec::default());
 
-        let _ = read_packet::<ServerboundLoginPacket, _>(
-            &mut stream,
-            &mut BytesMut::new(),
-            None,
-            &mut None,
-        )
-        .await
-        .unwrap();
+        let _ = read_packet::<ServerboundLoginPacket, _>(&mut stream)
+            .await
+            .unwrap();
     }
 
     #[tokio::test]
@@ -130,14 +144,12 @@ mod tests {
         write_packet(&packet, &mut stream, None, &mut None)
             .await
             .unwrap();
-        let mut stream = Cursor::new(stream);
+        let mut stream = FramedRead::new(Cursor::new(stream), PacketCodec::default());
 
-        let mut buffer = BytesMut::new();
-
-        let _ = read_packet::<ServerboundLoginPacket, _>(&mut stream, &mut buffer, None, &mut None)
+        let _ = read_packet::<ServerboundLoginPacket, _>(&mut stream)
             .await
             .unwrap();
-        let _ = read_packet::<ServerboundLoginPacket, _>(&mut stream, &mut buffer, None, &mut None)
+        let _ = read_packet::<ServerboundLoginPacket, _>(&mut stream)
             .await
             .unwrap();
     }
@@ -146,24 +158,144 @@ mod tests {
     async fn test_read_long_compressed_chat() {
         let compression_threshold = 256;
 
-        let buf = packet_encoder(
-            &ServerboundChatPacket {
-                message: "a".repeat(256),
+        let packet = ServerboundChatPacket {
+            message: "a".repeat(256),
+            timestamp: 0,
+            salt: 0,
+            signature: None,
+            last_seen_messages: LastSeenMessagesUpdate::default(),
+        }
+        .get();
+        let mut stream = Vec::new();
+        write_packet(&packet, &mut stream, Some(compression_threshold), &mut None)
+            .await
+            .unwrap();
+
+        let mut stream = FramedRead::new(
+            Cursor::new(stream),
+            PacketCodec {
+                compression_threshold: Some(compression_threshold),
+                ..Default::default()
+            },
+        );
+        match read_packet::<ServerboundGamePacket, _>(&mut stream)
+            .await
+            .unwrap()
+        {
+            ServerboundGamePacket::Chat(p) => assert_eq!(p.message, "a".repeat(256)),
+            p => panic!("Wrong packet {p:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_encrypted_in_small_pieces() {
+        let (enc_cipher, dec_cipher) = azalea_crypto::create_cipher(b"1234567890123456");
+        let mut enc_cipher = Some(enc_cipher);
+        let mut data = Vec::new();
+        for i in 0..10 {
+            let packet = ServerboundChatPacket {
+                message: "a".repeat(i * 50),
                 timestamp: 0,
                 salt: 0,
                 signature: None,
                 last_seen_messages: LastSeenMessagesUpdate::default(),
             }
-            .get(),
-        )
-        .unwrap();
+            .get();
+            write_packet(&packet, &mut data, Some(256), &mut enc_cipher)
+                .await
+                .unwrap();
+        }
+
+        // the stream only lets a few bytes through at a time, so packets get split
+        // up across reads
+        let (mut writer, reader) = tokio::io::duplex(7);
+        tokio::spawn(async move { writer.write_all(&data).await.unwrap() });
+        let mut stream = FramedRead::new(
+            reader,
+            PacketCodec {
+                compression_threshold: Some(256),
+                cipher: Some(dec_cipher),
+                ..Default::default()
+            },
+        );
+        for i in 0..10 {
+            match read_packet::<ServerboundGamePacket, _>(&mut stream)
+                .await
+                .unwrap()
+            {
+                ServerboundGamePacket::Chat(p) => assert_eq!(p.message, "a".repeat(i * 50)),
+                p => panic!("Wrong packet {p:?}"),
+            }
+        }
+    }
 
-        let buf = compression_encoder(&buf, compression_threshold)
+    #[tokio::test]
+    async fn test_chunk_data_borrows_from_frame() {
+        let packet = ClientboundLevelChunkWithLightPacket {
+            x: 0,
+            z: 0,
+            chunk_data: ClientboundLevelChunkPacketData {
+                heightmaps: Nbt::Compound(NbtCompound::default()),
+                data: vec![1; 1000].into(),
+                block_entities: Vec::new(),
+            },
+            light_data: ClientboundLightUpdatePacketData {
+                trust_edges: true,
+                sky_y_mask: BitSet::new(26),
+                block_y_mask: BitSet::new(26),
+                empty_sky_y_mask: BitSet::new(26),
+                empty_block_y_mask: BitSet::new(26),
+                sky_updates: vec![vec![2; 2048].into()],
+                block_updates: Vec::new(),
+            },
+        }
+        .get();
+        let mut stream = Vec::new();
+        write_packet(&packet, &mut stream, None, &mut None)
             .await
             .unwrap();
+        let mut stream = FramedRead::new(Cursor::new(stream), PacketCodec::default());
 
-        println!("{:?}", buf);
+        let buf = read_raw_packet(&mut stream).await.unwrap();
+        let ClientboundGamePacket::LevelChunkWithLight(p) = decode_packet(&buf).unwrap() else {
+            panic!("Wrong packet");
+        };
+        let frame = buf.as_ptr_range();
+        assert!(frame.contains(&p.chunk_data.data.as_ptr()));
+        assert!(frame.contains(&p.light_data.sky_updates[0].as_ptr()));
+        assert_eq!(p.chunk_data.data, vec![1; 1000]);
+    }
+
+    #[tokio::test]
+    async fn test_tag_names_borrow_from_frame() {
+        let packet = ClientboundUpdateTagsPacket {
+            tags: TagMap(
+                [(
+                    "minecraft:block".into(),
+                    vec![Tags {
+                        name: "minecraft:logs".into(),
+                        elements: vec![1, 2, 3],
+                    }],
+                )]
+                .into_iter()
+                .collect(),
+            ),
+        }
+        .get();
+        let mut stream = Vec::new();
+        write_packet(&packet, &mut stream, None, &mut None)
+            .await
+            .unwrap();
+        let mut stream = FramedRead::new(Cursor::new(stream), PacketCodec::default());
 
-        compression_decoder(&mut Cursor::new(&buf), compression_threshold).unwrap();
+        let buf = read_raw_packet(&mut stream).await.unwrap();
+        let ClientboundGamePacket::UpdateTags(p) = decode_packet(&buf).unwrap() else {
+            panic!("Wrong packet");
+        };
+        let frame = buf.as_ptr_range();
+        let (tag_type, tags) = p.tags.iter().next().unwrap();
+        assert!(frame.contains(&tag_type.as_ptr()));
+        assert!(frame.contains(&tags[0].name.as_ptr()));
+        assert_eq!(&p.tags["minecraft:block"][0].name[..], "minecraft:logs");
     }
 }
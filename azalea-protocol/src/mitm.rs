@@ -0,0 +1,566 @@
+//! A man-in-the-middle proxy for offline-mode servers that can read and
+//! rewrite game packets in both directions.
+//!
+//! The proxy logs the client in itself and makes its own connection to the
+//! server, so the two connections have separate compression and encryption.
+//! Only offline-mode servers work, since the proxy can't authenticate as the
+//! player.
+//!
+//! ```rust,no_run
+//! use azalea_protocol::{
+//!     mitm::{Action, Injector, MitmHandler, MitmProxy},
+//!     packets::game::ServerboundGamePacket,
+//! };
+//!
+//! struct LogChat;
+//! impl MitmHandler for LogChat {
+//!     fn serverbound(&mut self, packet: &mut ServerboundGamePacket) -> Action {
+//!         if let ServerboundGamePacket::Chat(p) = packet {
+//!             println!("client said {}", p.message);
+//!         }
+//!         Action::Forward
+//!     }
+//! }
+//!
+//! # async fn example() -> std::io::Result<()> {
+//! let proxy = MitmProxy::new("127.0.0.1:25565".parse().unwrap());
+//! proxy
+//!     .listen("127.0.0.1:25566".parse().unwrap(), |_hello, _injector| LogChat)
+//!     .await
+//! # }
+//! ```
+
+use crate::{
+    connect::Connection,
+    packets::{
+        game::{ClientboundGamePacket, ServerboundGamePacket},
+        handshake::{
+            client_intention_packet::ClientIntentionPacket, ClientboundHandshakePacket,
+            ServerboundHandshakePacket,
+        },
+        login::{
+            clientbound_login_compression_packet::ClientboundLoginCompressionPacket,
+            serverbound_hello_packet::ServerboundHelloPacket,
+            serverbound_key_packet::ServerboundKeyPacket, ClientboundLoginPacket,
+            ServerboundLoginPacket,
+        },
+        ConnectionProtocol, ProtocolPacket,
+    },
+    read::{decode_packet, read_raw_packet, PacketCodec, ReadPacketError},
+    write::write_raw_packet,
+};
+use log::{error, info, warn};
+use std::{fmt::Debug, net::SocketAddr, sync::Arc};
+use thiserror::Error;
+use tokio::{
+    io::AsyncWriteExt,
+    net::{
+        tcp::{OwnedReadHalf, ReuniteError},
+        TcpListener, TcpStream,
+    },
+    sync::mpsc,
+};
+use tokio_util::codec::FramedRead;
+
+#[derive(Error, Debug)]
+pub enum MitmError {
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    ReadPacket(#[from] Box<ReadPacketError>),
+    #[error("{0}")]
+    Reunite(#[from] ReuniteError),
+    #[error("Expected {expected} but got {packet}")]
+    UnexpectedPacket {
+        expected: &'static str,
+        packet: String,
+    },
+    #[error("The client connected with intention {0:?}")]
+    WrongIntention(ConnectionProtocol),
+    #[error("Couldn't enable encryption with the server: {0}")]
+    Encryption(String),
+}
+
+/// What to do with a packet after a hook has seen it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Send the (possibly modified) packet along.
+    Forward,
+    /// Don't send the packet.
+    Drop,
+}
+
+/// Hooks for the game packets that go through the proxy. One handler is made
+/// for every player that connects.
+///
+/// Packets that Azalea can't parse are forwarded without going through the
+/// hooks.
+pub trait MitmHandler: Send + 'static {
+    /// Called for every packet the server sends to the client.
+    fn clientbound(&mut self, _packet: &mut ClientboundGamePacket) -> Action {
+        Action::Forward
+    }
+    /// Called for every packet the client sends to the server.
+    fn serverbound(&mut self, _packet: &mut ServerboundGamePacket) -> Action {
+        Action::Forward
+    }
+    /// Called once when either side disconnects.
+    fn disconnected(&mut self) {}
+}
+
+enum ProxyEvent {
+    Clientbound(ClientboundGamePacket),
+    Serverbound(ServerboundGamePacket),
+    /// A packet that couldn't be parsed, so it gets forwarded as-is.
+    RawClientbound(Vec<u8>),
+    RawServerbound(Vec<u8>),
+    InjectClientbound(ClientboundGamePacket),
+    InjectServerbound(ServerboundGamePacket),
+    Closed(Option<Box<ReadPacketError>>),
+}
+
+/// A handle for sending extra packets to either side of a proxied
+/// connection. It can be cloned and used from other tasks.
+///
+/// Injected packets don't go through the [`MitmHandler`] hooks.
+#[derive(Clone)]
+pub struct Injector {
+    events: mpsc::UnboundedSender<ProxyEvent>,
+}
+
+impl Injector {
+    /// Send a packet to the client as if the server sent it. Does nothing if
+    /// the connection is closed.
+    pub fn send_to_client(&self, packet: ClientboundGamePacket) {
+        let _ = self.events.send(ProxyEvent::InjectClientbound(packet));
+    }
+
+    /// Send a packet to the server as if the client sent it. Does nothing if
+    /// the connection is closed.
+    pub fn send_to_server(&self, packet: ServerboundGamePacket) {
+        let _ = self.events.send(ProxyEvent::InjectServerbound(packet));
+    }
+
+    /// Whether the connection this injector is for has ended.
+    pub fn is_closed(&self) -> bool {
+        self.events.is_closed()
+    }
+}
+
+/// A proxy that players connect to instead of the server.
+pub struct MitmProxy {
+    upstream: SocketAddr,
+    /// The compression threshold to use between the client and the proxy.
+    /// The server decides the compression between the proxy and the server.
+    pub client_compression_threshold: Option<i32>,
+}
+
+impl MitmProxy {
+    /// Make a proxy for the server at this address.
+    pub fn new(upstream: SocketAddr) -> Self {
+        Self {
+            upstream,
+            client_compression_threshold: None,
+        }
+    }
+
+    /// Compress packets sent to the client. This is independent from the
+    /// server's compression.
+    #[must_use]
+    pub fn with_client_compression_threshold(mut self, threshold: i32) -> Self {
+        self.client_compression_threshold = Some(threshold);
+        self
+    }
+
+    /// Accept connections forever, making a new handler with `make_handler`
+    /// for every player that logs in.
+    pub async fn listen<H, F>(self, address: SocketAddr, make_handler: F) -> std::io::Result<()>
+    where
+        H: MitmHandler,
+        F: Fn(&ServerboundHelloPacket, Injector) -> H + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind(address).await?;
+        let proxy = Arc::new(self);
+        let make_handler = Arc::new(make_handler);
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let proxy = proxy.clone();
+            let make_handler = make_handler.clone();
+            tokio::spawn(async move {
+                if let Err(e) = proxy
+                    .handle_connection(stream, |hello, injector| make_handler(hello, injector))
+                    .await
+                {
+                    error!("Error proxying connection: {e}");
+                }
+            });
+        }
+    }
+
+    /// Proxy a single connection until either side disconnects. Status
+    /// requests are passed through without being parsed.
+    pub async fn handle_connection<H, F>(
+        &self,
+        stream: TcpStream,
+        make_handler: F,
+    ) -> Result<(), MitmError>
+    where
+        H: MitmHandler,
+        F: FnOnce(&ServerboundHelloPacket, Injector) -> H,
+    {
+        stream.set_nodelay(true)?;
+        let mut client: Connection<ServerboundHandshakePacket, ClientboundHandshakePacket> =
+            Connection::wrap(stream);
+        let ServerboundHandshakePacket::ClientIntention(intention) = client.read().await?;
+
+        let upstream_stream = TcpStream::connect(self.upstream).await?;
+        upstream_stream.set_nodelay(true)?;
+        let mut server: Connection<ClientboundHandshakePacket, ServerboundHandshakePacket> =
+            Connection::wrap(upstream_stream);
+        server
+            .write(
+                ClientIntentionPacket {
+                    hostname: self.upstream.ip().to_string(),
+                    port: self.upstream.port(),
+                    ..intention.clone()
+                }
+                .get(),
+            )
+            .await?;
+
+        match intention.intention {
+            ConnectionProtocol::Login => {}
+            ConnectionProtocol::Status => {
+                // the client might've sent more than just the handshake already
+                let buffered = client.reader.read_stream.read_buffer_mut().split();
+                let mut client = client.unwrap()?;
+                let mut server = server.unwrap()?;
+                server.write_all(&buffered).await?;
+                tokio::io::copy_bidirectional(&mut client, &mut server).await?;
+                return Ok(());
+            }
+            intention => return Err(MitmError::WrongIntention(intention)),
+        }
+
+        let mut client = client.login();
+        let mut server = server.login();
+
+        let hello = match client.read().await? {
+            ServerboundLoginPacket::Hello(p) => p,
+            packet => {
+                return Err(MitmError::UnexpectedPacket {
+                    expected: "Hello",
+                    packet: format!("{packet:?}"),
+                })
+            }
+        };
+        info!("{} is connecting through the proxy", hello.name);
+        server.write(hello.clone().get()).await?;
+
+        let (server, profile_packet) = loop {
+            match server.read().await? {
+                ClientboundLoginPacket::Hello(p) => {
+                    // offline-mode servers that have encryption enabled don't
+                    // check that we authenticated, so we can just send a key
+                    let e = azalea_crypto::encrypt(&p.public_key, &p.nonce)
+                        .map_err(MitmError::Encryption)?;
+                    server
+                        .write(
+                            ServerboundKeyPacket {
+                                key_bytes: e.encrypted_public_key,
+                                encrypted_challenge: e.encrypted_nonce,
+                            }
+                            .get(),
+                        )
+                        .await?;
+                    server.set_encryption_key(e.secret_key);
+                }
+                ClientboundLoginPacket::LoginCompression(p) => {
+                    server.set_compression_threshold(p.compression_threshold);
+                }
+                ClientboundLoginPacket::GameProfile(p) => break (server.game(), p),
+                ClientboundLoginPacket::LoginDisconnect(p) => {
+                    info!(
+                        "Server disconnected {} during login: {}",
+                        hello.name, p.reason
+                    );
+                    client.write(p.get()).await?;
+                    return Ok(());
+                }
+                ClientboundLoginPacket::CustomQuery(p) => {
+                    // let the client answer it
+                    client.write(p.get()).await?;
+                    match client.read().await? {
+                        ServerboundLoginPacket::CustomQuery(answer) => {
+                            server.write(answer.get()).await?;
+                        }
+                        packet => {
+                            return Err(MitmError::UnexpectedPacket {
+                                expected: "CustomQuery",
+                                packet: format!("{packet:?}"),
+                            })
+                        }
+                    }
+                }
+            }
+        };
+
+        if let Some(threshold) = self.client_compression_threshold {
+            client
+                .write(
+                    ClientboundLoginCompressionPacket {
+                        compression_threshold: threshold,
+                    }
+                    .get(),
+                )
+                .await?;
+            client.set_compression_threshold(threshold);
+        }
+        client.write(profile_packet.get()).await?;
+        let client = client.game();
+
+        let (events_sender, events) = mpsc::unbounded_channel();
+        let handler = make_handler(
+            &hello,
+            Injector {
+                events: events_sender.clone(),
+            },
+        );
+        run_game(handler, client, server, events_sender, events).await
+    }
+}
+
+/// Forward game packets in both directions until one side disconnects.
+async fn run_game<H: MitmHandler>(
+    mut handler: H,
+    client: Connection<ServerboundGamePacket, ClientboundGamePacket>,
+    server: Connection<ClientboundGamePacket, ServerboundGamePacket>,
+    events_sender: mpsc::UnboundedSender<ProxyEvent>,
+    mut events: mpsc::UnboundedReceiver<ProxyEvent>,
+) -> Result<(), MitmError> {
+    let (client_reader, mut client_writer) = client.into_split();
+    let (server_reader, mut server_writer) = server.into_split();
+
+    let client_task = tokio::spawn(read_task(
+        client_reader.read_stream,
+        events_sender.clone(),
+        ProxyEvent::Serverbound,
+        ProxyEvent::RawServerbound,
+    ));
+    let server_task = tokio::spawn(read_task(
+        server_reader.read_stream,
+        events_sender,
+        ProxyEvent::Clientbound,
+        ProxyEvent::RawClientbound,
+    ));
+
+    let result = async {
+        while let Some(event) = events.recv().await {
+            match event {
+                ProxyEvent::Clientbound(mut packet) => {
+                    if handler.clientbound(&mut packet) == Action::Forward {
+                        client_writer.write(packet).await?;
+                    }
+                }
+                ProxyEvent::Serverbound(mut packet) => {
+                    if handler.serverbound(&mut packet) == Action::Forward {
+                        server_writer.write(packet).await?;
+                    }
+                }
+                ProxyEvent::InjectClientbound(packet) => client_writer.write(packet).await?,
+                ProxyEvent::InjectServerbound(packet) => server_writer.write(packet).await?,
+                ProxyEvent::RawClientbound(data) => {
+                    write_raw_packet(
+                        data,
+                        &mut client_writer.write_stream,
+                        client_writer.compression_threshold,
+                        &mut client_writer.enc_cipher,
+                    )
+                    .await?
+                }
+                ProxyEvent::RawServerbound(data) => {
+                    write_raw_packet(
+                        data,
+                        &mut server_writer.write_stream,
+                        server_writer.compression_threshold,
+                        &mut server_writer.enc_cipher,
+                    )
+                    .await?
+                }
+                ProxyEvent::Closed(None) => break,
+                ProxyEvent::Closed(Some(e)) => return Err(MitmError::from(e)),
+            }
+        }
+        Ok::<(), MitmError>(())
+    }
+    .await;
+
+    client_task.abort();
+    server_task.abort();
+    // closing the write halves makes sure the other side notices we're done
+    let _ = client_writer.shutdown().await;
+    let _ = server_writer.shutdown().await;
+    handler.disconnected();
+    result
+}
+
+/// Read packets from one side of the proxy and turn them into events.
+async fn read_task<P: ProtocolPacket + Debug>(
+    mut read_stream: FramedRead<OwnedReadHalf, PacketCodec>,
+    events: mpsc::UnboundedSender<ProxyEvent>,
+    parsed: impl Fn(P) -> ProxyEvent,
+    raw: impl Fn(Vec<u8>) -> ProxyEvent,
+) {
+    loop {
+        let data = match read_raw_packet(&mut read_stream).await {
+            Ok(data) => data,
+            Err(e) => {
+                let error = match *e {
+                    ReadPacketError::ConnectionClosed => None,
+                    _ => Some(e),
+                };
+                let _ = events.send(ProxyEvent::Closed(error));
+                return;
+            }
+        };
+        let event = match decode_packet::<P>(&data) {
+            Ok(packet) => parsed(packet),
+            Err(e) => {
+                warn!("Couldn't parse packet, forwarding it as-is: {e}");
+                raw(data.to_vec())
+            }
+        };
+        if events.send(event).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packets::{
+        game::{
+            clientbound_keep_alive_packet::ClientboundKeepAlivePacket,
+            serverbound_keep_alive_packet::ServerboundKeepAlivePacket,
+            serverbound_pong_packet::ServerboundPongPacket,
+        },
+        login::clientbound_game_profile_packet::ClientboundGameProfilePacket,
+        PROTOCOL_VERSION,
+    };
+    use azalea_auth::game_profile::GameProfile;
+    use uuid::Uuid;
+
+    /// Adds one to keep alive ids from the server, and replaces keep alives
+    /// from the client with pongs.
+    struct Rewriter(Injector);
+    impl MitmHandler for Rewriter {
+        fn clientbound(&mut self, packet: &mut ClientboundGamePacket) -> Action {
+            if let ClientboundGamePacket::KeepAlive(p) = packet {
+                p.id += 1;
+            }
+            Action::Forward
+        }
+        fn serverbound(&mut self, packet: &mut ServerboundGamePacket) -> Action {
+            if let ServerboundGamePacket::KeepAlive(p) = packet {
+                self.0
+                    .send_to_server(ServerboundPongPacket { id: p.id as u32 }.get());
+                return Action::Drop;
+            }
+            Action::Forward
+        }
+    }
+
+    /// A server that logs in one player, sends a keep alive, and returns the
+    /// first packet the player sends back.
+    async fn upstream(listener: TcpListener) -> ServerboundGamePacket {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut conn: Connection<ServerboundHandshakePacket, ClientboundHandshakePacket> =
+            Connection::wrap(stream);
+        conn.read().await.unwrap();
+        let mut conn = conn.login();
+        let ServerboundLoginPacket::Hello(hello) = conn.read().await.unwrap() else {
+            panic!("Expected hello");
+        };
+        // make sure compression works between the proxy and the server
+        conn.write(
+            ClientboundLoginCompressionPacket {
+                compression_threshold: 16,
+            }
+            .get(),
+        )
+        .await
+        .unwrap();
+        conn.set_compression_threshold(16);
+        conn.write(
+            ClientboundGameProfilePacket {
+                game_profile: GameProfile::new(Uuid::nil(), hello.name),
+            }
+            .get(),
+        )
+        .await
+        .unwrap();
+        let mut conn = conn.game();
+        conn.write(ClientboundKeepAlivePacket { id: 1 }.get())
+            .await
+            .unwrap();
+        conn.read().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rewrite_packets() {
+        let upstream_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_address = upstream_listener.local_addr().unwrap();
+        let upstream_task = tokio::spawn(upstream(upstream_listener));
+
+        let proxy_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy_address = proxy_listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = proxy_listener.accept().await.unwrap();
+            MitmProxy::new(upstream_address)
+                .handle_connection(stream, |_, injector| Rewriter(injector))
+                .await
+        });
+
+        let mut conn = Connection::new(&proxy_address).await.unwrap();
+        conn.write(
+            ClientIntentionPacket {
+                protocol_version: PROTOCOL_VERSION,
+                hostname: proxy_address.ip().to_string(),
+                port: proxy_address.port(),
+                intention: ConnectionProtocol::Login,
+            }
+            .get(),
+        )
+        .await
+        .unwrap();
+        let mut conn = conn.login();
+        conn.write(
+            ServerboundHelloPacket {
+                name: "bot".to_string(),
+                profile_id: None,
+            }
+            .get(),
+        )
+        .await
+        .unwrap();
+        let ClientboundLoginPacket::GameProfile(profile) = conn.read().await.unwrap() else {
+            panic!("Expected game profile");
+        };
+        assert_eq!(profile.game_profile.name, "bot");
+        let mut conn = conn.game();
+
+        let ClientboundGamePacket::KeepAlive(keep_alive) = conn.read().await.unwrap() else {
+            panic!("Expected keep alive");
+        };
+        assert_eq!(keep_alive.id, 2);
+        conn.write(ServerboundKeepAlivePacket { id: 2 }.get())
+            .await
+            .unwrap();
+
+        match upstream_task.await.unwrap() {
+            ServerboundGamePacket::Pong(p) => assert_eq!(p.id, 2),
+            p => panic!("Expected pong, got {p:?}"),
+        }
+    }
+}
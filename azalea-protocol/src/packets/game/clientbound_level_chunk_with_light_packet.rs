@@ -1,5 +1,6 @@
 use azalea_buf::McBuf;
 use azalea_protocol_macros::ClientboundGamePacket;
+use bytes::Bytes;
 
 use super::clientbound_light_update_packet::ClientboundLightUpdatePacketData;
 
@@ -14,8 +15,9 @@ pub struct ClientboundLevelChunkWithLightPacket {
 #[derive(Clone, Debug, McBuf)]
 pub struct ClientboundLevelChunkPacketData {
     pub heightmaps: azalea_nbt::Nbt,
-    // we can't parse the data in azalea-protocol because it dependso on context from other packets
-    pub data: Vec<u8>,
+    // we can't parse the data in azalea-protocol because it dependso on context from other
+    // packets. this borrows from the packet's frame instead of being copied, since it's big
+    pub data: Bytes,
     pub block_entities: Vec<BlockEntity>,
 }
 
@@ -1,6 +1,7 @@
 use azalea_buf::McBuf;
 use azalea_core::BitSet;
 use azalea_protocol_macros::ClientboundGamePacket;
+use bytes::Bytes;
 
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
 pub struct ClientboundLightUpdatePacket {
@@ -18,6 +19,7 @@ pub struct ClientboundLightUpdatePacketData {
     pub block_y_mask: BitSet,
     pub empty_sky_y_mask: BitSet,
     pub empty_block_y_mask: BitSet,
-    pub sky_updates: Vec<Vec<u8>>,
-    pub block_updates: Vec<Vec<u8>>,
+    // these borrow from the packet's frame instead of being copied
+    pub sky_updates: Vec<Bytes>,
+    pub block_updates: Vec<Bytes>,
 }
@@ -70,22 +70,6 @@ pub struct ChatTypeBound {
     pub target_name: Option<FormattedText>,
 }
 
-// must be in Client
-#[derive(Clone, Debug, PartialEq)]
-pub struct MessageSignatureCache {
-    pub entries: Vec<Option<MessageSignature>>,
-}
-
-// impl MessageSignatureCache {
-//     pub fn unpacker(&self) -> impl Fn(u32) -> Option<SignedMessageBody> {
-
-//     }
-// }
-
-// impl PackedSignedMessageBody {
-//     pub fn unpack(&self, unpacker: impl Fn(u32) -> Option<SignedMessageBody>)
-// {} }
-
 impl ClientboundPlayerChatPacket {
     /// Returns the content of the message. If you want to get the FormattedText
     /// for the whole message including the sender part, use
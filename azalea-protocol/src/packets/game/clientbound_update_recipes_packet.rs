@@ -1,5 +1,6 @@
 use azalea_buf::{
-    BufReadError, McBuf, McBufReadable, McBufVarReadable, McBufVarWritable, McBufWritable,
+    BufReadError, Bytes, McBuf, McBufReadable, McBufVarReadable, McBufVarWritable, McBufWritable,
+    SharedString,
 };
 use azalea_core::{ResourceLocation, Slot};
 use azalea_protocol_macros::ClientboundGamePacket;
@@ -15,7 +16,9 @@ pub struct ClientboundUpdateRecipesPacket {
 
 #[derive(Clone, Debug)]
 pub struct Recipe {
-    pub identifier: ResourceLocation,
+    /// The recipe's id. This borrows from the packet's frame instead of being
+    /// copied, since there's usually over a thousand recipes.
+    pub identifier: SharedString,
     pub data: RecipeData,
 }
 
@@ -221,12 +224,24 @@ impl McBufWritable for Recipe {
 
 impl McBufReadable for Recipe {
     fn read_from(buf: &mut Cursor<&[u8]>) -> Result<Self, BufReadError> {
-        let recipe_serializer_name = ResourceLocation::read_from(buf)?;
-        let Ok(recipe_serializer) =
-            RecipeSerializer::from_str(&recipe_serializer_name.to_string()) else {
-                return Err(BufReadError::UnexpectedStringEnumVariant { id: recipe_serializer_name.to_string() });
-            };
-        let identifier = ResourceLocation::read_from(buf)?;
+        Self::read_from_shared(buf, &Bytes::new())
+    }
+
+    fn read_from_shared(buf: &mut Cursor<&[u8]>, shared: &Bytes) -> Result<Self, BufReadError> {
+        let recipe_serializer_name = SharedString::read_from_shared(buf, shared)?;
+        // the namespace is almost always there, so only make a ResourceLocation
+        // (which adds it) if it's not
+        let recipe_serializer = if recipe_serializer_name.contains(':') {
+            RecipeSerializer::from_str(&recipe_serializer_name)
+        } else {
+            RecipeSerializer::from_str(&ResourceLocation::new(&recipe_serializer_name).to_string())
+        };
+        let Ok(recipe_serializer) = recipe_serializer else {
+            return Err(BufReadError::UnexpectedStringEnumVariant {
+                id: recipe_serializer_name.to_string(),
+            });
+        };
+        let identifier = SharedString::read_from_shared(buf, shared)?;
 
         // rust doesn't let us match ResourceLocation so we have to do a big
         // if-else chain :(
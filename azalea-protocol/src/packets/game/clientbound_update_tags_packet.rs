@@ -1,6 +1,5 @@
-use azalea_buf::{BufReadError, McBuf, McBufVarReadable, McBufVarWritable};
+use azalea_buf::{BufReadError, Bytes, McBuf, McBufVarReadable, McBufVarWritable, SharedString};
 use azalea_buf::{McBufReadable, McBufWritable};
-use azalea_core::ResourceLocation;
 use azalea_protocol_macros::ClientboundGamePacket;
 use std::io::Cursor;
 use std::ops::Deref;
@@ -11,25 +10,31 @@ pub struct ClientboundUpdateTagsPacket {
     pub tags: TagMap,
 }
 
+/// The names of the tags and registries borrow from the packet's frame
+/// instead of being copied, since there's thousands of them.
 #[derive(Clone, Debug)]
 pub struct Tags {
-    pub name: ResourceLocation,
+    pub name: SharedString,
     pub elements: Vec<i32>,
 }
 
 #[derive(Clone, Debug)]
-pub struct TagMap(pub HashMap<ResourceLocation, Vec<Tags>>);
+pub struct TagMap(pub HashMap<SharedString, Vec<Tags>>);
 
 impl McBufReadable for TagMap {
     fn read_from(buf: &mut Cursor<&[u8]>) -> Result<Self, BufReadError> {
+        Self::read_from_shared(buf, &Bytes::new())
+    }
+
+    fn read_from_shared(buf: &mut Cursor<&[u8]>, shared: &Bytes) -> Result<Self, BufReadError> {
         let length = u32::var_read_from(buf)? as usize;
         let mut data = HashMap::with_capacity(length);
         for _ in 0..length {
-            let tag_type = ResourceLocation::read_from(buf)?;
+            let tag_type = SharedString::read_from_shared(buf, shared)?;
             let tags_count = i32::var_read_from(buf)? as usize;
             let mut tags_vec = Vec::with_capacity(tags_count);
             for _ in 0..tags_count {
-                let tags = Tags::read_from(buf)?;
+                let tags = Tags::read_from_shared(buf, shared)?;
                 tags_vec.push(tags);
             }
             data.insert(tag_type, tags_vec);
@@ -50,7 +55,11 @@ impl McBufWritable for TagMap {
 }
 impl McBufReadable for Tags {
     fn read_from(buf: &mut Cursor<&[u8]>) -> Result<Self, BufReadError> {
-        let name = ResourceLocation::read_from(buf)?;
+        Self::read_from_shared(buf, &Bytes::new())
+    }
+
+    fn read_from_shared(buf: &mut Cursor<&[u8]>, shared: &Bytes) -> Result<Self, BufReadError> {
+        let name = SharedString::read_from_shared(buf, shared)?;
         let elements = Vec::<i32>::var_read_from(buf)?;
         Ok(Tags { name, elements })
     }
@@ -65,7 +74,7 @@ impl McBufWritable for Tags {
 }
 
 impl Deref for TagMap {
-    type Target = HashMap<ResourceLocation, Vec<Tags>>;
+    type Target = HashMap<SharedString, Vec<Tags>>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -5,6 +5,7 @@ pub mod status;
 
 use crate::read::ReadPacketError;
 use azalea_buf::{BufReadError, McBufVarReadable, McBufVarWritable, McBufWritable};
+use bytes::Bytes;
 use std::io::{Cursor, Write};
 
 // TODO: rename the packet files to just like clientbound_add_entity instead of
@@ -33,15 +34,39 @@ impl ConnectionProtocol {
     }
 }
 
+/// Which way a packet is going.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PacketDirection {
+    /// Sent by the server to the client.
+    Clientbound,
+    /// Sent by the client to the server.
+    Serverbound,
+}
+
 /// An enum of packets for a certain protocol
 pub trait ProtocolPacket
 where
     Self: Sized,
 {
+    /// The state that these packets are sent in.
+    const STATE: ConnectionProtocol;
+    /// Whether these packets are sent to the client or to the server.
+    const DIRECTION: PacketDirection;
+
     fn id(&self) -> u32;
 
     /// Read a packet by its id, `ConnectionProtocol`, and flow
-    fn read(id: u32, buf: &mut Cursor<&[u8]>) -> Result<Self, Box<ReadPacketError>>;
+    fn read(id: u32, buf: &mut Cursor<&[u8]>) -> Result<Self, Box<ReadPacketError>> {
+        Self::read_shared(id, buf, &Bytes::new())
+    }
+
+    /// Read a packet from `buf`, which is a cursor over `shared`. See
+    /// [`McBufReadable::read_from_shared`](azalea_buf::McBufReadable::read_from_shared).
+    fn read_shared(
+        id: u32,
+        buf: &mut Cursor<&[u8]>,
+        shared: &Bytes,
+    ) -> Result<Self, Box<ReadPacketError>>;
 
     fn write(&self, buf: &mut impl Write) -> Result<(), std::io::Error>;
 }
@@ -0,0 +1,424 @@
+//! Connect to servers through SOCKS5 or HTTP CONNECT proxies.
+//!
+//! ```rust,no_run
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! use azalea_protocol::{connect::Connection, proxy::Proxy, resolver, ServerAddress};
+//!
+//! let address = ServerAddress::try_from("localhost").unwrap();
+//! let proxy = Proxy::socks5("127.0.0.1:1080".parse()?).with_auth("user", "hunter2");
+//! // this doesn't do the lookup if the proxy does it for us
+//! let resolved_address = resolver::resolve_address_unless_proxied(&address, Some(&proxy)).await?;
+//! let conn = Connection::new_with_proxy(&address, resolved_address.as_ref(), &proxy).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use std::{
+    io,
+    net::{IpAddr, SocketAddr},
+};
+use thiserror::Error;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+/// The longest HTTP response header we'll accept from a proxy.
+const MAX_HTTP_RESPONSE_LENGTH: usize = 8192;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProxyKind {
+    /// A SOCKS5 proxy, as described in RFC 1928.
+    Socks5,
+    /// An HTTP proxy that supports the `CONNECT` method.
+    Http,
+}
+
+/// A username and password for authenticating with a proxy.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProxyAuth {
+    pub username: String,
+    pub password: String,
+}
+
+/// A proxy server that connections can be tunneled through.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proxy {
+    pub kind: ProxyKind,
+    /// The address of the proxy server itself.
+    pub address: SocketAddr,
+    pub auth: Option<ProxyAuth>,
+    /// Send the server's hostname to the proxy and let it do the DNS lookup,
+    /// instead of sending the IP address that we resolved. SRV records aren't
+    /// used when this is enabled, since the proxy can't look them up.
+    pub remote_dns: bool,
+}
+
+#[derive(Error, Debug)]
+pub enum ProxyError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[error("The proxy responded with SOCKS version {0}, expected 5")]
+    WrongSocksVersion(u8),
+    #[error("The proxy doesn't accept any of our authentication methods")]
+    NoAcceptableAuth,
+    #[error("The proxy rejected our username or password")]
+    AuthFailed,
+    #[error("The proxy couldn't connect to the server: {}", socks5_reply_message(*.0))]
+    Socks5(u8),
+    #[error("The proxy couldn't connect to the server: {0}")]
+    Http(String),
+    #[error("The proxy sent an invalid response")]
+    InvalidResponse,
+    #[error("Hostnames and credentials sent to a SOCKS5 proxy must be at most 255 bytes")]
+    TooLong,
+}
+
+fn socks5_reply_message(code: u8) -> &'static str {
+    match code {
+        1 => "general failure",
+        2 => "connection not allowed by ruleset",
+        3 => "network unreachable",
+        4 => "host unreachable",
+        5 => "connection refused",
+        6 => "TTL expired",
+        7 => "command not supported",
+        8 => "address type not supported",
+        _ => "unknown error",
+    }
+}
+
+impl Proxy {
+    /// A SOCKS5 proxy without authentication.
+    pub fn socks5(address: SocketAddr) -> Self {
+        Self {
+            kind: ProxyKind::Socks5,
+            address,
+            auth: None,
+            remote_dns: false,
+        }
+    }
+
+    /// An HTTP CONNECT proxy without authentication.
+    pub fn http(address: SocketAddr) -> Self {
+        Self {
+            kind: ProxyKind::Http,
+            address,
+            auth: None,
+            remote_dns: false,
+        }
+    }
+
+    /// Authenticate with the proxy using a username and password.
+    #[must_use]
+    pub fn with_auth(mut self, username: &str, password: &str) -> Self {
+        self.auth = Some(ProxyAuth {
+            username: username.to_string(),
+            password: password.to_string(),
+        });
+        self
+    }
+
+    /// Let the proxy resolve hostnames. See [`Self::remote_dns`].
+    #[must_use]
+    pub fn with_remote_dns(mut self) -> Self {
+        self.remote_dns = true;
+        self
+    }
+
+    /// Connect to the proxy and ask it to open a tunnel to `host:port`. The
+    /// host can be a hostname or an IP address.
+    pub async fn connect(&self, host: &str, port: u16) -> Result<TcpStream, ProxyError> {
+        let mut stream = TcpStream::connect(self.address).await?;
+        match self.kind {
+            ProxyKind::Socks5 => self.socks5_handshake(&mut stream, host, port).await?,
+            ProxyKind::Http => self.http_handshake(&mut stream, host, port).await?,
+        }
+        Ok(stream)
+    }
+
+    async fn socks5_handshake(
+        &self,
+        stream: &mut TcpStream,
+        host: &str,
+        port: u16,
+    ) -> Result<(), ProxyError> {
+        // greeting, with the authentication methods we support
+        if self.auth.is_some() {
+            // no authentication or username/password
+            stream.write_all(&[5, 2, 0, 2]).await?;
+        } else {
+            stream.write_all(&[5, 1, 0]).await?;
+        }
+        let mut response = [0; 2];
+        stream.read_exact(&mut response).await?;
+        if response[0] != 5 {
+            return Err(ProxyError::WrongSocksVersion(response[0]));
+        }
+        match (response[1], &self.auth) {
+            (0, _) => {}
+            (2, Some(auth)) => {
+                // username/password authentication (RFC 1929)
+                let mut request = vec![1];
+                for field in [&auth.username, &auth.password] {
+                    let len: u8 = field.len().try_into().map_err(|_| ProxyError::TooLong)?;
+                    request.push(len);
+                    request.extend_from_slice(field.as_bytes());
+                }
+                stream.write_all(&request).await?;
+                let mut response = [0; 2];
+                stream.read_exact(&mut response).await?;
+                if response[1] != 0 {
+                    return Err(ProxyError::AuthFailed);
+                }
+            }
+            _ => return Err(ProxyError::NoAcceptableAuth),
+        }
+
+        // connect command
+        let mut request = vec![5, 1, 0];
+        match host.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => {
+                request.push(1);
+                request.extend_from_slice(&ip.octets());
+            }
+            Ok(IpAddr::V6(ip)) => {
+                request.push(4);
+                request.extend_from_slice(&ip.octets());
+            }
+            Err(_) => {
+                let len: u8 = host.len().try_into().map_err(|_| ProxyError::TooLong)?;
+                request.push(3);
+                request.push(len);
+                request.extend_from_slice(host.as_bytes());
+            }
+        }
+        request.extend_from_slice(&port.to_be_bytes());
+        stream.write_all(&request).await?;
+
+        let mut response = [0; 4];
+        stream.read_exact(&mut response).await?;
+        if response[0] != 5 {
+            return Err(ProxyError::WrongSocksVersion(response[0]));
+        }
+        if response[1] != 0 {
+            return Err(ProxyError::Socks5(response[1]));
+        }
+        // skip the address the proxy bound to, we don't need it
+        let address_len = match response[3] {
+            1 => 4,
+            3 => stream.read_u8().await? as usize,
+            4 => 16,
+            _ => return Err(ProxyError::InvalidResponse),
+        };
+        let mut bound_address = vec![0; address_len + 2];
+        stream.read_exact(&mut bound_address).await?;
+        Ok(())
+    }
+
+    async fn http_handshake(
+        &self,
+        stream: &mut TcpStream,
+        host: &str,
+        port: u16,
+    ) -> Result<(), ProxyError> {
+        let authority = match host.parse::<IpAddr>() {
+            Ok(IpAddr::V6(ip)) => format!("[{ip}]:{port}"),
+            _ => format!("{host}:{port}"),
+        };
+        let mut request = format!("CONNECT {authority} HTTP/1.1\r\nHost: {authority}\r\n");
+        if let Some(auth) = &self.auth {
+            let credentials = BASE64.encode(format!("{}:{}", auth.username, auth.password));
+            request.push_str(&format!("Proxy-Authorization: Basic {credentials}\r\n"));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).await?;
+
+        // read one byte at a time so we don't read past the end of the headers
+        // and into the tunnel
+        let mut response = Vec::new();
+        while !response.ends_with(b"\r\n\r\n") {
+            if response.len() >= MAX_HTTP_RESPONSE_LENGTH {
+                return Err(ProxyError::InvalidResponse);
+            }
+            response.push(stream.read_u8().await?);
+        }
+        let response = String::from_utf8_lossy(&response);
+        let status_line = response.lines().next().unwrap_or_default();
+        let mut parts = status_line.split(' ');
+        if !parts.next().unwrap_or_default().starts_with("HTTP/") {
+            return Err(ProxyError::InvalidResponse);
+        }
+        match parts.next().and_then(|code| code.parse::<u16>().ok()) {
+            Some(200..=299) => Ok(()),
+            Some(407) => Err(ProxyError::AuthFailed),
+            Some(_) => Err(ProxyError::Http(status_line.to_string())),
+            None => Err(ProxyError::InvalidResponse),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// Start a server that sends "hello" to whoever connects, and return its
+    /// address.
+    async fn hello_server() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            stream.write_all(b"hello").await.unwrap();
+        });
+        address
+    }
+
+    /// A stand-in SOCKS5 proxy that only accepts "user"/"pass" and connects
+    /// everything to `target`. The requested host is sent back over the
+    /// returned channel.
+    async fn socks5_proxy(
+        target: SocketAddr,
+    ) -> (SocketAddr, tokio::sync::oneshot::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let (host_tx, host_rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let (mut client, _) = listener.accept().await.unwrap();
+            let mut greeting = [0; 2];
+            client.read_exact(&mut greeting).await.unwrap();
+            let mut methods = vec![0; greeting[1] as usize];
+            client.read_exact(&mut methods).await.unwrap();
+            assert!(methods.contains(&2));
+            client.write_all(&[5, 2]).await.unwrap();
+
+            assert_eq!(client.read_u8().await.unwrap(), 1);
+            let mut credentials = Vec::new();
+            for _ in 0..2 {
+                let mut field = vec![0; client.read_u8().await.unwrap() as usize];
+                client.read_exact(&mut field).await.unwrap();
+                credentials.push(String::from_utf8(field).unwrap());
+            }
+            if credentials != ["user", "pass"] {
+                client.write_all(&[1, 1]).await.unwrap();
+                return;
+            }
+            client.write_all(&[1, 0]).await.unwrap();
+
+            let mut request = [0; 4];
+            client.read_exact(&mut request).await.unwrap();
+            assert_eq!(request[..3], [5, 1, 0]);
+            let host = match request[3] {
+                1 => {
+                    let mut ip = [0; 4];
+                    client.read_exact(&mut ip).await.unwrap();
+                    IpAddr::from(ip).to_string()
+                }
+                3 => {
+                    let mut host = vec![0; client.read_u8().await.unwrap() as usize];
+                    client.read_exact(&mut host).await.unwrap();
+                    String::from_utf8(host).unwrap()
+                }
+                atyp => panic!("Unexpected address type {atyp}"),
+            };
+            let _port = client.read_u16().await.unwrap();
+            host_tx.send(host).unwrap();
+
+            let mut server = TcpStream::connect(target).await.unwrap();
+            client
+                .write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0])
+                .await
+                .unwrap();
+            tokio::io::copy_bidirectional(&mut client, &mut server)
+                .await
+                .ok();
+        });
+        (address, host_rx)
+    }
+
+    /// A stand-in HTTP proxy that connects everything to `target`, and sends
+    /// back the request headers it got.
+    async fn http_proxy(
+        target: SocketAddr,
+    ) -> (SocketAddr, tokio::sync::oneshot::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let (request_tx, request_rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let (mut client, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            while !request.ends_with(b"\r\n\r\n") {
+                request.push(client.read_u8().await.unwrap());
+            }
+            request_tx
+                .send(String::from_utf8(request).unwrap())
+                .unwrap();
+
+            let mut server = TcpStream::connect(target).await.unwrap();
+            client
+                .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+                .await
+                .unwrap();
+            tokio::io::copy_bidirectional(&mut client, &mut server)
+                .await
+                .ok();
+        });
+        (address, request_rx)
+    }
+
+    async fn read_hello(mut stream: TcpStream) {
+        let mut hello = [0; 5];
+        stream.read_exact(&mut hello).await.unwrap();
+        assert_eq!(&hello, b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_socks5() {
+        let target = hello_server().await;
+        let (proxy_address, host) = socks5_proxy(target).await;
+        let proxy = Proxy::socks5(proxy_address).with_auth("user", "pass");
+
+        let stream = proxy.connect("127.0.0.1", target.port()).await.unwrap();
+        assert_eq!(host.await.unwrap(), "127.0.0.1");
+        read_hello(stream).await;
+    }
+
+    #[tokio::test]
+    async fn test_socks5_remote_dns() {
+        let target = hello_server().await;
+        let (proxy_address, host) = socks5_proxy(target).await;
+        let proxy = Proxy::socks5(proxy_address).with_auth("user", "pass");
+
+        let stream = proxy.connect("example.com", 25565).await.unwrap();
+        assert_eq!(host.await.unwrap(), "example.com");
+        read_hello(stream).await;
+    }
+
+    #[tokio::test]
+    async fn test_socks5_wrong_password() {
+        let target = hello_server().await;
+        let (proxy_address, _host) = socks5_proxy(target).await;
+        let proxy = Proxy::socks5(proxy_address).with_auth("user", "wrong");
+
+        assert!(matches!(
+            proxy.connect("127.0.0.1", target.port()).await,
+            Err(ProxyError::AuthFailed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_http() {
+        let target = hello_server().await;
+        let (proxy_address, request) = http_proxy(target).await;
+        let proxy = Proxy::http(proxy_address).with_auth("user", "pass");
+
+        let stream = proxy.connect("example.com", 25565).await.unwrap();
+        let request = request.await.unwrap();
+        assert!(request.starts_with("CONNECT example.com:25565 HTTP/1.1\r\n"));
+        // base64 of "user:pass"
+        assert!(request.contains("Proxy-Authorization: Basic dXNlcjpwYXNz\r\n"));
+        read_hello(stream).await;
+    }
+}
@@ -0,0 +1,390 @@
+//! Get information about a server with the GameSpy4 query protocol.
+//!
+//! Query has to be enabled in the server's `server.properties` with
+//! `enable-query=true`. It listens on UDP, on the same port as the server
+//! unless `query.port` is set.
+//!
+//! ```rust,no_run
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! use azalea_protocol::query::QueryClient;
+//!
+//! let mut query = QueryClient::connect("localhost").await?;
+//! let stat = query.full_stat().await?;
+//! println!("{} is running {}", stat.motd, stat.version);
+//! println!("Online: {}", stat.players.join(", "));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{resolver, ServerAddress};
+use std::{
+    collections::HashMap,
+    io,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+use tokio::{net::UdpSocket, time::timeout};
+
+const MAGIC: [u8; 2] = [0xfe, 0xfd];
+const TYPE_STAT: u8 = 0;
+const TYPE_HANDSHAKE: u8 = 9;
+
+/// The padding after the session id in full stat responses.
+const FULL_STAT_PADDING: &[u8] = b"splitnum\0\x80\0";
+/// The padding between the key-value section and the players.
+const PLAYERS_PADDING: &[u8] = b"\x01player_\0\0";
+
+#[derive(Error, Debug)]
+pub enum QueryError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[error("{0}")]
+    Resolver(#[from] resolver::ResolverError),
+    #[error("The given address could not be parsed into a ServerAddress")]
+    InvalidAddress,
+    #[error("The server didn't respond in time")]
+    Timeout,
+    #[error("The server sent an invalid response: {0}")]
+    InvalidResponse(String),
+}
+
+/// The response to a [`QueryClient::basic_stat`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasicStat {
+    pub motd: String,
+    /// This is always "SMP" for vanilla servers.
+    pub game_type: String,
+    /// The name of the world.
+    pub map: String,
+    pub online_players: u32,
+    pub max_players: u32,
+    pub host_port: u16,
+    pub host_ip: String,
+}
+
+/// The response to a [`QueryClient::full_stat`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FullStat {
+    pub motd: String,
+    /// This is always "SMP" for vanilla servers.
+    pub game_type: String,
+    /// This is always "MINECRAFT" for vanilla servers.
+    pub game_id: String,
+    /// The name of the server's version, like "1.19.4".
+    pub version: String,
+    /// The server software and its plugins, which is empty for vanilla
+    /// servers.
+    pub plugins: String,
+    /// The name of the world.
+    pub map: String,
+    pub online_players: u32,
+    pub max_players: u32,
+    pub host_port: u16,
+    pub host_ip: String,
+    /// The names of the players that are online.
+    pub players: Vec<String>,
+    /// Every key and value the server sent, including ones that aren't in the
+    /// fields above.
+    pub values: HashMap<String, String>,
+}
+
+/// A UDP socket for querying a server.
+pub struct QueryClient {
+    socket: UdpSocket,
+    session_id: i32,
+    /// How long to wait for each response. Query uses UDP, so responses can
+    /// get lost.
+    pub timeout: Duration,
+}
+
+impl QueryClient {
+    /// Look up the server's address and make a socket for querying it. This
+    /// doesn't send anything yet.
+    pub async fn connect(address: impl TryInto<ServerAddress>) -> Result<Self, QueryError> {
+        let address: ServerAddress = address.try_into().map_err(|_| QueryError::InvalidAddress)?;
+        let resolved_address = resolver::resolve_address(&address).await?;
+
+        let local_address = match resolved_address {
+            SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+            SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+        };
+        let socket = UdpSocket::bind(local_address).await?;
+        socket.connect(resolved_address).await?;
+
+        // the server only uses the low 4 bits of each byte
+        let session_id = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.subsec_nanos() as i32)
+            .unwrap_or_default()
+            & 0x0f0f0f0f;
+
+        Ok(Self {
+            socket,
+            session_id,
+            timeout: Duration::from_secs(5),
+        })
+    }
+
+    /// Get the MOTD, player count and a few other things.
+    pub async fn basic_stat(&mut self) -> Result<BasicStat, QueryError> {
+        let token = self.handshake().await?;
+        let response = self.request(TYPE_STAT, &token.to_be_bytes()).await?;
+        parse_basic_stat(&response).ok_or_else(|| invalid_response("malformed basic stat"))
+    }
+
+    /// Get everything from [`Self::basic_stat`] and the server's version,
+    /// plugins and the names of online players.
+    pub async fn full_stat(&mut self) -> Result<FullStat, QueryError> {
+        let token = self.handshake().await?;
+        let mut payload = token.to_be_bytes().to_vec();
+        // asking for the full stat is done by adding 4 bytes of padding
+        payload.extend([0; 4]);
+        let response = self.request(TYPE_STAT, &payload).await?;
+        parse_full_stat(&response)
+    }
+
+    /// Get a challenge token, which is needed for stat requests. Vanilla
+    /// servers forget tokens after 30 seconds, so we get a new one for every
+    /// request.
+    async fn handshake(&mut self) -> Result<i32, QueryError> {
+        let response = self.request(TYPE_HANDSHAKE, &[]).await?;
+        let token = read_string(&mut &response[..])
+            .and_then(|token| token.parse().ok())
+            .ok_or_else(|| invalid_response("the challenge token isn't a number"))?;
+        Ok(token)
+    }
+
+    /// Send a request and return the body of the response, after the type and
+    /// session id.
+    async fn request(&mut self, kind: u8, payload: &[u8]) -> Result<Vec<u8>, QueryError> {
+        let mut request = MAGIC.to_vec();
+        request.push(kind);
+        request.extend(self.session_id.to_be_bytes());
+        request.extend(payload);
+        self.socket.send(&request).await?;
+
+        let mut buf = vec![0; 65536];
+        loop {
+            let len = timeout(self.timeout, self.socket.recv(&mut buf))
+                .await
+                .map_err(|_| QueryError::Timeout)??;
+            let response = &buf[..len];
+            if len < 5 {
+                return Err(invalid_response("the response is too short"));
+            }
+            // ignore late responses to requests that timed out
+            if response[0] != kind || response[1..5] != self.session_id.to_be_bytes() {
+                continue;
+            }
+            return Ok(response[5..].to_vec());
+        }
+    }
+}
+
+fn invalid_response(reason: &str) -> QueryError {
+    QueryError::InvalidResponse(reason.to_string())
+}
+
+/// Read a null-terminated string and advance past it.
+fn read_string(buf: &mut &[u8]) -> Option<String> {
+    let end = buf.iter().position(|&b| b == 0)?;
+    let s = String::from_utf8_lossy(&buf[..end]).into_owned();
+    *buf = &buf[end + 1..];
+    Some(s)
+}
+
+fn parse_basic_stat(mut buf: &[u8]) -> Option<BasicStat> {
+    let motd = read_string(&mut buf)?;
+    let game_type = read_string(&mut buf)?;
+    let map = read_string(&mut buf)?;
+    let online_players = read_string(&mut buf)?.parse().ok()?;
+    let max_players = read_string(&mut buf)?.parse().ok()?;
+    // the port is the only little-endian number in the protocol
+    let host_port = u16::from_le_bytes(buf.get(..2)?.try_into().ok()?);
+    buf = &buf[2..];
+    let host_ip = read_string(&mut buf)?;
+    Some(BasicStat {
+        motd,
+        game_type,
+        map,
+        online_players,
+        max_players,
+        host_port,
+        host_ip,
+    })
+}
+
+fn parse_full_stat(mut buf: &[u8]) -> Result<FullStat, QueryError> {
+    buf = buf
+        .strip_prefix(FULL_STAT_PADDING)
+        .ok_or_else(|| invalid_response("missing the splitnum padding"))?;
+
+    let mut values = HashMap::new();
+    loop {
+        let key = read_string(&mut buf).ok_or_else(|| invalid_response("unterminated key"))?;
+        if key.is_empty() {
+            break;
+        }
+        let value = read_string(&mut buf).ok_or_else(|| invalid_response("unterminated value"))?;
+        values.insert(key, value);
+    }
+
+    buf = buf
+        .strip_prefix(PLAYERS_PADDING)
+        .ok_or_else(|| invalid_response("missing the player_ padding"))?;
+    let mut players = Vec::new();
+    loop {
+        let player =
+            read_string(&mut buf).ok_or_else(|| invalid_response("unterminated player name"))?;
+        if player.is_empty() {
+            break;
+        }
+        players.push(player);
+    }
+
+    let get = |key: &str| {
+        values
+            .get(key)
+            .cloned()
+            .ok_or_else(|| QueryError::InvalidResponse(format!("missing {key}")))
+    };
+    let parse = |key: &str| -> Result<u32, QueryError> {
+        get(key)?
+            .parse()
+            .map_err(|_| QueryError::InvalidResponse(format!("{key} isn't a number")))
+    };
+    Ok(FullStat {
+        motd: get("hostname")?,
+        game_type: get("gametype")?,
+        game_id: get("game_id")?,
+        version: get("version")?,
+        plugins: get("plugins")?,
+        map: get("map")?,
+        online_players: parse("numplayers")?,
+        max_players: parse("maxplayers")?,
+        host_port: parse("hostport")?
+            .try_into()
+            .map_err(|_| invalid_response("hostport is too big"))?,
+        host_ip: get("hostip")?,
+        players,
+        values,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A stand-in query server with a fixed challenge token, which answers
+    /// like a vanilla server with Steve and Alex online.
+    async fn query_server() -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0; 1460];
+            loop {
+                let (len, from) = socket.recv_from(&mut buf).await.unwrap();
+                let request = &buf[..len];
+                assert_eq!(request[..2], MAGIC);
+                let kind = request[2];
+                let session_id = &request[3..7];
+
+                let mut response = vec![kind];
+                response.extend(session_id);
+                match kind {
+                    TYPE_HANDSHAKE => response.extend(b"9513307\0"),
+                    TYPE_STAT => {
+                        assert_eq!(request[7..11], 9513307i32.to_be_bytes());
+                        if len == 15 {
+                            response.extend(FULL_STAT_PADDING);
+                            for (key, value) in [
+                                ("hostname", "A Minecraft Server"),
+                                ("gametype", "SMP"),
+                                ("game_id", "MINECRAFT"),
+                                ("version", "1.19.4"),
+                                ("plugins", ""),
+                                ("map", "world"),
+                                ("numplayers", "2"),
+                                ("maxplayers", "20"),
+                                ("hostport", "25565"),
+                                ("hostip", "127.0.0.1"),
+                            ] {
+                                response.extend(key.as_bytes());
+                                response.push(0);
+                                response.extend(value.as_bytes());
+                                response.push(0);
+                            }
+                            response.push(0);
+                            response.extend(PLAYERS_PADDING);
+                            response.extend(b"Steve\0Alex\0\0");
+                        } else {
+                            response.extend(b"A Minecraft Server\0SMP\0world\x002\x0020\0");
+                            response.extend(25565u16.to_le_bytes());
+                            response.extend(b"127.0.0.1\0");
+                        }
+                    }
+                    _ => continue,
+                }
+                socket.send_to(&response, from).await.unwrap();
+            }
+        });
+        address
+    }
+
+    #[tokio::test]
+    async fn test_basic_stat() {
+        let address = query_server().await;
+        let mut query = QueryClient::connect(address).await.unwrap();
+        assert_eq!(
+            query.basic_stat().await.unwrap(),
+            BasicStat {
+                motd: "A Minecraft Server".to_string(),
+                game_type: "SMP".to_string(),
+                map: "world".to_string(),
+                online_players: 2,
+                max_players: 20,
+                host_port: 25565,
+                host_ip: "127.0.0.1".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_full_stat() {
+        let address = query_server().await;
+        let mut query = QueryClient::connect(address).await.unwrap();
+        let stat = query.full_stat().await.unwrap();
+        assert_eq!(stat.motd, "A Minecraft Server");
+        assert_eq!(stat.version, "1.19.4");
+        assert_eq!(stat.plugins, "");
+        assert_eq!(stat.online_players, 2);
+        assert_eq!(stat.max_players, 20);
+        assert_eq!(stat.host_port, 25565);
+        assert_eq!(stat.players, vec!["Steve", "Alex"]);
+        assert_eq!(stat.values.len(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        // nothing is listening here, so we never get a response
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = socket.local_addr().unwrap();
+        let mut query = QueryClient::connect(address).await.unwrap();
+        query.timeout = Duration::from_millis(50);
+        assert!(matches!(query.basic_stat().await, Err(QueryError::Timeout)));
+    }
+
+    #[test]
+    fn test_invalid_full_stat() {
+        assert!(parse_full_stat(b"hostname\0").is_err());
+        let mut response = FULL_STAT_PADDING.to_vec();
+        response.extend(b"hostname\0motd\0\0");
+        response.extend(PLAYERS_PADDING);
+        response.extend(b"\0");
+        assert!(matches!(
+            parse_full_stat(&response),
+            Err(QueryError::InvalidResponse(reason)) if reason == "missing gametype"
+        ));
+    }
+}
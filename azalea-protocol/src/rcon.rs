@@ -0,0 +1,314 @@
+//! Run commands on a server with RCON.
+//!
+//! RCON has to be enabled in the server's `server.properties` with
+//! `enable-rcon=true` and an `rcon.password`.
+//!
+//! ```rust,no_run
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! use azalea_protocol::rcon::RconClient;
+//!
+//! let mut rcon = RconClient::connect("localhost:25575", "hunter2").await?;
+//! let players = rcon.command("list").await?;
+//! println!("{players}");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{resolver, ServerAddress};
+use std::io;
+use thiserror::Error;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+/// The port that RCON listens on if `rcon.port` isn't set. Note that
+/// [`ServerAddress`] defaults to 25565, so you'll usually have to include the
+/// port in the address.
+pub const DEFAULT_RCON_PORT: u16 = 25575;
+
+/// The longest command the server will accept. Vanilla reads at most 1460
+/// bytes per packet, and 14 of those are the header and null terminators.
+pub const MAX_COMMAND_LENGTH: usize = 1446;
+
+/// The longest packet we'll read. Vanilla splits responses into packets of at
+/// most 4096 (UTF-16) characters before encoding them as UTF-8, and each of
+/// those can take up to 3 bytes.
+const MAX_PACKET_LENGTH: i32 = 4096 * 3 + 10;
+
+const TYPE_RESPONSE: i32 = 0;
+const TYPE_COMMAND: i32 = 2;
+const TYPE_AUTH_RESPONSE: i32 = 2;
+const TYPE_LOGIN: i32 = 3;
+
+#[derive(Error, Debug)]
+pub enum RconError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[error("{0}")]
+    Resolver(#[from] resolver::ResolverError),
+    #[error("The given address could not be parsed into a ServerAddress")]
+    InvalidAddress,
+    #[error("The server rejected the RCON password")]
+    AuthFailed,
+    #[error("Commands sent over RCON must be at most {MAX_COMMAND_LENGTH} bytes")]
+    CommandTooLong,
+    #[error("The server sent an invalid packet: {0}")]
+    InvalidPacket(String),
+}
+
+/// A packet sent to or from the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RconPacket {
+    id: i32,
+    kind: i32,
+    body: String,
+}
+
+impl RconPacket {
+    async fn write(&self, stream: &mut TcpStream) -> io::Result<()> {
+        let mut buf = Vec::with_capacity(14 + self.body.len());
+        buf.extend((10 + self.body.len() as i32).to_le_bytes());
+        buf.extend(self.id.to_le_bytes());
+        buf.extend(self.kind.to_le_bytes());
+        buf.extend(self.body.as_bytes());
+        // the body is null-terminated, and then there's an empty string
+        buf.extend([0, 0]);
+        stream.write_all(&buf).await
+    }
+
+    async fn read(stream: &mut TcpStream) -> Result<Self, RconError> {
+        let len = stream.read_i32_le().await?;
+        if !(10..=MAX_PACKET_LENGTH).contains(&len) {
+            return Err(RconError::InvalidPacket(format!(
+                "the length ({len}) should be between 10 and {MAX_PACKET_LENGTH}"
+            )));
+        }
+        let mut buf = vec![0; len as usize];
+        stream.read_exact(&mut buf).await?;
+
+        let id = i32::from_le_bytes(buf[0..4].try_into().unwrap());
+        let kind = i32::from_le_bytes(buf[4..8].try_into().unwrap());
+        let body = &buf[8..];
+        let body = match body.iter().position(|&b| b == 0) {
+            Some(end) => &body[..end],
+            None => {
+                return Err(RconError::InvalidPacket(
+                    "the body isn't null-terminated".to_string(),
+                ))
+            }
+        };
+        Ok(Self {
+            id,
+            kind,
+            body: String::from_utf8_lossy(body).into_owned(),
+        })
+    }
+}
+
+/// A connection to a server's RCON port that's been authenticated.
+pub struct RconClient {
+    stream: TcpStream,
+    next_id: i32,
+}
+
+impl RconClient {
+    /// Connect to the server and log in with the RCON password.
+    pub async fn connect(
+        address: impl TryInto<ServerAddress>,
+        password: &str,
+    ) -> Result<Self, RconError> {
+        let address: ServerAddress = address.try_into().map_err(|_| RconError::InvalidAddress)?;
+        let resolved_address = resolver::resolve_address(&address).await?;
+        let stream = TcpStream::connect(resolved_address).await?;
+        Self::login(stream, password).await
+    }
+
+    /// Log in on a stream that's already connected to the RCON port.
+    pub async fn login(stream: TcpStream, password: &str) -> Result<Self, RconError> {
+        let mut client = Self { stream, next_id: 1 };
+        let id = client.next_id();
+        RconPacket {
+            id,
+            kind: TYPE_LOGIN,
+            body: password.to_string(),
+        }
+        .write(&mut client.stream)
+        .await?;
+
+        loop {
+            let packet = RconPacket::read(&mut client.stream).await?;
+            // some servers send an empty response before the auth response
+            if packet.kind != TYPE_AUTH_RESPONSE {
+                continue;
+            }
+            // the id is -1 if the password was wrong
+            if packet.id == -1 {
+                return Err(RconError::AuthFailed);
+            }
+            if packet.id != id {
+                return Err(RconError::InvalidPacket(format!(
+                    "expected the auth response to have id {id}, got {}",
+                    packet.id
+                )));
+            }
+            return Ok(client);
+        }
+    }
+
+    fn next_id(&mut self) -> i32 {
+        let id = self.next_id;
+        // ids have to be positive since -1 means the auth failed
+        self.next_id = self.next_id.checked_add(1).unwrap_or(1);
+        id
+    }
+
+    /// Run a command and return what it output.
+    ///
+    /// Long outputs are split into several packets by the server, so we send
+    /// an extra packet after the command and read until we get the response
+    /// to it.
+    pub async fn command(&mut self, command: &str) -> Result<String, RconError> {
+        if command.len() > MAX_COMMAND_LENGTH {
+            return Err(RconError::CommandTooLong);
+        }
+
+        let id = self.next_id();
+        RconPacket {
+            id,
+            kind: TYPE_COMMAND,
+            body: command.to_string(),
+        }
+        .write(&mut self.stream)
+        .await?;
+        // the server answers packets it doesn't understand with "Unknown
+        // request", which marks the end of the command's output
+        let end_id = self.next_id();
+        RconPacket {
+            id: end_id,
+            kind: TYPE_RESPONSE,
+            body: String::new(),
+        }
+        .write(&mut self.stream)
+        .await?;
+
+        let mut output = String::new();
+        loop {
+            let packet = RconPacket::read(&mut self.stream).await?;
+            if packet.id == end_id {
+                return Ok(output);
+            }
+            if packet.id != id {
+                return Err(RconError::InvalidPacket(format!(
+                    "expected a response with id {id}, got {}",
+                    packet.id
+                )));
+            }
+            output.push_str(&packet.body);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// A stand-in RCON server that accepts "hunter2" and answers "list" with
+    /// `list_output`, split into packets of 4096 characters like vanilla.
+    async fn rcon_server(list_output: String) -> std::net::SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let list_output = list_output.clone();
+                tokio::spawn(async move {
+                    let mut authed = false;
+                    while let Ok(packet) = RconPacket::read(&mut stream).await {
+                        let responses = match packet.kind {
+                            TYPE_LOGIN if packet.body == "hunter2" => {
+                                authed = true;
+                                vec![(packet.id, TYPE_AUTH_RESPONSE, String::new())]
+                            }
+                            TYPE_LOGIN => vec![(-1, TYPE_AUTH_RESPONSE, String::new())],
+                            TYPE_COMMAND if authed && packet.body == "list" => list_output
+                                .chars()
+                                .collect::<Vec<_>>()
+                                .chunks(4096)
+                                .map(|chunk| {
+                                    let chunk = chunk.iter().collect::<String>();
+                                    (packet.id, TYPE_RESPONSE, chunk)
+                                })
+                                .collect(),
+                            TYPE_COMMAND => vec![(
+                                packet.id,
+                                TYPE_RESPONSE,
+                                format!("Unknown command: {}", packet.body),
+                            )],
+                            kind => vec![(
+                                packet.id,
+                                TYPE_RESPONSE,
+                                format!("Unknown request {kind:x}"),
+                            )],
+                        };
+                        for (id, kind, body) in responses {
+                            RconPacket { id, kind, body }
+                                .write(&mut stream)
+                                .await
+                                .unwrap();
+                        }
+                    }
+                });
+            }
+        });
+        address
+    }
+
+    #[tokio::test]
+    async fn test_command() {
+        let address = rcon_server("There are 0 of a max of 20 players online: ".to_string()).await;
+        let mut rcon = RconClient::connect(address, "hunter2").await.unwrap();
+        assert_eq!(
+            rcon.command("list").await.unwrap(),
+            "There are 0 of a max of 20 players online: "
+        );
+        assert_eq!(rcon.command("foo").await.unwrap(), "Unknown command: foo");
+    }
+
+    #[tokio::test]
+    async fn test_multi_packet_response() {
+        let output = "a".repeat(10000);
+        let address = rcon_server(output.clone()).await;
+        let mut rcon = RconClient::connect(address, "hunter2").await.unwrap();
+        assert_eq!(rcon.command("list").await.unwrap(), output);
+    }
+
+    #[tokio::test]
+    async fn test_multibyte_response() {
+        // the first packet is 4096 three-byte characters
+        let output = "€".repeat(4096) + "§é";
+        let address = rcon_server(output.clone()).await;
+        let mut rcon = RconClient::connect(address, "hunter2").await.unwrap();
+        assert_eq!(rcon.command("list").await.unwrap(), output);
+    }
+
+    #[tokio::test]
+    async fn test_wrong_password() {
+        let address = rcon_server(String::new()).await;
+        assert!(matches!(
+            RconClient::connect(address, "password123").await,
+            Err(RconError::AuthFailed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_command_too_long() {
+        let address = rcon_server(String::new()).await;
+        let mut rcon = RconClient::connect(address, "hunter2").await.unwrap();
+        assert!(matches!(
+            rcon.command(&"a".repeat(MAX_COMMAND_LENGTH + 1)).await,
+            Err(RconError::CommandTooLong)
+        ));
+    }
+}
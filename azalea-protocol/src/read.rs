@@ -4,19 +4,15 @@ use crate::packets::ProtocolPacket;
 use azalea_buf::BufReadError;
 use azalea_buf::McBufVarReadable;
 use azalea_crypto::Aes128CfbDec;
-use bytes::Buf;
-use bytes::BytesMut;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use flate2::read::ZlibDecoder;
 use futures::StreamExt;
 use log::{log_enabled, trace};
 use std::backtrace::Backtrace;
-use std::{
-    fmt::Debug,
-    io::{Cursor, Read},
-};
+use std::{fmt::Debug, io::Cursor};
 use thiserror::Error;
 use tokio::io::AsyncRead;
-use tokio_util::codec::{BytesCodec, FramedRead};
+use tokio_util::codec::{Decoder, FramedRead};
 
 #[derive(Error, Debug)]
 pub enum ReadPacketError {
@@ -77,56 +73,43 @@ pub enum FrameSplitterError {
 }
 
 /// Read a length, then read that amount of bytes from `BytesMut`. If there's
-/// not enough data, return None
-fn parse_frame(buffer: &mut BytesMut) -> Result<BytesMut, FrameSplitterError> {
-    // copy the buffer first and read from the copy, then once we make sure
-    // the packet is all good we read it fully
+/// not enough data yet, return None and leave the buffer alone.
+fn parse_frame(buffer: &mut BytesMut) -> Result<Option<BytesMut>, FrameSplitterError> {
+    // read from a cursor first, then once we make sure the packet is all good
+    // we take it out of the buffer
     let mut buffer_copy = Cursor::new(&buffer[..]);
     // Packet Length
     let length = match u32::var_read_from(&mut buffer_copy) {
         Ok(length) => length as usize,
-        Err(err) => match err {
-            BufReadError::Io { source } => return Err(FrameSplitterError::Io { source }),
-            _ => return Err(err.into()),
-        },
+        // we probably just haven't read the whole varint yet
+        Err(BufReadError::Io { .. }) => return Ok(None),
+        Err(err) => return Err(err.into()),
     };
 
     if length > buffer_copy.remaining() {
-        return Err(FrameSplitterError::BadLength {
-            max: buffer_copy.remaining(),
-            size: length,
-        });
+        return Ok(None);
     }
 
-    // we read from the copy and we know it's legit, so we can take those bytes
-    // from the real buffer now
-
     // the length of the varint that says the length of the whole packet
     let varint_length = buffer.remaining() - buffer_copy.remaining();
 
     buffer.advance(varint_length);
-    let data = buffer.split_to(length);
-
-    Ok(data)
+    Ok(Some(buffer.split_to(length)))
 }
 
-fn frame_splitter(buffer: &mut BytesMut) -> Result<Option<Vec<u8>>, FrameSplitterError> {
-    // https://tokio.rs/tokio/tutorial/framing
-    let read_frame = parse_frame(buffer);
-    match read_frame {
-        Ok(frame) => return Ok(Some(frame.to_vec())),
-        Err(err) => match err {
-            FrameSplitterError::BadLength { .. } | FrameSplitterError::Io { .. } => {
-                // we probably just haven't read enough yet
-            }
-            _ => return Err(err),
-        },
-    }
-
-    Ok(None)
+/// Parse a packet from the bytes returned by [`read_raw_packet`]. Anything in
+/// the packet that can share memory with `buf` (like chunk data and the
+/// identifiers in `ClientboundUpdateTagsPacket`) does instead of being copied.
+pub fn decode_packet<P: ProtocolPacket + Debug>(buf: &Bytes) -> Result<P, Box<ReadPacketError>> {
+    let mut stream = Cursor::new(&buf[..]);
+    let packet_id =
+        u32::var_read_from(&mut stream).map_err(|e| ReadPacketError::ReadPacketId { source: e })?;
+    P::read_shared(packet_id, &mut stream, buf)
 }
 
-fn packet_decoder<P: ProtocolPacket + Debug>(
+/// Parse a packet from its id and data, after it's been decrypted and
+/// decompressed.
+pub fn packet_decoder<P: ProtocolPacket + Debug>(
     stream: &mut Cursor<&[u8]>,
 ) -> Result<P, Box<ReadPacketError>> {
     // Packet ID
@@ -161,99 +144,140 @@ pub enum DecompressionError {
     AboveCompressionThreshold { size: u32, maximum: u32 },
 }
 
-/// Get the decompressed bytes from a packet. It must have been decrypted
-/// first.
-pub fn compression_decoder(
-    stream: &mut Cursor<&[u8]>,
-    compression_threshold: u32,
-) -> Result<Vec<u8>, DecompressionError> {
-    // Data Length
-    let n = u32::var_read_from(stream)?;
-    if n == 0 {
-        // no data size, no compression
-        let mut buf = vec![];
-        std::io::Read::read_to_end(stream, &mut buf)?;
-        return Ok(buf);
-    }
+/// Splits the data from a stream into packets, decrypting and decompressing
+/// them.
+///
+/// This is kept around for as long as the connection so its buffers can be
+/// reused. Data is decrypted in place as soon as it's read, uncompressed
+/// packets are split off of the read buffer without being copied, and
+/// compressed packets are inflated into a buffer that gets reused once the
+/// previous packet is dropped.
+#[derive(Default)]
+pub struct PacketCodec {
+    pub compression_threshold: Option<u32>,
+    pub cipher: Option<Aes128CfbDec>,
+    /// How many bytes at the start of the read buffer have already been
+    /// decrypted.
+    decrypted: usize,
+    decompress_buffer: BytesMut,
+}
 
-    if VALIDATE_DECOMPRESSED {
-        if n < compression_threshold {
-            return Err(DecompressionError::BelowCompressionThreshold {
-                size: n,
-                threshold: compression_threshold,
-            });
+impl PacketCodec {
+    /// Get the decompressed bytes from a packet. It must have been decrypted
+    /// first.
+    fn decompress(
+        &mut self,
+        frame: Bytes,
+        compression_threshold: u32,
+    ) -> Result<Bytes, DecompressionError> {
+        let mut stream = Cursor::new(&frame[..]);
+        // Data Length
+        let n = u32::var_read_from(&mut stream)?;
+        let data_start = stream.position() as usize;
+        if n == 0 {
+            // no data size, no compression
+            return Ok(frame.slice(data_start..));
         }
-        if n > MAXIMUM_UNCOMPRESSED_LENGTH {
-            return Err(DecompressionError::AboveCompressionThreshold {
-                size: n,
-                maximum: MAXIMUM_UNCOMPRESSED_LENGTH,
-            });
+
+        if VALIDATE_DECOMPRESSED {
+            if n < compression_threshold {
+                return Err(DecompressionError::BelowCompressionThreshold {
+                    size: n,
+                    threshold: compression_threshold,
+                });
+            }
+            if n > MAXIMUM_UNCOMPRESSED_LENGTH {
+                return Err(DecompressionError::AboveCompressionThreshold {
+                    size: n,
+                    maximum: MAXIMUM_UNCOMPRESSED_LENGTH,
+                });
+            }
         }
-    }
 
-    let mut decoded_buf = vec![];
-    let mut decoder = ZlibDecoder::new(stream);
-    decoder.read_to_end(&mut decoded_buf)?;
+        // this only allocates if a packet from the last time we decompressed is
+        // still being used
+        self.decompress_buffer.reserve(n as usize);
+        let mut writer = (&mut self.decompress_buffer).writer();
+        let mut decoder = ZlibDecoder::new(&frame[data_start..]);
+        std::io::copy(&mut decoder, &mut writer)?;
 
-    Ok(decoded_buf)
+        Ok(self.decompress_buffer.split().freeze())
+    }
 }
 
-/// Read a single packet from a stream.
-///
-/// The buffer is required because servers may send multiple packets in the
-/// same frame, so we need to store the packet data that's left to read.
-///
-/// The current protocol state must be passed as a generic.
-pub async fn read_packet<'a, P: ProtocolPacket + Debug, R>(
-    stream: &'a mut R,
-    buffer: &mut BytesMut,
-    compression_threshold: Option<u32>,
-    cipher: &mut Option<Aes128CfbDec>,
-) -> Result<P, Box<ReadPacketError>>
-where
-    R: AsyncRead + std::marker::Unpin + std::marker::Send + std::marker::Sync,
-{
-    let mut framed = FramedRead::new(stream, BytesCodec::new());
-    let mut buf = loop {
-        if let Some(buf) = frame_splitter(buffer).map_err(ReadPacketError::from)? {
-            // we got a full packet!!
-            break buf;
-        } else {
-            // no full packet yet :( keep reading
-        };
-
-        // if we were given a cipher, decrypt the packet
-        if let Some(message) = framed.next().await {
-            let mut bytes = message.map_err(ReadPacketError::from)?;
+impl Decoder for PacketCodec {
+    type Item = Bytes;
+    type Error = ReadPacketError;
 
-            if let Some(cipher) = cipher {
-                azalea_crypto::decrypt_packet(cipher, &mut bytes);
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Bytes>, ReadPacketError> {
+        // decrypt whatever was read since last time
+        if let Some(cipher) = &mut self.cipher {
+            if self.decrypted < src.len() {
+                azalea_crypto::decrypt_packet(cipher, &mut src[self.decrypted..]);
             }
+        }
 
-            buffer.extend_from_slice(&bytes);
-        } else {
-            return Err(Box::new(ReadPacketError::ConnectionClosed));
+        let frame = parse_frame(src)?;
+        // everything that's left in the buffer has been decrypted
+        self.decrypted = src.len();
+        let Some(frame) = frame else {
+            return Ok(None);
         };
-    };
 
-    if let Some(compression_threshold) = compression_threshold {
-        buf = compression_decoder(&mut Cursor::new(&buf[..]), compression_threshold)
-            .map_err(ReadPacketError::from)?;
+        let mut buf = frame.freeze();
+        if let Some(compression_threshold) = self.compression_threshold {
+            buf = self.decompress(buf, compression_threshold)?;
+        }
+
+        if log_enabled!(log::Level::Trace) {
+            let buf_string: String = {
+                if buf.len() > 500 {
+                    let cut_off_buf = &buf[..500];
+                    format!("{cut_off_buf:?}...")
+                } else {
+                    format!("{:?}", &buf[..])
+                }
+            };
+            trace!("Reading packet with bytes: {buf_string}");
+        }
+
+        Ok(Some(buf))
     }
 
-    if log_enabled!(log::Level::Trace) {
-        let buf_string: String = {
-            if buf.len() > 500 {
-                let cut_off_buf = &buf[..500];
-                format!("{cut_off_buf:?}...")
-            } else {
-                format!("{buf:?}")
-            }
-        };
-        trace!("Reading packet with bytes: {buf_string}");
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Bytes>, ReadPacketError> {
+        // if the stream ends partway through a packet, treat it the same as the
+        // connection closing normally
+        self.decode(src)
     }
+}
 
-    let packet = packet_decoder(&mut Cursor::new(&buf[..]))?;
+/// Read a single packet from a stream.
+///
+/// The current protocol state must be passed as a generic.
+pub async fn read_packet<P: ProtocolPacket + Debug, R>(
+    stream: &mut FramedRead<R, PacketCodec>,
+) -> Result<P, Box<ReadPacketError>>
+where
+    R: AsyncRead + Unpin,
+{
+    let buf = read_raw_packet(stream).await?;
+    decode_packet(&buf)
+}
 
-    Ok(packet)
+/// Read the bytes of a single packet from a stream, without parsing it. The
+/// returned bytes are decrypted and decompressed and start with the packet
+/// id, so they can be passed to [`decode_packet`].
+///
+/// This is cancel safe, if the future is dropped before it finishes then no
+/// data is lost.
+pub async fn read_raw_packet<R>(
+    stream: &mut FramedRead<R, PacketCodec>,
+) -> Result<Bytes, Box<ReadPacketError>>
+where
+    R: AsyncRead + Unpin,
+{
+    match stream.next().await {
+        Some(buf) => Ok(buf?),
+        None => Err(Box::new(ReadPacketError::ConnectionClosed)),
+    }
 }
@@ -1,6 +1,6 @@
 //! Resolve IPs from hostnames.
 
-use crate::ServerAddress;
+use crate::{proxy::Proxy, ServerAddress};
 use async_recursion::async_recursion;
 use std::net::{IpAddr, SocketAddr};
 use thiserror::Error;
@@ -17,6 +17,19 @@ pub enum ResolverError {
     NoIp,
 }
 
+/// Resolve the address unless we're connecting through a proxy that does its
+/// own DNS lookups ([`Proxy::remote_dns`]), in which case `None` is returned so
+/// the lookup doesn't happen outside of the proxy.
+pub async fn resolve_address_unless_proxied(
+    address: &ServerAddress,
+    proxy: Option<&Proxy>,
+) -> Result<Option<SocketAddr>, ResolverError> {
+    if proxy.map_or(false, |proxy| proxy.remote_dns) {
+        return Ok(None);
+    }
+    resolve_address(address).await.map(Some)
+}
+
 /// Resolve a Minecraft server address into an IP address and port.
 /// If it's already an IP address, it's returned as-is.
 #[must_use]
@@ -89,7 +89,21 @@ where
     W: AsyncWrite + Unpin + Send,
 {
     trace!("Sending packet: {:?}", packet,);
-    let mut buf = packet_encoder(packet).unwrap();
+    let buf = packet_encoder(packet).unwrap();
+    write_raw_packet(buf, stream, compression_threshold, cipher).await
+}
+
+/// Write a packet that was already encoded with [`packet_encoder`], so it
+/// starts with the packet id.
+pub async fn write_raw_packet<W>(
+    mut buf: Vec<u8>,
+    stream: &mut W,
+    compression_threshold: Option<u32>,
+    cipher: &mut Option<Aes128CfbEnc>,
+) -> std::io::Result<()>
+where
+    W: AsyncWrite + Unpin + Send,
+{
     if let Some(threshold) = compression_threshold {
         buf = compression_encoder(&buf, threshold).await.unwrap();
     }
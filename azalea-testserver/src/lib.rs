@@ -0,0 +1,226 @@
+#![doc = include_str!("../README.md")]
+
+mod player;
+mod registry;
+
+pub use player::TestPlayer;
+
+use azalea_auth::game_profile::GameProfile;
+use azalea_protocol::{
+    connect::Connection,
+    packets::{
+        handshake::{ClientboundHandshakePacket, ServerboundHandshakePacket},
+        login::{
+            clientbound_game_profile_packet::ClientboundGameProfilePacket,
+            clientbound_hello_packet::ClientboundHelloPacket,
+            clientbound_login_compression_packet::ClientboundLoginCompressionPacket,
+            ServerboundLoginPacket,
+        },
+        ConnectionProtocol,
+    },
+    read::ReadPacketError,
+};
+use log::debug;
+use md5::{Digest, Md5};
+use rand::{rngs::OsRng, RngCore};
+use rsa::{pkcs8::EncodePublicKey, Pkcs1v15Encrypt, RsaPrivateKey, RsaPublicKey};
+use std::{future::Future, net::SocketAddr, time::Duration};
+use thiserror::Error;
+use tokio::net::TcpListener;
+use uuid::Uuid;
+
+/// How long we wait for the client to send a packet before giving up, so a
+/// broken test fails instead of hanging forever.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Error, Debug)]
+pub enum TestServerError {
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    ReadPacket(#[from] Box<ReadPacketError>),
+    #[error("Expected {expected} but the client sent {packet}")]
+    UnexpectedPacket {
+        expected: &'static str,
+        packet: String,
+    },
+    #[error("The client wanted to connect with intention {0:?} instead of logging in")]
+    WrongIntention(ConnectionProtocol),
+    #[error("Couldn't decrypt what the client sent: {0}")]
+    Encryption(String),
+    #[error("The client sent back the wrong encryption challenge")]
+    WrongChallenge,
+    #[error("The client disconnected")]
+    Disconnected,
+    #[error("Timed out waiting for the client")]
+    Timeout,
+}
+
+/// A Minecraft server that only exists to test clients.
+///
+/// It listens on a random port on loopback, and [`Self::accept`] logs in the
+/// next client that connects and returns a [`TestPlayer`] for scripting what
+/// the client sees.
+pub struct TestServer {
+    listener: TcpListener,
+    address: SocketAddr,
+    compression_threshold: Option<i32>,
+    private_key: Option<RsaPrivateKey>,
+    /// How long to wait for the client before returning
+    /// [`TestServerError::Timeout`].
+    pub timeout: Duration,
+}
+
+impl TestServer {
+    /// Start listening on a random port on 127.0.0.1. Compression and
+    /// encryption are disabled by default.
+    pub async fn bind() -> Result<Self, TestServerError> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        Ok(Self {
+            listener,
+            address,
+            compression_threshold: None,
+            private_key: None,
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Compress packets that are at least this many bytes long, like the
+    /// `network-compression-threshold` option in `server.properties`.
+    #[must_use]
+    pub fn with_compression_threshold(mut self, threshold: i32) -> Self {
+        self.compression_threshold = Some(threshold);
+        self
+    }
+
+    /// Encrypt the connection. The client isn't authenticated with Mojang,
+    /// so offline accounts work.
+    #[must_use]
+    pub fn with_encryption(mut self) -> Self {
+        // vanilla uses 1024-bit keys too
+        let private_key = RsaPrivateKey::new(&mut OsRng, 1024)
+            .expect("Generating a 1024-bit RSA key should never fail");
+        self.private_key = Some(private_key);
+        self
+    }
+
+    /// The address clients should connect to.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Wait for a client to connect and log in, and return the player after
+    /// they've been sent the login packet.
+    pub async fn accept(&self) -> Result<TestPlayer, TestServerError> {
+        let (stream, _) = with_timeout(self.timeout, self.listener.accept()).await??;
+        stream.set_nodelay(true)?;
+        let mut conn: Connection<ServerboundHandshakePacket, ClientboundHandshakePacket> =
+            Connection::wrap(stream);
+
+        let ServerboundHandshakePacket::ClientIntention(intention) =
+            with_timeout(self.timeout, conn.read()).await??;
+        if intention.intention != ConnectionProtocol::Login {
+            return Err(TestServerError::WrongIntention(intention.intention));
+        }
+        let mut conn = conn.login();
+
+        let hello = match with_timeout(self.timeout, conn.read()).await?? {
+            ServerboundLoginPacket::Hello(p) => p,
+            packet => {
+                return Err(TestServerError::UnexpectedPacket {
+                    expected: "Hello",
+                    packet: format!("{packet:?}"),
+                })
+            }
+        };
+        debug!("{} is logging in", hello.name);
+
+        if let Some(private_key) = &self.private_key {
+            let public_key = RsaPublicKey::from(private_key)
+                .to_public_key_der()
+                .map_err(|e| TestServerError::Encryption(e.to_string()))?;
+            let mut nonce = vec![0; 4];
+            OsRng.fill_bytes(&mut nonce);
+            conn.write(
+                ClientboundHelloPacket {
+                    server_id: String::new(),
+                    public_key: public_key.as_bytes().to_vec(),
+                    nonce: nonce.clone(),
+                }
+                .get(),
+            )
+            .await?;
+
+            let key = match with_timeout(self.timeout, conn.read()).await?? {
+                ServerboundLoginPacket::Key(p) => p,
+                packet => {
+                    return Err(TestServerError::UnexpectedPacket {
+                        expected: "Key",
+                        packet: format!("{packet:?}"),
+                    })
+                }
+            };
+            let decrypt = |data: &[u8]| {
+                private_key
+                    .decrypt(Pkcs1v15Encrypt, data)
+                    .map_err(|e| TestServerError::Encryption(e.to_string()))
+            };
+            if decrypt(&key.encrypted_challenge)? != nonce {
+                return Err(TestServerError::WrongChallenge);
+            }
+            let secret_key: [u8; 16] = decrypt(&key.key_bytes)?
+                .try_into()
+                .map_err(|key| TestServerError::Encryption(format!("Shared secret was {key:?}")))?;
+            conn.set_encryption_key(secret_key);
+        }
+
+        if let Some(threshold) = self.compression_threshold {
+            conn.write(
+                ClientboundLoginCompressionPacket {
+                    compression_threshold: threshold,
+                }
+                .get(),
+            )
+            .await?;
+            conn.set_compression_threshold(threshold);
+        }
+
+        let uuid = hello
+            .profile_id
+            .unwrap_or_else(|| offline_uuid(&hello.name));
+        let profile = GameProfile::new(uuid, hello.name);
+        conn.write(
+            ClientboundGameProfilePacket {
+                game_profile: profile.clone(),
+            }
+            .get(),
+        )
+        .await?;
+
+        let mut player = TestPlayer::new(conn.game(), profile, self.timeout);
+        player
+            .send(registry::login_packet(player.entity_id))
+            .await?;
+        Ok(player)
+    }
+}
+
+/// A UUID that's always the same for the given player name, for clients
+/// that didn't say what their UUID is. This is the same as vanilla's
+/// `UUID.nameUUIDFromBytes`, which is a version 3 UUID without a namespace.
+pub fn offline_uuid(name: &str) -> Uuid {
+    let hash = Md5::digest(format!("OfflinePlayer:{name}").as_bytes());
+    uuid::Builder::from_md5_bytes(hash.into()).into_uuid()
+}
+
+/// Give up with [`TestServerError::Timeout`] if the future takes longer than
+/// `timeout`.
+async fn with_timeout<T>(
+    timeout: Duration,
+    future: impl Future<Output = T>,
+) -> Result<T, TestServerError> {
+    tokio::time::timeout(timeout, future)
+        .await
+        .map_err(|_| TestServerError::Timeout)
+}
@@ -0,0 +1,208 @@
+use crate::{registry::SECTION_COUNT, with_timeout, TestServerError};
+use azalea_auth::game_profile::GameProfile;
+use azalea_buf::McBufWritable;
+use azalea_core::{BitSet, ChunkPos, Vec3};
+use azalea_nbt::{Nbt, NbtCompound};
+use azalea_protocol::{
+    connect::Connection,
+    packets::game::{
+        clientbound_add_entity_packet::ClientboundAddEntityPacket,
+        clientbound_level_chunk_with_light_packet::{
+            ClientboundLevelChunkPacketData, ClientboundLevelChunkWithLightPacket,
+        },
+        clientbound_light_update_packet::ClientboundLightUpdatePacketData,
+        clientbound_player_position_packet::{ClientboundPlayerPositionPacket, RelativeMovements},
+        ClientboundGamePacket, ServerboundGamePacket,
+    },
+    read::ReadPacketError,
+};
+use azalea_registry::EntityKind;
+use azalea_world::Chunk;
+use std::time::Duration;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+/// A client that has logged into a [`TestServer`](crate::TestServer).
+///
+/// Use this to send the client whatever packets the test needs, and to check
+/// what the client sent back.
+pub struct TestPlayer {
+    pub profile: GameProfile,
+    /// The id of the player's entity, which was sent in the login packet.
+    pub entity_id: u32,
+    /// How long [`Self::next_packet`] and [`Self::wait_for`] wait before
+    /// returning [`TestServerError::Timeout`].
+    pub timeout: Duration,
+    conn: Connection<ServerboundGamePacket, ClientboundGamePacket>,
+    /// Every packet the client has sent us so far.
+    received: Vec<ServerboundGamePacket>,
+    next_teleport_id: u32,
+}
+
+impl TestPlayer {
+    pub(crate) fn new(
+        conn: Connection<ServerboundGamePacket, ClientboundGamePacket>,
+        profile: GameProfile,
+        timeout: Duration,
+    ) -> Self {
+        Self {
+            profile,
+            entity_id: 1,
+            timeout,
+            conn,
+            received: Vec::new(),
+            next_teleport_id: 1,
+        }
+    }
+
+    /// Send any packet to the client.
+    pub async fn send(&mut self, packet: ClientboundGamePacket) -> Result<(), TestServerError> {
+        self.conn.write(packet).await?;
+        Ok(())
+    }
+
+    /// Send a chunk to the client. The chunk should have the same number of
+    /// sections as the overworld (24).
+    pub async fn send_chunk(
+        &mut self,
+        pos: ChunkPos,
+        chunk: &Chunk,
+    ) -> Result<(), TestServerError> {
+        let mut data = Vec::new();
+        chunk.write_into(&mut data)?;
+        // light sections go one past the top and bottom of the world
+        let light_sections = SECTION_COUNT + 2;
+        self.send(
+            ClientboundLevelChunkWithLightPacket {
+                x: pos.x,
+                z: pos.z,
+                chunk_data: ClientboundLevelChunkPacketData {
+                    heightmaps: Nbt::Compound(NbtCompound::default()),
+                    data: data.into(),
+                    block_entities: Vec::new(),
+                },
+                light_data: ClientboundLightUpdatePacketData {
+                    trust_edges: true,
+                    sky_y_mask: BitSet::new(light_sections),
+                    block_y_mask: BitSet::new(light_sections),
+                    empty_sky_y_mask: BitSet::new(light_sections),
+                    empty_block_y_mask: BitSet::new(light_sections),
+                    sky_updates: Vec::new(),
+                    block_updates: Vec::new(),
+                },
+            }
+            .get(),
+        )
+        .await
+    }
+
+    /// Spawn an entity with default metadata for the client, and return its
+    /// UUID.
+    pub async fn add_entity(
+        &mut self,
+        id: u32,
+        kind: EntityKind,
+        position: Vec3,
+    ) -> Result<Uuid, TestServerError> {
+        let uuid = Uuid::from_u128(id as u128);
+        self.send(
+            ClientboundAddEntityPacket {
+                id,
+                uuid,
+                entity_type: kind,
+                position,
+                x_rot: 0,
+                y_rot: 0,
+                y_head_rot: 0,
+                data: 0,
+                x_vel: 0,
+                y_vel: 0,
+                z_vel: 0,
+            }
+            .get(),
+        )
+        .await?;
+        Ok(uuid)
+    }
+
+    /// Move the client to an absolute position, and return the teleport id
+    /// that the client should accept.
+    pub async fn teleport(&mut self, position: Vec3) -> Result<u32, TestServerError> {
+        let id = self.next_teleport_id;
+        self.next_teleport_id += 1;
+        self.send(
+            ClientboundPlayerPositionPacket {
+                x: position.x,
+                y: position.y,
+                z: position.z,
+                y_rot: 0.,
+                x_rot: 0.,
+                relative_arguments: RelativeMovements {
+                    x: false,
+                    y: false,
+                    z: false,
+                    y_rot: false,
+                    x_rot: false,
+                },
+                id,
+            }
+            .get(),
+        )
+        .await?;
+        Ok(id)
+    }
+
+    /// Read the next packet the client sends.
+    pub async fn next_packet(&mut self) -> Result<ServerboundGamePacket, TestServerError> {
+        let packet = self.read(self.timeout).await?;
+        self.received.push(packet.clone());
+        Ok(packet)
+    }
+
+    /// Read packets until `f` returns `Some`, and return what it returned.
+    /// Packets that are skipped are still kept in [`Self::received`].
+    ///
+    /// ```rust,no_run
+    /// # async fn example(player: &mut azalea_testserver::TestPlayer) {
+    /// use azalea_protocol::packets::game::ServerboundGamePacket;
+    ///
+    /// let message = player
+    ///     .wait_for(|p| match p {
+    ///         ServerboundGamePacket::Chat(p) => Some(p.message.clone()),
+    ///         _ => None,
+    ///     })
+    ///     .await
+    ///     .unwrap();
+    /// # }
+    /// ```
+    pub async fn wait_for<T>(
+        &mut self,
+        mut f: impl FnMut(&ServerboundGamePacket) -> Option<T>,
+    ) -> Result<T, TestServerError> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let packet = self.read(timeout).await?;
+            let result = f(&packet);
+            self.received.push(packet);
+            if let Some(result) = result {
+                return Ok(result);
+            }
+        }
+    }
+
+    async fn read(&mut self, timeout: Duration) -> Result<ServerboundGamePacket, TestServerError> {
+        match with_timeout(timeout, self.conn.read()).await? {
+            Ok(packet) => Ok(packet),
+            Err(e) if matches!(*e, ReadPacketError::ConnectionClosed) => {
+                Err(TestServerError::Disconnected)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Every packet the client has sent that we've read so far, oldest first.
+    pub fn received(&self) -> &[ServerboundGamePacket] {
+        &self.received
+    }
+}
@@ -0,0 +1,69 @@
+//! The smallest login packet that Azalea will accept.
+
+use azalea_core::{GameType, OptionalGameType, ResourceLocation};
+use azalea_nbt::{Nbt, NbtCompound};
+use azalea_protocol::packets::game::{
+    clientbound_login_packet::{
+        registry::{DimensionTypeElement, RegistryHolder, RegistryRoot, RegistryType, TypeValue},
+        ClientboundLoginPacket,
+    },
+    ClientboundGamePacket,
+};
+
+/// The height of the overworld that test players are put in.
+pub const WORLD_HEIGHT: u32 = 384;
+/// The lowest y coordinate in the overworld that test players are put in.
+pub const WORLD_MIN_Y: i32 = -64;
+
+/// The number of chunk sections in every chunk of the test world.
+pub const SECTION_COUNT: usize = (WORLD_HEIGHT / 16) as usize;
+
+/// A registry with only the overworld dimension type. Everything else is
+/// empty since Azalea doesn't need it to join.
+fn registry_holder() -> RegistryHolder {
+    let empty = || Nbt::Compound(NbtCompound::default());
+    RegistryHolder {
+        root: RegistryRoot {
+            trim_material: empty(),
+            chat_type: empty(),
+            dimension_type: RegistryType {
+                kind: ResourceLocation::new("minecraft:dimension_type"),
+                value: vec![TypeValue {
+                    id: 0,
+                    name: ResourceLocation::new("minecraft:overworld"),
+                    element: DimensionTypeElement {
+                        height: WORLD_HEIGHT,
+                        min_y: WORLD_MIN_Y,
+                    },
+                }],
+            },
+            world_type: empty(),
+            trim_pattern: empty(),
+            damage_type: empty(),
+        },
+    }
+}
+
+pub fn login_packet(player_id: u32) -> ClientboundGamePacket {
+    let overworld = ResourceLocation::new("minecraft:overworld");
+    ClientboundLoginPacket {
+        player_id,
+        hardcore: false,
+        game_type: GameType::Survival,
+        previous_game_type: OptionalGameType(None),
+        levels: vec![overworld.clone()],
+        registry_holder: registry_holder(),
+        dimension_type: overworld.clone(),
+        dimension: overworld,
+        seed: 0,
+        max_players: 1,
+        chunk_radius: 8,
+        simulation_distance: 8,
+        reduced_debug_info: false,
+        show_death_screen: true,
+        is_debug: false,
+        is_flat: true,
+        last_death_location: None,
+    }
+    .get()
+}
@@ -0,0 +1,169 @@
+use azalea_client::{Account, Client};
+use azalea_core::{BlockPos, ChunkBlockPos, ChunkPos, Vec3};
+use azalea_protocol::packets::game::{
+    clientbound_bundle_packet::ClientboundBundlePacket,
+    clientbound_set_entity_data_packet::ClientboundSetEntityDataPacket, ServerboundGamePacket,
+};
+use azalea_registry::EntityKind;
+use azalea_testserver::{offline_uuid, TestPlayer, TestServer};
+use azalea_world::{
+    entity::{metadata::AirSupply, EntityDataItem, EntityDataValue, MinecraftEntityId},
+    Chunk,
+};
+use std::time::Duration;
+
+/// Connect a client to the server and wait until it's received the login
+/// packet.
+async fn join(server: TestServer) -> (Client, TestPlayer) {
+    let address = server.address();
+    let (client, player) = tokio::join!(
+        Client::join(&Account::offline("bot"), address),
+        server.accept()
+    );
+    let (client, _rx) = client.unwrap();
+    let mut player = player.unwrap();
+    // the client sends its settings after it gets the login packet
+    player
+        .wait_for(|p| matches!(p, ServerboundGamePacket::ClientInformation(_)).then_some(()))
+        .await
+        .unwrap();
+    (client, player)
+}
+
+async fn check_chat(client: &Client, player: &mut TestPlayer) {
+    client.chat("hello");
+    let message = player
+        .wait_for(|p| match p {
+            ServerboundGamePacket::Chat(p) => Some(p.message.clone()),
+            _ => None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(message, "hello");
+}
+
+#[tokio::test]
+async fn test_login() {
+    let server = TestServer::bind().await.unwrap();
+    let (client, mut player) = join(server).await;
+    assert_eq!(player.profile.name, "bot");
+
+    let id = player.teleport(Vec3::new(0.5, 70., 0.5)).await.unwrap();
+    let accepted = player
+        .wait_for(|p| match p {
+            ServerboundGamePacket::AcceptTeleportation(p) => Some(p.id),
+            _ => None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(accepted, id);
+
+    check_chat(&client, &mut player).await;
+}
+
+#[tokio::test]
+async fn test_login_compressed_and_encrypted() {
+    let server = TestServer::bind()
+        .await
+        .unwrap()
+        .with_compression_threshold(256)
+        .with_encryption();
+    let (client, mut player) = join(server).await;
+
+    check_chat(&client, &mut player).await;
+}
+
+#[tokio::test]
+async fn test_send_chunk() {
+    let server = TestServer::bind().await.unwrap();
+    let (client, mut player) = join(server).await;
+
+    let mut chunk = Chunk::default();
+    chunk.set(
+        &ChunkBlockPos::from(&BlockPos::new(1, 2, 3)),
+        azalea_registry::Block::Stone.into(),
+        -64,
+    );
+    player
+        .send_chunk(ChunkPos::new(0, 0), &chunk)
+        .await
+        .unwrap();
+
+    let world = client.world();
+    let mut state = None;
+    for _ in 0..100 {
+        state = world.read().chunks.get_block_state(&BlockPos::new(1, 2, 3));
+        if state.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(state, Some(azalea_registry::Block::Stone.into()));
+}
+
+#[tokio::test]
+async fn test_bundled_entity_metadata() {
+    let server = TestServer::bind().await.unwrap();
+    let (client, mut player) = join(server).await;
+
+    let id = 100;
+    // whether the entity exists, and its air supply if it has one
+    let find_entity = || {
+        let mut ecs = client.ecs.lock();
+        let mut query = ecs.query::<(&MinecraftEntityId, Option<&AirSupply>)>();
+        query
+            .iter(&ecs)
+            .find(|(entity_id, _)| entity_id.0 == id)
+            .map(|(_, air_supply)| air_supply.map(|a| a.0))
+    };
+
+    // the metadata has to be applied in the same update as the entity is
+    // spawned, so nothing happens until the bundle is closed
+    player.send(ClientboundBundlePacket {}.get()).await.unwrap();
+    player
+        .add_entity(id, EntityKind::Zombie, Vec3::new(0.5, 70., 0.5))
+        .await
+        .unwrap();
+    for _ in 0..10 {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(
+            find_entity(),
+            None,
+            "the entity was added before its metadata"
+        );
+    }
+    player
+        .send(
+            ClientboundSetEntityDataPacket {
+                id,
+                packed_items: vec![EntityDataItem {
+                    index: 1,
+                    value: EntityDataValue::Int(42),
+                }]
+                .into(),
+            }
+            .get(),
+        )
+        .await
+        .unwrap();
+    player.send(ClientboundBundlePacket {}.get()).await.unwrap();
+
+    let mut entity = None;
+    for _ in 0..100 {
+        entity = find_entity();
+        if entity.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(entity, Some(Some(42)));
+}
+
+#[test]
+fn test_offline_uuid() {
+    // the uuid a vanilla offline-mode server gives Notch
+    assert_eq!(
+        offline_uuid("Notch").to_string(),
+        "b50ad385-829d-3141-a216-7e7d7539ba7f"
+    );
+}
@@ -0,0 +1,66 @@
+use azalea_client::{Account, Client};
+use azalea_protocol::{packets::game::ServerboundGamePacket, proxy::Proxy};
+use azalea_testserver::TestServer;
+use std::net::SocketAddr;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+/// A stand-in SOCKS5 proxy without authentication that connects whoever
+/// connects to it to `target`, no matter what address they asked for.
+async fn socks5_proxy(target: SocketAddr) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut client, _) = listener.accept().await.unwrap();
+        let mut greeting = [0; 2];
+        client.read_exact(&mut greeting).await.unwrap();
+        let mut methods = vec![0; greeting[1] as usize];
+        client.read_exact(&mut methods).await.unwrap();
+        client.write_all(&[5, 0]).await.unwrap();
+
+        let mut request = [0; 4];
+        client.read_exact(&mut request).await.unwrap();
+        let address_len = match request[3] {
+            1 => 4,
+            3 => client.read_u8().await.unwrap() as usize,
+            _ => 16,
+        };
+        let mut requested_address = vec![0; address_len + 2];
+        client.read_exact(&mut requested_address).await.unwrap();
+
+        let mut server = TcpStream::connect(target).await.unwrap();
+        client
+            .write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0])
+            .await
+            .unwrap();
+        tokio::io::copy_bidirectional(&mut client, &mut server)
+            .await
+            .ok();
+    });
+    address
+}
+
+#[tokio::test]
+async fn test_login_through_proxy() {
+    let server = TestServer::bind().await.unwrap();
+    let address = server.address();
+    let proxy_address = socks5_proxy(address).await;
+    let account = Account::offline("bot").with_proxy(Proxy::socks5(proxy_address));
+
+    let (client, player) = tokio::join!(Client::join(&account, address), server.accept());
+    let (client, _rx) = client.unwrap();
+    let mut player = player.unwrap();
+    assert_eq!(player.profile.name, "bot");
+
+    client.chat("hello");
+    let message = player
+        .wait_for(|p| match p {
+            ServerboundGamePacket::Chat(p) => Some(p.message.clone()),
+            _ => None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(message, "hello");
+}
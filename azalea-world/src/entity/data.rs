@@ -23,6 +23,12 @@ pub struct EntityDataItem {
     pub value: EntityDataValue,
 }
 
+impl From<Vec<EntityDataItem>> for EntityMetadataItems {
+    fn from(items: Vec<EntityDataItem>) -> Self {
+        EntityMetadataItems(items)
+    }
+}
+
 impl McBufReadable for EntityMetadataItems {
     fn read_from(buf: &mut Cursor<&[u8]>) -> Result<Self, BufReadError> {
         let mut metadata = Vec::new();
@@ -128,6 +128,11 @@ impl Debug for EntityUuid {
 /// automatically.
 #[derive(Component, Clone, Copy, Debug, Default, PartialEq, Deref, DerefMut)]
 pub struct Position(Vec3);
+impl Position {
+    pub fn new(pos: Vec3) -> Self {
+        Self(pos)
+    }
+}
 impl From<Position> for ChunkPos {
     fn from(value: Position) -> Self {
         ChunkPos::from(&value.0)
@@ -187,7 +192,7 @@ pub struct Jumping(bool);
 
 /// The physics data relating to the entity, such as position, velocity, and
 /// bounding box.
-#[derive(Debug, Component)]
+#[derive(Debug, Clone, Component)]
 pub struct Physics {
     pub delta: Vec3,
 
@@ -295,6 +295,10 @@ pub fn update_entity_by_id_index(
     for (entity, id, world_name, local) in query.iter_mut() {
         let world_lock = world_container.get(world_name).unwrap();
         let mut world = world_lock.write();
+        if world.entity_by_id.get(id) == Some(&entity) {
+            // it was already indexed when it was spawned
+            continue;
+        }
         if local.is_none() {
             if let Some(old_entity) = world.entity_by_id.get(id) {
                 debug!(
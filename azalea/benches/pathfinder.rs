@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use azalea::pathfinder::{
+    coordinator::ReservationTable, danger::DangerCtx, find_path, world::CachedWorld, BlockPosGoal,
+    Node, SearchLimits, SearchOpts, VerticalVel,
+};
+use azalea_core::{BlockPos, ChunkPos};
+use azalea_world::{Chunk, ChunkStorage, Instance, PartialInstance};
+use criterion::{criterion_group, criterion_main, Criterion};
+use parking_lot::RwLock;
+
+/// How many chunks out from the origin the fixture worlds go in each
+/// direction.
+const CHUNK_RADIUS: i32 = 3;
+const FLOOR_Y: i32 = 69;
+
+struct Fixture {
+    // the instance only keeps weak references to its chunks, so this has to be
+    // kept around for them to stay loaded
+    _partial_world: PartialInstance,
+    world: Arc<RwLock<Instance>>,
+}
+
+/// Make a flat stone world, and then add whatever extra blocks `is_wall`
+/// returns true for (two blocks tall, so we can't jump over them).
+fn make_fixture(is_wall: impl Fn(i32, i32) -> bool) -> Fixture {
+    let mut partial_world = PartialInstance::default();
+    let mut chunk_storage = ChunkStorage::default();
+    for chunk_x in -CHUNK_RADIUS..=CHUNK_RADIUS {
+        for chunk_z in -CHUNK_RADIUS..=CHUNK_RADIUS {
+            partial_world.chunks.set(
+                &ChunkPos::new(chunk_x, chunk_z),
+                Some(Chunk::default()),
+                &mut chunk_storage,
+            );
+        }
+    }
+    let size = (CHUNK_RADIUS + 1) * 16;
+    for x in -size..size {
+        for z in -size..size {
+            let mut set = |y: i32| {
+                partial_world.chunks.set_block_state(
+                    &BlockPos::new(x, y, z),
+                    azalea_registry::Block::Stone.into(),
+                    &mut chunk_storage,
+                );
+            };
+            set(FLOOR_Y);
+            if is_wall(x, z) {
+                set(FLOOR_Y + 1);
+                set(FLOOR_Y + 2);
+            }
+        }
+    }
+    Fixture {
+        _partial_world: partial_world,
+        world: Arc::new(RwLock::new(chunk_storage.into())),
+    }
+}
+
+fn bench_search(c: &mut Criterion, name: &str, fixture: &Fixture) {
+    let start = Node {
+        pos: BlockPos::new(-40, FLOOR_Y + 1, -44),
+        vertical_vel: VerticalVel::None,
+    };
+    let goal = BlockPosGoal::from(BlockPos::new(40, FLOOR_Y + 1, 44));
+    let opts = SearchOpts {
+        danger: DangerCtx {
+            health: 20.,
+            ..Default::default()
+        },
+        unknown_chunk_cost: 2.,
+        limits: SearchLimits::default(),
+        reserved_nodes: ReservationTable::default(),
+    };
+
+    c.bench_function(name, |b| {
+        b.iter(|| {
+            // a new cache for every search, like the pathfinder does
+            let world = CachedWorld::new(fixture.world.clone());
+            find_path(start, &goal, &world, &opts).unwrap()
+        })
+    });
+}
+
+fn bench(c: &mut Criterion) {
+    let flat = make_fixture(|_, _| false);
+    bench_search(c, "Pathfind across flat world", &flat);
+
+    // a grid of pillars with a gap every 4 blocks
+    let pillars = make_fixture(|x, z| x.rem_euclid(4) == 0 && z.rem_euclid(4) == 0);
+    bench_search(c, "Pathfind through pillars", &pillars);
+
+    // long walls with a gap at alternating ends, so the path has to zigzag
+    let zigzag = make_fixture(|x, z| {
+        let row = z.div_euclid(8);
+        z.rem_euclid(8) == 0
+            && if row.rem_euclid(2) == 0 {
+                x < 56
+            } else {
+                x > -56
+            }
+    });
+    bench_search(c, "Pathfind through zigzag walls", &zigzag);
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -17,6 +17,7 @@ use bot::DefaultBotPlugins;
 use ecs::component::Component;
 use futures::Future;
 use protocol::{
+    proxy::Proxy,
     resolver::{self, ResolverError},
     ServerAddress,
 };
@@ -65,6 +66,8 @@ where
     /// The function that's called every time a bot receives an [`Event`].
     handler: Option<HandleFn<Fut, S>>,
     state: S,
+    /// The proxy that's used if the account doesn't have its own.
+    proxy: Option<Proxy>,
 }
 impl<S, Fut> ClientBuilder<S, Fut>
 where
@@ -81,6 +84,7 @@ where
 
             handler: None,
             state: S::default(),
+            proxy: None,
         }
         .add_plugins(DefaultBotPlugins)
     }
@@ -112,6 +116,13 @@ where
         self.state = state;
         self
     }
+    /// Connect through the given proxy, unless the account already has a
+    /// [`proxy`](Account::proxy) set.
+    #[must_use]
+    pub fn set_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
     /// Add a plugin to the client.
     #[must_use]
     pub fn add_plugin<T: Plugin>(mut self, plugin: T) -> Self {
@@ -134,11 +145,15 @@ where
     /// [`ServerAddress`]: azalea_protocol::ServerAddress
     pub async fn start(
         self,
-        account: Account,
+        mut account: Account,
         address: impl TryInto<ServerAddress>,
     ) -> Result<(), StartError> {
+        if account.proxy.is_none() {
+            account.proxy = self.proxy.clone();
+        }
         let address: ServerAddress = address.try_into().map_err(|_| JoinError::InvalidAddress)?;
-        let resolved_address = resolver::resolve_address(&address).await?;
+        let resolved_address =
+            resolver::resolve_address_unless_proxied(&address, account.proxy.as_ref()).await?;
 
         // An event that causes the schedule to run. This is only used internally.
         let (run_schedule_sender, run_schedule_receiver) = mpsc::unbounded_channel();
@@ -149,7 +164,7 @@ where
             ecs_lock,
             &account,
             &address,
-            &resolved_address,
+            resolved_address.as_ref(),
             run_schedule_sender,
         )
         .await?;
@@ -0,0 +1,725 @@
+//! Coordinate the paths of the bots in a swarm so they don't get in each
+//! other's way.
+//!
+//! Every coordinated bot reserves the nodes on its path for the ticks it
+//! expects to be there. Paths are planned one bot at a time with a search over
+//! both positions and ticks (cooperative A*), so later bots wait or go around
+//! instead of being in a node at the same time as a bot that reserved it.
+//! While walking, a bot waits before stepping into a node that a bot with a
+//! higher priority is currently going through.
+
+use super::{
+    edges, find_path, moves, mtdstarlite::Edge, path_found_listener, search_opts,
+    tick_execute_path, world::CachedWorld, BlockPosGoal, Goal, GotoProgressEvent, MobsData,
+    MobsFilter, Node, PathFoundEvent, SearchOpts, SearchOptsData, VerticalVel,
+};
+use crate::app::{App, CoreSchedule, IntoSystemAppConfig, Plugin};
+use crate::ecs::{
+    component::Component,
+    entity::Entity,
+    event::{EventReader, EventWriter},
+    query::With,
+    schedule::IntoSystemConfig,
+    system::{Commands, Query, Res, ResMut, Resource},
+};
+use azalea_core::BlockPos;
+use azalea_world::{
+    entity::{Position, WorldName},
+    InstanceContainer,
+};
+use bevy_tasks::{AsyncComputeTaskPool, Task};
+use futures_lite::future;
+use log::{debug, error, warn};
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+    ops::Range,
+    sync::Arc,
+    time::Instant,
+};
+
+/// Roughly how many ticks it takes for a bot to go from one node to the next.
+const TICKS_PER_NODE: u64 = 5;
+
+/// The cost of standing still for [`TICKS_PER_NODE`] ticks in a cooperative
+/// search. This is the same as walking one block, since it takes as long.
+const WAIT_COST: f32 = 1.;
+
+/// How far away from the goal (horizontally) the cells that bots are sent to
+/// can be.
+const GOAL_SPREAD: i32 = 6;
+
+#[derive(Clone, Default)]
+pub struct PathCoordinatorPlugin;
+impl Plugin for PathCoordinatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PathReservations>()
+            .add_event::<GotoAllEvent>()
+            .add_system(
+                tick_reservations
+                    .in_schedule(CoreSchedule::FixedUpdate)
+                    .before(tick_execute_path),
+            )
+            .add_system(goto_all_listener)
+            .add_system(handle_coordinated_tasks.before(path_found_listener))
+            .add_system(reserve_found_paths.after(handle_coordinated_tasks))
+            .add_system(release_finished_paths);
+    }
+}
+
+/// A marker component for bots whose paths are coordinated with the rest of
+/// the swarm. This is added by [`GotoAllEvent`].
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct CoordinatedPathfinding;
+
+/// Send all of these bots to the same position. Each bot gets its own cell
+/// near the goal so they don't all try to stand in the same block.
+///
+/// This is usually sent with
+/// [`Swarm::goto_all`](crate::swarm::Swarm::goto_all).
+#[derive(Debug, Clone)]
+pub struct GotoAllEvent {
+    pub entities: Vec<Entity>,
+    pub goal: BlockPos,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Reservation {
+    entity: Entity,
+    /// The first tick that the entity is expected to be at the node.
+    start: u64,
+    /// The tick after the last one the entity is expected to be at the node.
+    end: u64,
+}
+
+impl Reservation {
+    /// Whether this reservation should go before the other one if they're for
+    /// the same node.
+    fn has_priority_over(&self, other: &Reservation) -> bool {
+        (self.start, self.entity.to_bits()) < (other.start, other.entity.to_bits())
+    }
+}
+
+/// The tick that the node at this index of a path starting at tick 0 is
+/// reserved from.
+pub(super) fn node_start_tick(index: usize) -> u64 {
+    index as u64 * TICKS_PER_NODE
+}
+
+/// The ticks that each node of a path starting at tick 0 is reserved for. The
+/// last node is reserved forever, since that's where the entity will be
+/// standing.
+fn path_reservations(
+    path: impl IntoIterator<Item = BlockPos>,
+) -> impl Iterator<Item = (BlockPos, Range<u64>)> {
+    let mut path = path.into_iter().peekable();
+    let mut i = 0;
+    std::iter::from_fn(move || {
+        let pos = path.next()?;
+        let start = node_start_tick(i);
+        i += 1;
+        let end = if path.peek().is_none() {
+            u64::MAX
+        } else {
+            // give some leeway in case the entity is late
+            start + TICKS_PER_NODE * 2
+        };
+        Some((pos, start..end))
+    })
+}
+
+/// Which nodes coordinated bots are planning to be at, and when.
+#[derive(Resource, Default)]
+pub struct PathReservations {
+    /// How many ticks have passed since the reservations were created.
+    tick: u64,
+    nodes: HashMap<BlockPos, Vec<Reservation>>,
+}
+
+impl PathReservations {
+    /// Replace this entity's reservations with the nodes of a new path that
+    /// starts now. The last node is reserved until the entity gets there and
+    /// its reservations are released.
+    pub fn reserve_path(&mut self, entity: Entity, path: impl IntoIterator<Item = BlockPos>) {
+        self.release(entity);
+        for (pos, ticks) in path_reservations(path) {
+            self.nodes.entry(pos).or_default().push(Reservation {
+                entity,
+                start: self.tick + ticks.start,
+                end: self.tick.saturating_add(ticks.end),
+            });
+        }
+    }
+
+    /// Remove all of this entity's reservations.
+    pub fn release(&mut self, entity: Entity) {
+        for reservations in self.nodes.values_mut() {
+            reservations.retain(|r| r.entity != entity);
+        }
+        self.nodes
+            .retain(|_, reservations| !reservations.is_empty());
+    }
+
+    /// The reservations of every entity other than this one, relative to the
+    /// current tick.
+    pub fn reserved_by_others(&self, entity: Entity) -> ReservationTable {
+        let mut table = ReservationTable::default();
+        for (pos, reservations) in &self.nodes {
+            for r in reservations.iter().filter(|r| r.entity != entity) {
+                let start = r.start.saturating_sub(self.tick);
+                let end = if r.end == u64::MAX {
+                    u64::MAX
+                } else {
+                    r.end.saturating_sub(self.tick)
+                };
+                table.reserve(*pos, start..end);
+            }
+        }
+        table
+    }
+
+    /// Whether the entity should wait before going into the node at `pos`,
+    /// because another entity with a higher priority is supposed to be there
+    /// right now.
+    pub fn should_wait(&self, pos: &BlockPos, entity: Entity) -> bool {
+        let Some(reservations) = self.nodes.get(pos) else {
+            return false;
+        };
+        let ours = reservations.iter().find(|r| r.entity == entity);
+        reservations.iter().any(|r| {
+            r.entity != entity
+                && r.start <= self.tick
+                && self.tick < r.end
+                && ours.map_or(true, |ours| r.has_priority_over(ours))
+        })
+    }
+
+    fn tick(&mut self) {
+        self.tick += 1;
+        let tick = self.tick;
+        for reservations in self.nodes.values_mut() {
+            reservations.retain(|r| r.end > tick);
+        }
+        self.nodes
+            .retain(|_, reservations| !reservations.is_empty());
+    }
+}
+
+/// The ticks that other bots are going to be in each node, counted from when
+/// a search starts. A search with reservations treats a node as blocked while
+/// it's reserved.
+#[derive(Clone, Debug, Default)]
+pub struct ReservationTable {
+    nodes: HashMap<BlockPos, Vec<Range<u64>>>,
+    /// The tick after which nothing changes anymore, i.e. the latest start or
+    /// (finite) end of a reservation.
+    horizon: u64,
+}
+
+impl ReservationTable {
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Reserve the node at `pos` for these ticks.
+    pub fn reserve(&mut self, pos: BlockPos, ticks: Range<u64>) {
+        self.horizon = self.horizon.max(ticks.start);
+        if ticks.end != u64::MAX {
+            self.horizon = self.horizon.max(ticks.end);
+        }
+        self.nodes.entry(pos).or_default().push(ticks);
+    }
+
+    /// Reserve the nodes of a path that starts at tick 0.
+    pub fn reserve_path(&mut self, path: impl IntoIterator<Item = BlockPos>) {
+        for (pos, ticks) in path_reservations(path) {
+            self.reserve(pos, ticks);
+        }
+    }
+
+    /// Add all of the other table's reservations to this one.
+    pub fn extend(&mut self, other: &ReservationTable) {
+        for (pos, reservations) in &other.nodes {
+            for ticks in reservations {
+                self.reserve(*pos, ticks.clone());
+            }
+        }
+    }
+
+    /// Whether the node at `pos` is reserved at any point during these ticks.
+    pub fn is_reserved(&self, pos: &BlockPos, ticks: Range<u64>) -> bool {
+        self.nodes.get(pos).map_or(false, |reservations| {
+            reservations
+                .iter()
+                .any(|r| r.start < ticks.end && ticks.start < r.end)
+        })
+    }
+}
+
+/// A node in a cooperative search, which is a position at a certain tick.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+struct TimedNode {
+    node: Node,
+    /// The tick that we get to the node, clamped to the reservations'
+    /// horizon since nothing changes after that.
+    tick: u64,
+}
+
+struct OpenNode {
+    f: f32,
+    timed: TimedNode,
+}
+impl PartialEq for OpenNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.f.total_cmp(&other.f) == Ordering::Equal
+    }
+}
+impl Eq for OpenNode {}
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // reversed so the BinaryHeap pops the lowest cost first
+        other.f.total_cmp(&self.f)
+    }
+}
+
+/// Search for a path over positions and ticks, waiting or going around
+/// instead of going through a node while it's in
+/// [`SearchOpts::reserved_nodes`]. The goal only counts as reached if nobody
+/// has reserved it for after we get there. Returns the path (which repeats a
+/// node wherever we wait) and whether it stops early because a search limit
+/// was hit.
+pub(super) fn find_cooperative_path(
+    start: Node,
+    goal: &dyn Goal,
+    world: &CachedWorld,
+    opts: &SearchOpts,
+) -> Option<(Vec<Node>, bool)> {
+    let reserved = &opts.reserved_nodes;
+    let start_time = Instant::now();
+
+    let start = TimedNode {
+        node: start,
+        tick: 0,
+    };
+    let mut open = BinaryHeap::new();
+    // the cost to get to each node and which node we came from
+    let mut scores: HashMap<TimedNode, (f32, Option<TimedNode>)> = HashMap::new();
+    scores.insert(start, (0., None));
+    open.push(OpenNode {
+        f: goal.heuristic(&start.node),
+        timed: start,
+    });
+
+    let mut best: Option<(TimedNode, f32)> = None;
+    let mut expanded_nodes = 0;
+    let mut hit_limit = false;
+    let mut end = None;
+
+    while let Some(OpenNode { f, timed }) = open.pop() {
+        let g = scores[&timed].0;
+        if f > g + goal.heuristic(&timed.node) {
+            // we already found a cheaper way here
+            continue;
+        }
+
+        if goal.success(&timed.node) && !reserved.is_reserved(&timed.node.pos, timed.tick..u64::MAX)
+        {
+            end = Some(timed);
+            break;
+        }
+
+        expanded_nodes += 1;
+        if opts.limits.max_nodes.map_or(false, |max| expanded_nodes > max)
+            // checking the time is relatively slow so don't do it every node
+            || (expanded_nodes % 64 == 0
+                && opts
+                    .limits
+                    .timeout
+                    .map_or(false, |timeout| start_time.elapsed() > timeout))
+        {
+            hit_limit = true;
+            break;
+        }
+
+        let h = goal.heuristic(&timed.node);
+        if best.map_or(true, |(_, best_h)| h < best_h) {
+            best = Some((timed, h));
+        }
+
+        let tick = timed.tick;
+        let wait = Edge {
+            target: timed.node,
+            cost: WAIT_COST,
+        };
+        for edge in edges(&timed.node, world, opts).into_iter().chain([wait]) {
+            if !edge.cost.is_finite()
+                || reserved.is_reserved(&edge.target.pos, tick..tick + TICKS_PER_NODE * 2)
+            {
+                continue;
+            }
+            let target = TimedNode {
+                node: edge.target,
+                tick: (tick + TICKS_PER_NODE).min(reserved.horizon),
+            };
+            if target == timed {
+                // waiting after the horizon doesn't help
+                continue;
+            }
+            let target_g = g + edge.cost;
+            if scores
+                .get(&target)
+                .map_or(true, |(score, _)| target_g < *score)
+            {
+                scores.insert(target, (target_g, Some(timed)));
+                open.push(OpenNode {
+                    f: target_g + goal.heuristic(&target.node),
+                    timed: target,
+                });
+            }
+        }
+    }
+
+    let (end, partial) = match end {
+        Some(end) if end == start => return None,
+        Some(end) => (end, false),
+        // we hit a limit, so just get as close to the goal as we can
+        None if hit_limit => match best {
+            Some((best, _)) if best != start => (best, true),
+            _ => return None,
+        },
+        None => return None,
+    };
+
+    let mut path = vec![end.node];
+    let mut current = scores[&end].1;
+    while let Some(timed) = current {
+        if timed == start {
+            break;
+        }
+        path.push(timed.node);
+        current = scores[&timed].1;
+    }
+    path.reverse();
+    Some((path, partial))
+}
+
+fn tick_reservations(mut reservations: ResMut<PathReservations>) {
+    reservations.tick();
+}
+
+/// Find up to `count` different positions near `goal` that a bot can stand
+/// at, closest first. If there aren't enough, some positions are repeated.
+pub fn goal_cells(world: &CachedWorld, goal: BlockPos, count: usize) -> Vec<BlockPos> {
+    let mut candidates = Vec::new();
+    for x in -GOAL_SPREAD..=GOAL_SPREAD {
+        for z in -GOAL_SPREAD..=GOAL_SPREAD {
+            for y in -2..=2 {
+                candidates.push(BlockPos::new(x, y, z));
+            }
+        }
+    }
+    // prefer staying on the same level as the goal
+    candidates.sort_by_key(|offset| (offset.x * offset.x + offset.z * offset.z, offset.y.abs()));
+
+    let mut cells = candidates
+        .into_iter()
+        .map(|offset| goal + offset)
+        .filter(|pos| moves::is_standable(pos, world))
+        .take(count)
+        .collect::<Vec<_>>();
+    if cells.is_empty() {
+        cells.push(goal);
+    }
+    let found = cells.len();
+    for i in 0..count.saturating_sub(found) {
+        cells.push(cells[i % found]);
+    }
+    cells
+}
+
+struct CoordinatedSearch {
+    entity: Entity,
+    start: Node,
+    opts: SearchOpts,
+}
+
+#[derive(Component)]
+pub struct ComputeCoordinatedPaths(Task<Vec<PathFoundEvent>>);
+
+fn goto_all_listener(
+    mut commands: Commands,
+    mut events: EventReader<GotoAllEvent>,
+    query: Query<(&Position, &WorldName, SearchOptsData)>,
+    mobs: Query<MobsData, MobsFilter>,
+    mut reservations: ResMut<PathReservations>,
+    world_container: Res<InstanceContainer>,
+) {
+    let thread_pool = AsyncComputeTaskPool::get();
+
+    for event in events.iter() {
+        let mut bots = event
+            .entities
+            .iter()
+            .filter_map(|&entity| query.get(entity).ok().map(|item| (entity, item)))
+            .collect::<Vec<_>>();
+        let Some((_, (_, world_name, _))) = bots.first() else {
+            continue;
+        };
+        let world_name = (*world_name).clone();
+        bots.retain(|(entity, (_, bot_world_name, _))| {
+            let same_world = **bot_world_name == world_name;
+            if !same_world {
+                warn!("Not coordinating {entity:?} since it's in a different world");
+            }
+            same_world
+        });
+        let Some(world_lock) = world_container.get(&world_name) else {
+            continue;
+        };
+
+        // the bots that are closest to the goal go first so the ones behind them
+        // don't get in their way
+        let goal = event.goal;
+        let distance_to_goal = |position: &Position| (**position - goal.center()).length_sqr();
+        bots.sort_by(|(_, (a, _, _)), (_, (b, _, _))| {
+            distance_to_goal(*a).total_cmp(&distance_to_goal(*b))
+        });
+
+        for (entity, _) in &bots {
+            commands.entity(*entity).insert(CoordinatedPathfinding);
+            reservations.release(*entity);
+        }
+
+        let searches = bots
+            .into_iter()
+            .map(
+                |(entity, (position, world_name, opts_data))| CoordinatedSearch {
+                    entity,
+                    start: Node {
+                        pos: BlockPos::from(position),
+                        vertical_vel: VerticalVel::None,
+                    },
+                    opts: search_opts(entity, world_name, opts_data, &mobs, Some(&*reservations)),
+                },
+            )
+            .collect::<Vec<_>>();
+
+        let task = thread_pool.spawn(async move {
+            let world = CachedWorld::new(world_lock);
+            let mut cells = goal_cells(&world, goal, searches.len());
+            // when the bots we already planned for are going through each node
+            let mut reserved = ReservationTable::default();
+            let mut found_events = Vec::new();
+
+            for mut search in searches {
+                // take the free cell that's closest to this bot
+                let Some(index) = (0..cells.len()).min_by_key(|&i| {
+                    let offset = cells[i] - search.start.pos;
+                    offset.x * offset.x + offset.y * offset.y + offset.z * offset.z
+                }) else {
+                    break;
+                };
+                let cell = cells.swap_remove(index);
+                if cell == search.start.pos {
+                    // it's already there, so the other bots have to go around it
+                    reserved.reserve(cell, 0..u64::MAX);
+                    continue;
+                }
+                search.opts.reserved_nodes.extend(&reserved);
+
+                let goal: Arc<dyn Goal + Send + Sync> = Arc::new(BlockPosGoal::from(cell));
+                let Some(found) = find_path(search.start, goal.as_ref(), &world, &search.opts)
+                else {
+                    error!("no path found for {:?} to {cell:?}", search.entity);
+                    continue;
+                };
+                debug!("coordinated path for {:?}: {:?}", search.entity, found.path);
+                reserved.reserve_path(found.path.iter().map(|n| n.pos));
+                found_events.push(PathFoundEvent {
+                    entity: search.entity,
+                    start: search.start,
+                    goal,
+                    path: found.path.into_iter().collect(),
+                    frontier: found.frontier,
+                });
+            }
+            found_events
+        });
+
+        commands.spawn(ComputeCoordinatedPaths(task));
+    }
+}
+
+fn handle_coordinated_tasks(
+    mut commands: Commands,
+    mut tasks: Query<(Entity, &mut ComputeCoordinatedPaths)>,
+    mut path_found_events: EventWriter<PathFoundEvent>,
+) {
+    for (entity, mut task) in &mut tasks {
+        if let Some(found_events) = future::block_on(future::poll_once(&mut task.0)) {
+            path_found_events.send_batch(found_events);
+            commands.entity(entity).remove::<ComputeCoordinatedPaths>();
+        }
+    }
+}
+
+fn reserve_found_paths(
+    mut events: EventReader<PathFoundEvent>,
+    coordinated: Query<(), With<CoordinatedPathfinding>>,
+    mut reservations: ResMut<PathReservations>,
+) {
+    for event in events.iter() {
+        if coordinated.contains(event.entity) {
+            reservations.reserve_path(event.entity, event.path.iter().map(|n| n.pos));
+        }
+    }
+}
+
+/// Stop reserving the nodes of bots that got to their goal, so bots that have
+/// to pass by them later don't wait forever.
+fn release_finished_paths(
+    mut events: EventReader<GotoProgressEvent>,
+    mut reservations: ResMut<PathReservations>,
+) {
+    for event in events.iter() {
+        if event.finished {
+            reservations.release(event.entity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azalea_core::ChunkPos;
+    use azalea_world::{Chunk, ChunkStorage, PartialInstance};
+    use parking_lot::RwLock;
+
+    #[test]
+    fn test_should_wait() {
+        let mut reservations = PathReservations::default();
+        let first = Entity::from_raw(0);
+        let second = Entity::from_raw(1);
+        let pos = BlockPos::new(0, 70, 0);
+        reservations.reserve_path(first, [pos, BlockPos::new(1, 70, 0)]);
+        reservations.reserve_path(second, [BlockPos::new(0, 70, 1), pos]);
+
+        // the first bot reserved it first, so the second bot waits for it
+        assert!(reservations.should_wait(&pos, second));
+        assert!(!reservations.should_wait(&pos, first));
+
+        // and once the first bot's reservation is over, we can go
+        for _ in 0..TICKS_PER_NODE * 2 {
+            reservations.tick();
+        }
+        assert!(!reservations.should_wait(&pos, second));
+    }
+
+    #[test]
+    fn test_goal_released_on_arrival() {
+        let mut reservations = PathReservations::default();
+        let first = Entity::from_raw(0);
+        let second = Entity::from_raw(1);
+        let goal = BlockPos::new(1, 70, 0);
+        reservations.reserve_path(first, [BlockPos::new(0, 70, 0), goal]);
+        reservations.reserve_path(second, [BlockPos::new(2, 70, 0), goal]);
+
+        // the first bot's goal stays reserved while it's on its way...
+        for _ in 0..TICKS_PER_NODE * 10 {
+            reservations.tick();
+        }
+        assert!(reservations.should_wait(&goal, second));
+        let table = reservations.reserved_by_others(second);
+        assert!(table.is_reserved(&goal, 0..1));
+        assert!(!table.is_reserved(&BlockPos::new(0, 70, 0), 0..u64::MAX));
+
+        // ...but not after it gets there
+        reservations.release(first);
+        assert!(!reservations.should_wait(&goal, second));
+    }
+
+    #[test]
+    fn test_cooperative_path_waits() {
+        let mut partial_world = PartialInstance::default();
+        let mut chunk_storage = ChunkStorage::default();
+        partial_world.chunks.set(
+            &ChunkPos { x: 0, z: 0 },
+            Some(Chunk::default()),
+            &mut chunk_storage,
+        );
+        // a tunnel that's one block wide
+        for x in 0..8 {
+            partial_world.chunks.set_block_state(
+                &BlockPos::new(x, 69, 0),
+                azalea_registry::Block::Stone.into(),
+                &mut chunk_storage,
+            );
+        }
+        let world = CachedWorld::new(Arc::new(RwLock::new(chunk_storage.into())));
+
+        let blocked = BlockPos::new(3, 70, 0);
+        let mut reserved_nodes = ReservationTable::default();
+        reserved_nodes.reserve(blocked, 0..TICKS_PER_NODE * 8);
+        let opts = SearchOpts {
+            danger: Default::default(),
+            unknown_chunk_cost: f32::INFINITY,
+            limits: Default::default(),
+            reserved_nodes,
+        };
+        let start = Node {
+            pos: BlockPos::new(0, 70, 0),
+            vertical_vel: VerticalVel::None,
+        };
+        let goal = BlockPosGoal::from(BlockPos::new(6, 70, 0));
+
+        let (path, partial) = find_cooperative_path(start, &goal, &world, &opts).unwrap();
+        assert!(!partial);
+        assert_eq!(path.last().unwrap().pos, BlockPos::new(6, 70, 0));
+        // there's no way around, so we have to wait until the node is free
+        let index = path.iter().position(|n| n.pos == blocked).unwrap();
+        assert!(index as u64 * TICKS_PER_NODE >= TICKS_PER_NODE * 8);
+
+        // and if the goal is taken forever, there's no cooperative path
+        let mut opts = opts;
+        opts.reserved_nodes = ReservationTable::default();
+        opts.reserved_nodes
+            .reserve(BlockPos::new(6, 70, 0), 0..u64::MAX);
+        assert!(find_cooperative_path(start, &goal, &world, &opts).is_none());
+    }
+
+    #[test]
+    fn test_goal_cells() {
+        let mut partial_world = PartialInstance::default();
+        let mut chunk_storage = ChunkStorage::default();
+        partial_world.chunks.set(
+            &ChunkPos { x: 0, z: 0 },
+            Some(Chunk::default()),
+            &mut chunk_storage,
+        );
+        for x in 0..3 {
+            partial_world.chunks.set_block_state(
+                &BlockPos::new(x, 69, 0),
+                azalea_registry::Block::Stone.into(),
+                &mut chunk_storage,
+            );
+        }
+        let world = CachedWorld::new(Arc::new(RwLock::new(chunk_storage.into())));
+
+        let cells = goal_cells(&world, BlockPos::new(0, 70, 0), 4);
+        assert_eq!(
+            cells,
+            vec![
+                BlockPos::new(0, 70, 0),
+                BlockPos::new(1, 70, 0),
+                BlockPos::new(2, 70, 0),
+                // there's only three places to stand so we have to share
+                BlockPos::new(0, 70, 0),
+            ]
+        );
+    }
+}
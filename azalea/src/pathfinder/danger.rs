@@ -0,0 +1,311 @@
+//! Extra costs for dangerous places, so the pathfinder avoids them even if
+//! they're on the shortest path.
+
+use crate::ecs::component::Component;
+use azalea_block::{BlockState, BlockStates};
+use azalea_client::WorldBorder;
+use azalea_core::{BlockPos, Vec3};
+use azalea_registry::EntityKind;
+use once_cell::sync::Lazy;
+
+use super::world::CachedWorld;
+
+/// How much extra it costs for the pathfinder to go through each kind of
+/// hazard. Setting a cost to [`f32::INFINITY`] makes the pathfinder never go
+/// through it.
+///
+/// This is a component on the bot, so you can change it with
+/// `bot.ecs.lock()`.
+#[derive(Component, Clone, Debug)]
+pub struct DangerSettings {
+    /// The cost for being next to lava (including above or below it).
+    pub lava_adjacent: f32,
+    /// The cost for walking through fire or soul fire.
+    pub fire: f32,
+    /// The cost for standing on a magma block.
+    pub magma: f32,
+    /// The cost for being next to a cactus.
+    pub cactus: f32,
+    /// The cost for walking through a sweet berry bush.
+    pub sweet_berry_bush: f32,
+    /// The cost for walking on or through powder snow, which we'd sink into.
+    pub powder_snow: f32,
+    /// The cost for every point (half a heart) of fall damage we'd take.
+    pub fall_damage_per_point: f32,
+    /// Never take falls that would kill us with our current health.
+    pub avoid_deadly_falls: bool,
+    /// Hostile mobs that are closer than this many blocks to a node make it
+    /// more expensive.
+    pub hostile_mob_radius: f64,
+    /// The cost for a node that's right next to a hostile mob. This goes down
+    /// linearly until it's 0 at [`Self::hostile_mob_radius`].
+    pub hostile_mob: f32,
+    /// Nodes that are closer than this many blocks to the world border (or
+    /// outside of it) are never pathed through.
+    pub world_border_margin: f64,
+}
+
+impl Default for DangerSettings {
+    fn default() -> Self {
+        Self {
+            lava_adjacent: 20.,
+            fire: 10.,
+            magma: 5.,
+            cactus: 10.,
+            sweet_berry_bush: 3.,
+            powder_snow: 20.,
+            fall_damage_per_point: 2.,
+            avoid_deadly_falls: true,
+            hostile_mob_radius: 8.,
+            hostile_mob: 5.,
+            world_border_margin: 1.,
+        }
+    }
+}
+
+struct DangerousBlocks {
+    lava: BlockStates,
+    fire: BlockStates,
+    magma: BlockStates,
+    cactus: BlockStates,
+    sweet_berry_bush: BlockStates,
+    powder_snow: BlockStates,
+}
+
+static DANGEROUS_BLOCKS: Lazy<DangerousBlocks> = Lazy::new(|| {
+    let mut fire = BlockStates::from(azalea_registry::Block::Fire);
+    fire.set
+        .extend(BlockStates::from(azalea_registry::Block::SoulFire));
+    DangerousBlocks {
+        lava: azalea_registry::Block::Lava.into(),
+        fire,
+        magma: azalea_registry::Block::MagmaBlock.into(),
+        cactus: azalea_registry::Block::Cactus.into(),
+        sweet_berry_bush: azalea_registry::Block::SweetBerryBush.into(),
+        powder_snow: azalea_registry::Block::PowderSnow.into(),
+    }
+});
+
+/// Whether this kind of entity will attack us on sight.
+pub fn is_hostile(kind: EntityKind) -> bool {
+    matches!(
+        kind,
+        EntityKind::Blaze
+            | EntityKind::CaveSpider
+            | EntityKind::Creeper
+            | EntityKind::Drowned
+            | EntityKind::ElderGuardian
+            | EntityKind::EnderDragon
+            | EntityKind::Endermite
+            | EntityKind::Evoker
+            | EntityKind::Ghast
+            | EntityKind::Guardian
+            | EntityKind::Hoglin
+            | EntityKind::Husk
+            | EntityKind::MagmaCube
+            | EntityKind::Phantom
+            | EntityKind::PiglinBrute
+            | EntityKind::Pillager
+            | EntityKind::Ravager
+            | EntityKind::Shulker
+            | EntityKind::Silverfish
+            | EntityKind::Skeleton
+            | EntityKind::Slime
+            | EntityKind::Spider
+            | EntityKind::Stray
+            | EntityKind::Vex
+            | EntityKind::Vindicator
+            | EntityKind::Warden
+            | EntityKind::Witch
+            | EntityKind::Wither
+            | EntityKind::WitherSkeleton
+            | EntityKind::Zoglin
+            | EntityKind::Zombie
+            | EntityKind::ZombieVillager
+    )
+}
+
+/// Everything needed to calculate danger costs. This is copied out of the ECS
+/// when the path is requested so it can be used from the pathfinding task.
+#[derive(Clone, Debug)]
+pub struct DangerCtx {
+    pub settings: DangerSettings,
+    /// Our current health, used for deciding which falls are deadly.
+    pub health: f32,
+    /// The positions of every hostile mob we know about.
+    pub hostile_mobs: Vec<Vec3>,
+    pub world_border: Option<WorldBorder>,
+}
+
+impl Default for DangerCtx {
+    fn default() -> Self {
+        Self {
+            settings: DangerSettings::default(),
+            // full health, so falls aren't treated as deadly unless we know
+            // they are
+            health: 20.,
+            hostile_mobs: Vec::new(),
+            world_border: None,
+        }
+    }
+}
+
+impl DangerCtx {
+    /// The extra cost for having our feet at `pos`.
+    pub fn cost(&self, world: &CachedWorld, pos: &BlockPos) -> f32 {
+        let settings = &self.settings;
+        let blocks = &*DANGEROUS_BLOCKS;
+        let mut cost = 0.;
+
+        if let Some(world_border) = &self.world_border {
+            let center = pos.center();
+            if world_border.distance_to_edge(center.x, center.z) < settings.world_border_margin {
+                return f32::INFINITY;
+            }
+        }
+
+        let state_at = |pos: &BlockPos| world.get_block_state(pos);
+        let is = |state: Option<BlockState>, states: &BlockStates| {
+            state.map(|s| states.contains(&s)).unwrap_or(false)
+        };
+
+        let below = state_at(&pos.down(1));
+        let feet = state_at(pos);
+        let head = state_at(&pos.up(1));
+
+        if is(feet, &blocks.fire) || is(head, &blocks.fire) {
+            cost += settings.fire;
+        }
+        if is(below, &blocks.magma) {
+            cost += settings.magma;
+        }
+        if is(feet, &blocks.sweet_berry_bush) || is(head, &blocks.sweet_berry_bush) {
+            cost += settings.sweet_berry_bush;
+        }
+        if is(below, &blocks.powder_snow)
+            || is(feet, &blocks.powder_snow)
+            || is(head, &blocks.powder_snow)
+        {
+            cost += settings.powder_snow;
+        }
+
+        let mut next_to_lava = is(below, &blocks.lava)
+            || is(feet, &blocks.lava)
+            || is(head, &blocks.lava)
+            || is(state_at(&pos.up(2)), &blocks.lava);
+        let mut next_to_cactus = is(below, &blocks.cactus);
+        for offset in [
+            BlockPos::new(1, 0, 0),
+            BlockPos::new(-1, 0, 0),
+            BlockPos::new(0, 0, 1),
+            BlockPos::new(0, 0, -1),
+        ] {
+            for y in 0..2 {
+                let state = state_at(&(*pos + offset).up(y));
+                next_to_lava = next_to_lava || is(state, &blocks.lava);
+                next_to_cactus = next_to_cactus || is(state, &blocks.cactus);
+            }
+        }
+        if next_to_lava {
+            cost += settings.lava_adjacent;
+        }
+        if next_to_cactus {
+            cost += settings.cactus;
+        }
+
+        if settings.hostile_mob > 0. && settings.hostile_mob_radius > 0. {
+            let center = pos.center();
+            for mob in &self.hostile_mobs {
+                let distance = (*mob - center).length_sqr().sqrt();
+                if distance < settings.hostile_mob_radius {
+                    cost += settings.hostile_mob
+                        * (1. - (distance / settings.hostile_mob_radius) as f32);
+                }
+            }
+        }
+
+        cost
+    }
+
+    /// The extra cost for falling `distance` blocks and landing on a full
+    /// block.
+    pub fn fall_cost(&self, distance: i32) -> f32 {
+        // you take one point of damage for every block after the third
+        let damage = (distance - 3).max(0) as f32;
+        if damage == 0. {
+            return 0.;
+        }
+        if self.settings.avoid_deadly_falls && damage >= self.health {
+            return f32::INFINITY;
+        }
+        damage * self.settings.fall_damage_per_point
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azalea_core::ChunkPos;
+    use azalea_world::{Chunk, ChunkStorage, PartialInstance};
+    use parking_lot::RwLock;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_lava_adjacent_cost() {
+        let mut partial_world = PartialInstance::default();
+        let mut chunk_storage = ChunkStorage::default();
+        partial_world.chunks.set(
+            &ChunkPos { x: 0, z: 0 },
+            Some(Chunk::default()),
+            &mut chunk_storage,
+        );
+        partial_world.chunks.set_block_state(
+            &BlockPos::new(2, 70, 0),
+            azalea_registry::Block::Lava.into(),
+            &mut chunk_storage,
+        );
+        let world = CachedWorld::new(Arc::new(RwLock::new(chunk_storage.into())));
+
+        let ctx = DangerCtx::default();
+        assert_eq!(ctx.cost(&world, &BlockPos::new(1, 70, 0)), 20.);
+        assert_eq!(ctx.cost(&world, &BlockPos::new(0, 70, 0)), 0.);
+    }
+
+    #[test]
+    fn test_fall_cost() {
+        let ctx = DangerCtx {
+            health: 5.,
+            ..Default::default()
+        };
+        assert_eq!(ctx.fall_cost(3), 0.);
+        assert_eq!(ctx.fall_cost(5), 4.);
+        assert_eq!(ctx.fall_cost(8), f32::INFINITY);
+    }
+
+    #[test]
+    fn test_shrinking_world_border() {
+        let world = CachedWorld::new(Arc::new(RwLock::new(ChunkStorage::default().into())));
+
+        let mut world_border = WorldBorder::default();
+        world_border.lerp_size(100., 20., 60_000);
+        let ctx = DangerCtx {
+            world_border: Some(world_border.clone()),
+            ..Default::default()
+        };
+        // this is inside of the border now, but won't be once it's done
+        // shrinking
+        assert_eq!(ctx.cost(&world, &BlockPos::new(30, 70, 0)), f32::INFINITY);
+        assert_eq!(ctx.cost(&world, &BlockPos::new(5, 70, 0)), 0.);
+
+        // a border that's growing is only as big as it was before
+        world_border.lerp_size(20., 100., 60_000);
+        assert!(!world_border.contains(30., 0.));
+        world_border.lerp_size(20., 100., 0);
+        assert!(world_border.contains(30., 0.));
+
+        // servers can send lerp times that are too long to finish
+        world_border.lerp_size(100., 20., u64::MAX);
+        assert!(!world_border.contains(30., 0.));
+        assert!(world_border.lerp_target.is_none());
+    }
+}
@@ -1,8 +1,17 @@
+pub mod coordinator;
+pub mod danger;
 mod moves;
 mod mtdstarlite;
+pub mod simulation;
+pub mod world;
 
 use crate::bot::{JumpEvent, LookAtEvent};
 use crate::{SprintDirection, WalkDirection};
+use coordinator::{CoordinatedPathfinding, PathReservations, ReservationTable};
+use danger::{DangerCtx, DangerSettings};
+use moves::{ExecuteCtx, Move};
+use simulation::SimulatedPlayer;
+use world::CachedWorld;
 
 use crate::app::{App, CoreSchedule, IntoSystemAppConfig, Plugin};
 use crate::ecs::{
@@ -13,22 +22,23 @@ use crate::ecs::{
     schedule::IntoSystemConfig,
     system::{Commands, Query, Res},
 };
-use azalea_client::{StartSprintEvent, StartWalkEvent};
-use azalea_core::{BlockPos, CardinalDirection};
+use azalea_client::{StartSprintEvent, StartWalkEvent, WorldBorder};
+use azalea_core::{BlockPos, ChunkPos};
 use azalea_physics::PhysicsSet;
-use azalea_world::entity::metadata::Player;
-use azalea_world::entity::Local;
+use azalea_world::entity::metadata::{Health, Player, Sprinting};
+use azalea_world::entity::{Dead, EntityKind, Local};
 use azalea_world::{
-    entity::{Physics, Position, WorldName},
+    entity::{Attributes, Physics, Position, WorldName},
     InstanceContainer,
 };
 use bevy_tasks::{AsyncComputeTaskPool, Task};
 use futures_lite::future;
 use log::{debug, error};
 use mtdstarlite::Edge;
-pub use mtdstarlite::MTDStarLite;
+pub use mtdstarlite::{MTDStarLite, SearchLimits};
 use std::collections::VecDeque;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Clone, Default)]
 pub struct PathfinderPlugin;
@@ -36,6 +46,12 @@ impl Plugin for PathfinderPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<GotoEvent>()
             .add_event::<PathFoundEvent>()
+            .add_event::<GotoProgressEvent>()
+            .add_system(
+                replan_at_frontier
+                    .in_schedule(CoreSchedule::FixedUpdate)
+                    .before(tick_execute_path),
+            )
             .add_system(
                 // Adding `.in_schedule(CoreSchedule::FixedUpdate)` makes a system run every
                 // Minecraft tick (every 50 milliseconds).
@@ -50,10 +66,79 @@ impl Plugin for PathfinderPlugin {
     }
 }
 
+/// How many ticks we can go without reaching a node before we assume we're
+/// stuck and recalculate the path.
+pub const DEFAULT_STUCK_TIMEOUT: usize = 40;
+
+/// How much more walking through a block in an unloaded chunk costs than
+/// walking through a block we know is walkable.
+pub const DEFAULT_UNKNOWN_CHUNK_COST: f32 = 2.;
+
+/// The default [`SearchLimits::max_nodes`] for a [`Pathfinder`].
+pub const DEFAULT_MAX_SEARCH_NODES: usize = 50_000;
+/// The default [`SearchLimits::timeout`] for a [`Pathfinder`].
+pub const DEFAULT_SEARCH_TIMEOUT: Duration = Duration::from_secs(1);
+
+fn default_search_limits() -> SearchLimits {
+    SearchLimits {
+        max_nodes: Some(DEFAULT_MAX_SEARCH_NODES),
+        timeout: Some(DEFAULT_SEARCH_TIMEOUT),
+    }
+}
+
+/// When a path only goes as far as the edge of the loaded chunks, we start
+/// planning the next segment once there are this many nodes left.
+const REPLAN_NODES_LEFT: usize = 8;
+
 /// A component that makes this entity able to pathfind.
-#[derive(Component, Default)]
+#[derive(Component)]
 pub struct Pathfinder {
     pub path: VecDeque<Node>,
+    /// The goal of the current path, used for recalculating the path if we get
+    /// stuck or fall off of it.
+    pub goal: Option<Arc<dyn Goal + Send + Sync>>,
+    /// The last node that we reached, i.e. where we're coming from.
+    pub last_reached_node: Option<Node>,
+    /// How many ticks it's been since we last reached a node.
+    pub ticks_without_progress: usize,
+    /// How many ticks it's been since the current path was found.
+    pub path_ticks: u64,
+    /// How many nodes of the current path we've gone through, which is also
+    /// the index of the first node in [`Self::path`] in the path as it was
+    /// found.
+    pub nodes_reached: usize,
+    /// After this many ticks without reaching a node, the path is
+    /// recalculated.
+    pub stuck_timeout: usize,
+    /// Where the current path stops if it only goes partway to the goal,
+    /// either because the rest of the way is through unloaded chunks or
+    /// because the search hit one of its [`Self::search_limits`]. The next
+    /// segment is planned once we're close to it and its chunk is loaded.
+    pub frontier: Option<BlockPos>,
+    /// How many times more expensive it is to go through blocks in unloaded
+    /// chunks. The pathfinder assumes unloaded chunks are walkable, so this
+    /// is what lets it plan routes that are longer than our render distance.
+    /// Set it to [`f32::INFINITY`] to only path through loaded chunks.
+    pub unknown_chunk_cost: f32,
+    /// How much work a single search can do before we give up and go
+    /// towards the node that got closest to the goal.
+    pub search_limits: SearchLimits,
+}
+impl Default for Pathfinder {
+    fn default() -> Self {
+        Self {
+            path: VecDeque::new(),
+            goal: None,
+            last_reached_node: None,
+            ticks_without_progress: 0,
+            path_ticks: 0,
+            nodes_reached: 0,
+            stuck_timeout: DEFAULT_STUCK_TIMEOUT,
+            frontier: None,
+            unknown_chunk_cost: DEFAULT_UNKNOWN_CHUNK_COST,
+            search_limits: default_search_limits(),
+        }
+    }
 }
 #[allow(clippy::type_complexity)]
 fn add_default_pathfinder(
@@ -61,7 +146,9 @@ fn add_default_pathfinder(
     mut query: Query<Entity, (Without<Pathfinder>, With<Local>, With<Player>)>,
 ) {
     for entity in &mut query {
-        commands.entity(entity).insert(Pathfinder::default());
+        commands
+            .entity(entity)
+            .insert((Pathfinder::default(), DangerSettings::default()));
     }
 }
 
@@ -83,22 +170,40 @@ pub struct GotoEvent {
 }
 pub struct PathFoundEvent {
     pub entity: Entity,
+    pub start: Node,
+    pub goal: Arc<dyn Goal + Send + Sync>,
     pub path: VecDeque<Node>,
+    /// See [`FoundPath::frontier`].
+    pub frontier: Option<BlockPos>,
+}
+
+/// Sent every time a new segment of a path is found and when the goal is
+/// reached, so you can tell how far along a long trip is.
+#[derive(Debug, Clone)]
+pub struct GotoProgressEvent {
+    pub entity: Entity,
+    /// The straight-line distance from the bot to the goal.
+    pub distance: f64,
+    /// Whether the bot has reached the goal.
+    pub finished: bool,
 }
 
 #[derive(Component)]
 pub struct ComputePath(Task<Option<PathFoundEvent>>);
 
+#[allow(clippy::type_complexity)]
 fn goto_listener(
     mut commands: Commands,
     mut events: EventReader<GotoEvent>,
-    mut query: Query<(&Position, &WorldName)>,
+    mut query: Query<(&Position, &WorldName, SearchOptsData)>,
+    mobs: Query<MobsData, MobsFilter>,
+    reservations: Option<Res<PathReservations>>,
     world_container: Res<InstanceContainer>,
 ) {
     let thread_pool = AsyncComputeTaskPool::get();
 
     for event in events.iter() {
-        let (position, world_name) = query
+        let (position, world_name, opts_data) = query
             .get_mut(event.entity)
             .expect("Called goto on an entity that's not in the world");
         let start = Node {
@@ -114,64 +219,36 @@ fn goto_listener(
         let goal = event.goal.clone();
         let entity = event.entity;
 
+        let opts = search_opts(
+            entity,
+            world_name,
+            opts_data,
+            &mobs,
+            reservations.as_deref(),
+        );
+
         let task = thread_pool.spawn(async move {
             debug!("start: {start:?}, end: {end:?}");
 
-            let possible_moves: Vec<&dyn moves::Move> = vec![
-                &moves::ForwardMove(CardinalDirection::North),
-                &moves::ForwardMove(CardinalDirection::East),
-                &moves::ForwardMove(CardinalDirection::South),
-                &moves::ForwardMove(CardinalDirection::West),
-                //
-                &moves::AscendMove(CardinalDirection::North),
-                &moves::AscendMove(CardinalDirection::East),
-                &moves::AscendMove(CardinalDirection::South),
-                &moves::AscendMove(CardinalDirection::West),
-                //
-                &moves::DescendMove(CardinalDirection::North),
-                &moves::DescendMove(CardinalDirection::East),
-                &moves::DescendMove(CardinalDirection::South),
-                &moves::DescendMove(CardinalDirection::West),
-                //
-                &moves::DiagonalMove(CardinalDirection::North),
-                &moves::DiagonalMove(CardinalDirection::East),
-                &moves::DiagonalMove(CardinalDirection::South),
-                &moves::DiagonalMove(CardinalDirection::West),
-            ];
-
-            let successors = |node: &Node| {
-                let mut edges = Vec::new();
-
-                let world = world_lock.read();
-                for possible_move in &possible_moves {
-                    edges.push(Edge {
-                        target: possible_move.next_node(node),
-                        cost: possible_move.cost(&world, node),
-                    });
-                }
-                edges
-            };
-
-            let mut pf = MTDStarLite::new(
-                start,
-                end,
-                |n| goal.heuristic(n),
-                successors,
-                successors,
-                |n| goal.success(n),
-            );
-
+            let world = CachedWorld::new(world_lock);
             let start_time = std::time::Instant::now();
-            let p = pf.find_path();
+            let found = find_path(start, goal.as_ref(), &world, &opts);
             let end_time = std::time::Instant::now();
-            debug!("path: {p:?}");
+            debug!("path: {found:?}");
             debug!("time: {:?}", end_time - start_time);
 
-            // convert the Option<Vec<Node>> to a VecDeque<Node>
-            if let Some(p) = p {
-                let path = p.into_iter().collect::<VecDeque<_>>();
+            if let Some(found) = found {
+                if let Some(frontier) = found.frontier {
+                    debug!("path doesn't reach the goal yet, stopping at {frontier:?}");
+                }
                 // commands.entity(event.entity).insert(Pathfinder { path: p });
-                Some(PathFoundEvent { entity, path })
+                Some(PathFoundEvent {
+                    entity,
+                    start,
+                    goal: goal.clone(),
+                    path: found.path.into_iter().collect(),
+                    frontier: found.frontier,
+                })
             } else {
                 error!("no path found");
                 None
@@ -182,6 +259,159 @@ fn goto_listener(
     }
 }
 
+/// Options for a single path search. These are copied out of the ECS when a
+/// path is requested so the search can run on another thread.
+#[derive(Clone, Debug)]
+pub struct SearchOpts {
+    pub danger: DangerCtx,
+    /// See [`Pathfinder::unknown_chunk_cost`].
+    pub unknown_chunk_cost: f32,
+    pub limits: SearchLimits,
+    /// When other bots are planning to go through each node. If this isn't
+    /// empty, the search avoids being in those nodes at the same time as them.
+    pub reserved_nodes: ReservationTable,
+}
+
+/// The components that are needed to make [`SearchOpts`] for an entity.
+pub(crate) type SearchOptsData = (
+    Option<&'static DangerSettings>,
+    Option<&'static Health>,
+    Option<&'static WorldBorder>,
+    Option<&'static Pathfinder>,
+);
+pub(crate) type MobsData = (&'static Position, &'static EntityKind, &'static WorldName);
+pub(crate) type MobsFilter = (Without<Local>, Without<Dead>);
+
+/// Copy everything that's needed to search for a path for this entity out of
+/// the ECS.
+pub(crate) fn search_opts(
+    entity: Entity,
+    world_name: &WorldName,
+    (danger_settings, health, world_border, pathfinder): (
+        Option<&DangerSettings>,
+        Option<&Health>,
+        Option<&WorldBorder>,
+        Option<&Pathfinder>,
+    ),
+    mobs: &Query<MobsData, MobsFilter>,
+    reservations: Option<&PathReservations>,
+) -> SearchOpts {
+    let danger = DangerCtx {
+        settings: danger_settings.cloned().unwrap_or_default(),
+        health: health.map(|h| **h).unwrap_or(20.),
+        hostile_mobs: mobs
+            .iter()
+            .filter(|(_, kind, mob_world_name)| {
+                *mob_world_name == world_name && danger::is_hostile(***kind)
+            })
+            .map(|(position, _, _)| **position)
+            .collect(),
+        world_border: world_border.cloned(),
+    };
+    let (unknown_chunk_cost, limits) = match pathfinder {
+        Some(p) => (p.unknown_chunk_cost, p.search_limits.clone()),
+        None => (DEFAULT_UNKNOWN_CHUNK_COST, default_search_limits()),
+    };
+    SearchOpts {
+        danger,
+        unknown_chunk_cost,
+        limits,
+        reserved_nodes: reservations
+            .map(|r| r.reserved_by_others(entity))
+            .unwrap_or_default(),
+    }
+}
+
+/// A path returned by [`find_path`].
+#[derive(Clone, Debug)]
+pub struct FoundPath {
+    /// The nodes to walk through, not including the start.
+    pub path: Vec<Node>,
+    /// If the path doesn't go all the way to the goal, this is where the next
+    /// segment should be planned from. This is either the first node that was
+    /// cut off because it's in an unloaded chunk, or the last node of the path
+    /// if the search hit one of its limits.
+    pub frontier: Option<BlockPos>,
+}
+
+/// Search for a path from `start` towards the goal. This blocks until the
+/// search is done, so it shouldn't be called from a system.
+pub fn find_path(
+    start: Node,
+    goal: &dyn Goal,
+    world: &CachedWorld,
+    opts: &SearchOpts,
+) -> Option<FoundPath> {
+    let (mut path, partial) = if opts.reserved_nodes.is_empty() {
+        search(start, goal, world, opts)?
+    } else {
+        coordinator::find_cooperative_path(start, goal, world, opts).or_else(|| {
+            // if there's no way around the other bots, go anyways and wait for
+            // them while walking
+            debug!("no path around the reserved nodes, ignoring them");
+            search(start, goal, world, opts)
+        })?
+    };
+
+    // we can't trust anything past the first unloaded chunk, so only go up to
+    // there and plan the rest once we know what it looks like
+    let frontier = match path.iter().position(|n| !world.is_chunk_loaded(&n.pos)) {
+        Some(index) => {
+            let frontier = path[index].pos;
+            path.truncate(index);
+            Some(frontier)
+        }
+        None if partial => path.last().map(|n| n.pos),
+        None => None,
+    };
+
+    Some(FoundPath { path, frontier })
+}
+
+/// Every node that can be reached from this one, and how much it costs to get
+/// there.
+fn edges(node: &Node, world: &CachedWorld, opts: &SearchOpts) -> Vec<Edge<Node, f32>> {
+    let mut edges = Vec::with_capacity(moves::DEFAULT_MOVES.len());
+
+    for possible_move in moves::DEFAULT_MOVES.iter() {
+        let target = possible_move.next_node(node);
+        let mut cost = if world.is_chunk_loaded(&target.pos) {
+            possible_move.cost(world, &opts.danger, node)
+        } else {
+            moves::unknown_chunk_cost(possible_move.as_ref(), opts.unknown_chunk_cost)
+        };
+        if cost.is_finite() {
+            cost += opts.danger.cost(world, &target.pos);
+        }
+        edges.push(Edge { target, cost });
+    }
+    edges
+}
+
+/// Find a path with [`MTDStarLite`], ignoring reservations. Returns the path
+/// and whether it stops early because a search limit was hit.
+fn search(
+    start: Node,
+    goal: &dyn Goal,
+    world: &CachedWorld,
+    opts: &SearchOpts,
+) -> Option<(Vec<Node>, bool)> {
+    let successors = |node: &Node| edges(node, world, opts);
+
+    let mut pf = MTDStarLite::new(
+        start,
+        goal.goal_node(),
+        |n| goal.heuristic(n),
+        successors,
+        successors,
+        |n| goal.success(n),
+    );
+    pf.limits = opts.limits.clone();
+
+    let path = pf.find_path()?;
+    Some((path, pf.is_partial()))
+}
+
 // poll the tasks and send the PathFoundEvent if they're done
 fn handle_tasks(
     mut commands: Commands,
@@ -201,64 +431,308 @@ fn handle_tasks(
 }
 
 // set the path for the target entity when we get the PathFoundEvent
-fn path_found_listener(mut events: EventReader<PathFoundEvent>, mut query: Query<&mut Pathfinder>) {
+fn path_found_listener(
+    mut events: EventReader<PathFoundEvent>,
+    mut query: Query<(&mut Pathfinder, &Position)>,
+    mut progress_events: EventWriter<GotoProgressEvent>,
+) {
     for event in events.iter() {
-        let mut pathfinder = query
+        let (mut pathfinder, position) = query
             .get_mut(event.entity)
             .expect("Path found for an entity that doesn't have a pathfinder");
         pathfinder.path = event.path.clone();
+        pathfinder.goal = Some(event.goal.clone());
+        pathfinder.last_reached_node = Some(event.start);
+        pathfinder.ticks_without_progress = 0;
+        pathfinder.path_ticks = 0;
+        pathfinder.nodes_reached = 0;
+        pathfinder.frontier = event.frontier;
+
+        // we might've kept moving while the path was being calculated, so skip
+        // the nodes we already passed
+        let current = BlockPos::from(position);
+        if let Some(index) = pathfinder.path.iter().position(|n| n.pos == current) {
+            let reached = pathfinder.path.drain(..=index).last();
+            pathfinder.last_reached_node = reached;
+            pathfinder.nodes_reached = index + 1;
+        }
+
+        progress_events.send(GotoProgressEvent {
+            entity: event.entity,
+            distance: distance_to_goal(position, event.goal.as_ref()),
+            finished: false,
+        });
+    }
+}
+
+/// Start planning the next segment of a path that only went as far as the
+/// loaded chunks, once the chunk it stopped at has been loaded.
+fn replan_at_frontier(
+    mut query: Query<(Entity, &mut Pathfinder, &WorldName)>,
+    world_container: Res<InstanceContainer>,
+    mut goto_events: EventWriter<GotoEvent>,
+) {
+    for (entity, mut pathfinder, world_name) in &mut query {
+        let Some(frontier) = pathfinder.frontier else {
+            continue;
+        };
+        if pathfinder.path.len() > REPLAN_NODES_LEFT {
+            continue;
+        }
+        let Some(world_lock) = world_container.get(world_name) else {
+            continue;
+        };
+        if world_lock
+            .read()
+            .chunks
+            .get(&ChunkPos::from(&frontier))
+            .is_none()
+        {
+            continue;
+        }
+        let Some(goal) = pathfinder.goal.clone() else {
+            continue;
+        };
+        debug!("pathfinder {entity:?} is near the frontier at {frontier:?}, planning next segment");
+        // this gets set again when the next segment is found
+        pathfinder.frontier = None;
+        goto_events.send(GotoEvent { entity, goal });
     }
 }
 
+/// The straight-line distance from the position to the goal's node.
+fn distance_to_goal(position: &Position, goal: &(dyn Goal + Send + Sync)) -> f64 {
+    (**position - goal.goal_node().pos.center())
+        .length_sqr()
+        .sqrt()
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 fn tick_execute_path(
-    mut query: Query<(Entity, &mut Pathfinder, &Position, &Physics)>,
+    mut query: Query<(
+        Entity,
+        &mut Pathfinder,
+        &Position,
+        &Physics,
+        &Attributes,
+        &Sprinting,
+        &WorldName,
+        Option<&CoordinatedPathfinding>,
+    )>,
+    world_container: Res<InstanceContainer>,
+    reservations: Option<Res<PathReservations>>,
     mut look_at_events: EventWriter<LookAtEvent>,
     mut sprint_events: EventWriter<StartSprintEvent>,
     mut walk_events: EventWriter<StartWalkEvent>,
     mut jump_events: EventWriter<JumpEvent>,
+    mut goto_events: EventWriter<GotoEvent>,
+    mut progress_events: EventWriter<GotoProgressEvent>,
 ) {
-    for (entity, mut pathfinder, position, physics) in &mut query {
+    for (
+        entity,
+        mut pathfinder,
+        position,
+        physics,
+        attributes,
+        sprinting,
+        world_name,
+        coordinated,
+    ) in &mut query
+    {
+        let Some(world_lock) = world_container.get(world_name) else {
+            continue;
+        };
+        pathfinder.path_ticks += 1;
+
         loop {
-            let Some(target) = pathfinder.path.front() else {
+            let Some(target) = pathfinder.path.front().copied() else {
                 break;
             };
-            let center = target.pos.center();
-            // println!("going to {center:?} (at {pos:?})", pos = bot.entity().pos());
-            look_at_events.send(LookAtEvent {
-                entity,
-                position: center,
-            });
-            debug!(
-                "tick: pathfinder {entity:?}; going to {:?}; currently at {position:?}",
-                target.pos
-            );
-            sprint_events.send(StartSprintEvent {
-                entity,
-                direction: SprintDirection::Forward,
-            });
-            // check if we should jump
-            if target.pos.y > position.y.floor() as i32 {
-                jump_events.send(JumpEvent(entity));
-            }
 
             if target.is_reached(position, physics) {
+                // paths from a cooperative search wait by repeating a node, so stay here
+                // until the next node is reserved for us
+                if pathfinder.last_reached_node.map(|n| n.pos) == Some(target.pos)
+                    && pathfinder.path_ticks
+                        < coordinator::node_start_tick(pathfinder.nodes_reached + 1)
+                {
+                    pathfinder.ticks_without_progress = 0;
+                    walk_events.send(StartWalkEvent {
+                        entity,
+                        direction: WalkDirection::None,
+                    });
+                    break;
+                }
+
                 // println!("reached target");
                 pathfinder.path.pop_front();
+                pathfinder.last_reached_node = Some(target);
+                pathfinder.nodes_reached += 1;
+                pathfinder.ticks_without_progress = 0;
                 if pathfinder.path.is_empty() {
-                    // println!("reached goal");
                     walk_events.send(StartWalkEvent {
                         entity,
                         direction: WalkDirection::None,
                     });
+                    // if there's a frontier then this was only the end of a segment, and we
+                    // wait here until the next one is planned
+                    if pathfinder.frontier.is_none() {
+                        // println!("reached goal");
+                        if let Some(goal) = pathfinder.goal.take() {
+                            progress_events.send(GotoProgressEvent {
+                                entity,
+                                distance: distance_to_goal(position, goal.as_ref()),
+                                finished: true,
+                            });
+                        }
+                    }
                 }
                 // tick again, maybe we already reached the next node!
-            } else {
+                continue;
+            }
+
+            pathfinder.ticks_without_progress += 1;
+
+            let start = pathfinder
+                .last_reached_node
+                .map(|n| n.pos)
+                .unwrap_or_else(|| BlockPos::from(position));
+
+            if physics.on_ground && is_off_path(position, &start, &target.pos) {
+                // maybe we just skipped a few nodes, in which case we can keep going from
+                // there
+                let current = BlockPos::from(position);
+                if let Some(index) = pathfinder.path.iter().position(|n| n.pos == current) {
+                    debug!("pathfinder {entity:?} skipped ahead {index} nodes");
+                    let reached = pathfinder.path.drain(..=index).last();
+                    pathfinder.last_reached_node = reached;
+                    pathfinder.nodes_reached += index + 1;
+                    pathfinder.ticks_without_progress = 0;
+                    continue;
+                }
+                debug!("pathfinder {entity:?} fell off the path at {current:?}, recalculating");
+                recalculate_path(&mut pathfinder, entity, &mut walk_events, &mut goto_events);
                 break;
             }
+
+            if pathfinder.ticks_without_progress > pathfinder.stuck_timeout {
+                debug!(
+                    "pathfinder {entity:?} hasn't made progress in {} ticks, recalculating",
+                    pathfinder.ticks_without_progress
+                );
+                recalculate_path(&mut pathfinder, entity, &mut walk_events, &mut goto_events);
+                break;
+            }
+
+            if let (Some(reservations), Some(_)) = (&reservations, coordinated) {
+                // let the bot that reserved this node first go through before us, so we
+                // don't get stuck pushing into each other. if this goes on for too long
+                // the stuck timeout will make us find another way.
+                if reservations.should_wait(&target.pos, entity) {
+                    walk_events.send(StartWalkEvent {
+                        entity,
+                        direction: WalkDirection::None,
+                    });
+                    break;
+                }
+            }
+
+            let center = target.pos.center();
+            look_at_events.send(LookAtEvent {
+                entity,
+                position: center,
+            });
+            debug!(
+                "tick: pathfinder {entity:?}; going to {:?}; currently at {position:?}",
+                target.pos
+            );
+
+            let world = world_lock.read();
+            let player =
+                SimulatedPlayer::new(*position, physics.clone(), attributes.clone(), **sprinting);
+            let ctx = ExecuteCtx {
+                start,
+                target: target.pos,
+                world: &world,
+                player: &player,
+            };
+            let controls = match moves::move_between(&start, &target.pos) {
+                Some(movement) => movement.execute(&ctx),
+                // this can happen if we're recovering
+                None => ctx.recovery_controls(),
+            };
+
+            if !controls.forward {
+                walk_events.send(StartWalkEvent {
+                    entity,
+                    direction: WalkDirection::None,
+                });
+            } else if controls.sprinting {
+                sprint_events.send(StartSprintEvent {
+                    entity,
+                    direction: SprintDirection::Forward,
+                });
+            } else {
+                walk_events.send(StartWalkEvent {
+                    entity,
+                    direction: WalkDirection::Forward,
+                });
+            }
+            if controls.jumping {
+                jump_events.send(JumpEvent(entity));
+            }
+
+            break;
         }
     }
 }
 
+/// Whether the position is far enough from the line between `start` and
+/// `target` that we can't continue following the path.
+fn is_off_path(position: &Position, start: &BlockPos, target: &BlockPos) -> bool {
+    let current = BlockPos::from(position);
+    if current == *start || current == *target {
+        return false;
+    }
+    // we fell below both of the nodes
+    if current.y < start.y.min(target.y) {
+        return true;
+    }
+    // or we got pushed too far to the side
+    let start = start.center();
+    let target = target.center();
+    let (dx, dz) = (target.x - start.x, target.z - start.z);
+    let length_sqr = dx * dx + dz * dz;
+    let (px, pz) = (position.x - start.x, position.z - start.z);
+    let t = if length_sqr == 0. {
+        0.
+    } else {
+        ((px * dx + pz * dz) / length_sqr).clamp(0., 1.)
+    };
+    let (ox, oz) = (px - dx * t, pz - dz * t);
+    ox * ox + oz * oz > 1.5 * 1.5
+}
+
+/// Stop moving and ask for a new path to the same goal.
+fn recalculate_path(
+    pathfinder: &mut Pathfinder,
+    entity: Entity,
+    walk_events: &mut EventWriter<StartWalkEvent>,
+    goto_events: &mut EventWriter<GotoEvent>,
+) {
+    pathfinder.path.clear();
+    pathfinder.last_reached_node = None;
+    pathfinder.ticks_without_progress = 0;
+    pathfinder.frontier = None;
+    walk_events.send(StartWalkEvent {
+        entity,
+        direction: WalkDirection::None,
+    });
+    if let Some(goal) = pathfinder.goal.take() {
+        goto_events.send(GotoEvent { entity, goal });
+    }
+}
+
 /// Information about our vertical velocity
 #[derive(Eq, PartialEq, Hash, Clone, Copy, Debug)]
 pub enum VerticalVel {
@@ -330,3 +804,104 @@ impl From<BlockPos> for BlockPosGoal {
         Self { pos }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::event::Events;
+    use azalea_core::{ResourceLocation, Vec3};
+    use azalea_world::{entity::EntityBundle, Chunk, ChunkStorage, Instance, PartialInstance};
+    use parking_lot::RwLock;
+    use uuid::Uuid;
+
+    fn drain_walk_events(app: &mut App) -> Vec<WalkDirection> {
+        app.world
+            .resource_mut::<Events<StartWalkEvent>>()
+            .drain()
+            .map(|e| e.direction)
+            .collect()
+    }
+
+    #[test]
+    fn test_execute_path_waits() {
+        let mut app = App::new();
+        app.init_resource::<Events<LookAtEvent>>()
+            .init_resource::<Events<StartSprintEvent>>()
+            .init_resource::<Events<StartWalkEvent>>()
+            .init_resource::<Events<JumpEvent>>()
+            .init_resource::<Events<GotoEvent>>()
+            .init_resource::<Events<GotoProgressEvent>>()
+            .init_resource::<InstanceContainer>()
+            .add_system(tick_execute_path);
+
+        let mut partial_world = PartialInstance::default();
+        let mut chunk_storage = ChunkStorage::default();
+        partial_world.chunks.set(
+            &ChunkPos { x: 0, z: 0 },
+            Some(Chunk::default()),
+            &mut chunk_storage,
+        );
+        for x in 0..4 {
+            partial_world.chunks.set_block_state(
+                &BlockPos::new(x, 69, 0),
+                azalea_registry::Block::Stone.into(),
+                &mut chunk_storage,
+            );
+        }
+        let world_name = ResourceLocation::new("minecraft:overworld");
+        let world: Arc<RwLock<Instance>> = Arc::new(RwLock::new(chunk_storage.into()));
+        app.world
+            .resource_mut::<InstanceContainer>()
+            .worlds
+            .insert(world_name.clone(), Arc::downgrade(&world));
+
+        let start = Node {
+            pos: BlockPos::new(0, 70, 0),
+            vertical_vel: VerticalVel::None,
+        };
+        let next = Node {
+            pos: BlockPos::new(1, 70, 0),
+            vertical_vel: VerticalVel::None,
+        };
+        let mut bundle = EntityBundle::new(
+            Uuid::nil(),
+            Vec3::new(0.5, 70., 0.5),
+            azalea_registry::EntityKind::Player,
+            world_name,
+        );
+        bundle.physics.on_ground = true;
+        let entity = app
+            .world
+            .spawn((
+                bundle,
+                Sprinting(false),
+                Pathfinder {
+                    // wait at the start for a node before going to the next one
+                    path: VecDeque::from([start, next]),
+                    last_reached_node: Some(start),
+                    ..Default::default()
+                },
+            ))
+            .id();
+
+        for _ in 0..coordinator::node_start_tick(1) - 1 {
+            app.update();
+            assert!(matches!(
+                drain_walk_events(&mut app)[..],
+                [WalkDirection::None]
+            ));
+        }
+        let pathfinder = app.world.get::<Pathfinder>(entity).unwrap();
+        assert_eq!(pathfinder.path.front(), Some(&start));
+        assert_eq!(pathfinder.ticks_without_progress, 0);
+
+        // once it's our turn we stop waiting and walk to the next node
+        app.update();
+        let pathfinder = app.world.get::<Pathfinder>(entity).unwrap();
+        assert_eq!(pathfinder.path.front(), Some(&next));
+        assert_eq!(pathfinder.nodes_reached, 1);
+        assert!(drain_walk_events(&mut app)
+            .iter()
+            .all(|direction| !matches!(direction, WalkDirection::None)));
+    }
+}
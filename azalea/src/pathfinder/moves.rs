@@ -1,43 +1,166 @@
+use super::danger::DangerCtx;
+use super::simulation::{Controls, SimulatedPlayer};
+use super::world::CachedWorld;
 use super::{Node, VerticalVel};
 use azalea_core::{BlockPos, CardinalDirection};
-use azalea_physics::collision::{self, BlockWithShape};
 use azalea_world::Instance;
+use once_cell::sync::Lazy;
 
 /// whether this block is passable
-fn is_block_passable(pos: &BlockPos, world: &Instance) -> bool {
-    if let Some(block) = world.chunks.get_block_state(pos) {
-        block.shape() == &collision::empty_shape()
-    } else {
-        false
-    }
+fn is_block_passable(pos: &BlockPos, world: &CachedWorld) -> bool {
+    world.is_block_passable(pos)
 }
 
 /// whether this block has a solid hitbox (i.e. we can stand on it)
-fn is_block_solid(pos: &BlockPos, world: &Instance) -> bool {
-    if let Some(block) = world.chunks.get_block_state(pos) {
-        block.shape() == &collision::block_shape()
-    } else {
-        false
-    }
+fn is_block_solid(pos: &BlockPos, world: &CachedWorld) -> bool {
+    world.is_block_solid(pos)
 }
 
 /// Whether this block and the block above are passable
-fn is_passable(pos: &BlockPos, world: &Instance) -> bool {
+fn is_passable(pos: &BlockPos, world: &CachedWorld) -> bool {
     is_block_passable(pos, world) && is_block_passable(&pos.up(1), world)
 }
 
 /// Whether we can stand in this position. Checks if the block below is solid,
 /// and that the two blocks above that are passable.
 
-fn is_standable(pos: &BlockPos, world: &Instance) -> bool {
+pub fn is_standable(pos: &BlockPos, world: &CachedWorld) -> bool {
     is_block_solid(&pos.down(1), world) && is_passable(pos, world)
 }
 
+/// The cost for doing this move into a chunk we don't know anything about yet.
+/// Blocks in unloaded chunks are never passable or solid, so the normal
+/// [`Move::cost`] would always be infinite.
+/// We optimistically assume it's flat enough to walk through, so only moves
+/// that go at most one block up or down are allowed.
+pub fn unknown_chunk_cost(movement: &dyn Move, cost_per_block: f32) -> f32 {
+    let offset = movement.offset();
+    if offset.y.abs() > 1 {
+        return f32::INFINITY;
+    }
+    let horizontal = ((offset.x * offset.x + offset.z * offset.z) as f32).sqrt();
+    (horizontal + offset.y.abs() as f32) * WALK_ONE_BLOCK_COST * cost_per_block
+}
+
 const JUMP_COST: f32 = 0.5;
 const WALK_ONE_BLOCK_COST: f32 = 1.0;
+const FALL_ONE_BLOCK_COST: f32 = 0.5;
+
+/// The furthest we'll fall in a single [`FallMove`].
+pub const MAX_FALL_DISTANCE: i32 = 16;
+
+/// The most ticks we'll simulate ahead when deciding how to execute a move.
+const MAX_SIMULATED_TICKS: usize = 20;
+
+/// Every move the pathfinder can make.
+pub static DEFAULT_MOVES: Lazy<Vec<Box<dyn Move>>> = Lazy::new(|| {
+    let mut moves: Vec<Box<dyn Move>> = Vec::new();
+    for direction in CardinalDirection::iter() {
+        moves.push(Box::new(ForwardMove(direction)));
+        moves.push(Box::new(AscendMove(direction)));
+        moves.push(Box::new(DescendMove(direction)));
+        moves.push(Box::new(DiagonalMove(direction)));
+        for distance in 2..=MAX_FALL_DISTANCE {
+            moves.push(Box::new(FallMove {
+                direction,
+                distance,
+            }));
+        }
+    }
+    moves
+});
+
+/// Find the move that goes from `from` to `to`, if there is one.
+pub fn move_between(from: &BlockPos, to: &BlockPos) -> Option<&'static dyn Move> {
+    let offset = *to - *from;
+    DEFAULT_MOVES
+        .iter()
+        .find(|m| m.offset() == offset)
+        .map(|m| m.as_ref())
+}
+
+/// The state that a [`Move`] gets to decide what to do this tick.
+pub struct ExecuteCtx<'a> {
+    /// The position of the node we're coming from.
+    pub start: BlockPos,
+    /// The position of the node we're trying to reach.
+    pub target: BlockPos,
+    pub world: &'a Instance,
+    /// A copy of the player's current state, which can be ticked to see what
+    /// the player would do with certain controls.
+    pub player: &'a SimulatedPlayer,
+}
+
+impl ExecuteCtx<'_> {
+    /// The controls that walk straight at the target without jumping.
+    pub fn walk_controls(&self) -> Controls {
+        Controls::towards(&self.player.position, &self.target.center())
+    }
+
+    /// Simulate holding `controls` for one tick, and return whether something
+    /// stops us from moving horizontally.
+    pub fn is_blocked(&self, controls: &Controls) -> bool {
+        let mut player = self.player.clone();
+        player.tick(self.world, controls);
+        let moved = *player.position - *self.player.position;
+        moved.x.abs() < 0.001 && moved.z.abs() < 0.001
+    }
+
+    /// The controls to use when there's no move from the start to the target,
+    /// like when we got knocked off the path. This walks straight at the
+    /// target and jumps when something is in the way.
+    pub fn recovery_controls(&self) -> Controls {
+        let controls = self.walk_controls();
+        Controls {
+            jumping: self.player.physics.on_ground && self.is_blocked(&controls),
+            ..controls
+        }
+    }
+
+    /// Simulate holding `first_tick` for one tick and then `controls` until
+    /// the player leaves the ground and lands again. Returns the block
+    /// position where they landed, or `None` if they didn't land in time.
+    pub fn simulate_landing(&self, first_tick: &Controls, controls: &Controls) -> Option<BlockPos> {
+        let mut player = self.player.clone();
+        let mut left_ground = !player.physics.on_ground;
+        player.tick(self.world, first_tick);
+        for _ in 0..MAX_SIMULATED_TICKS {
+            if !player.physics.on_ground {
+                left_ground = true;
+            } else if left_ground {
+                return Some(BlockPos::from(&player.position));
+            }
+            player.tick(self.world, controls);
+        }
+        None
+    }
+
+    /// Simulate jumping right now while holding `controls`, and return
+    /// whether we'd end up standing on the target.
+    pub fn jump_reaches_target(&self, controls: &Controls) -> bool {
+        let mut player = self.player.clone();
+        player.tick(
+            self.world,
+            &Controls {
+                jumping: true,
+                ..*controls
+            },
+        );
+        for _ in 0..MAX_SIMULATED_TICKS {
+            if player.physics.on_ground {
+                return BlockPos::from(&player.position) == self.target;
+            }
+            player.tick(self.world, controls);
+        }
+        false
+    }
+}
 
 pub trait Move: Send + Sync {
-    fn cost(&self, world: &Instance, node: &Node) -> f32;
+    /// How expensive it is to do this move from the given node. This doesn't
+    /// include the cost of being in a dangerous place, since that's added by
+    /// the pathfinder separately.
+    fn cost(&self, world: &CachedWorld, danger: &DangerCtx, node: &Node) -> f32;
     /// Returns by how much the entity's position should be changed when this
     /// move is executed.
     fn offset(&self) -> BlockPos;
@@ -47,11 +170,23 @@ pub trait Move: Send + Sync {
             vertical_vel: VerticalVel::None,
         }
     }
+    /// Decide what controls should be held this tick to get to the target.
+    /// This is called every tick until the target node is reached.
+    ///
+    /// By default this sprints at the target and jumps if it's higher than
+    /// the node we came from.
+    fn execute(&self, ctx: &ExecuteCtx) -> Controls {
+        Controls {
+            sprinting: true,
+            jumping: ctx.target.y > ctx.start.y,
+            ..ctx.walk_controls()
+        }
+    }
 }
 
 pub struct ForwardMove(pub CardinalDirection);
 impl Move for ForwardMove {
-    fn cost(&self, world: &Instance, node: &Node) -> f32 {
+    fn cost(&self, world: &CachedWorld, _danger: &DangerCtx, node: &Node) -> f32 {
         if is_standable(&(node.pos + self.offset()), world)
             && node.vertical_vel == VerticalVel::None
         {
@@ -67,7 +202,7 @@ impl Move for ForwardMove {
 
 pub struct AscendMove(pub CardinalDirection);
 impl Move for AscendMove {
-    fn cost(&self, world: &Instance, node: &Node) -> f32 {
+    fn cost(&self, world: &CachedWorld, _danger: &DangerCtx, node: &Node) -> f32 {
         if node.vertical_vel == VerticalVel::None
             && is_block_passable(&node.pos.up(2), world)
             && is_standable(&(node.pos + self.offset()), world)
@@ -86,10 +221,23 @@ impl Move for AscendMove {
             vertical_vel: VerticalVel::None,
         }
     }
+    fn execute(&self, ctx: &ExecuteCtx) -> Controls {
+        let controls = ctx.walk_controls();
+        if !ctx.player.physics.on_ground {
+            return controls;
+        }
+        // only jump once the simulation says the jump will actually land on
+        // the block, otherwise we'd bonk into the side of it and fall back
+        // down
+        Controls {
+            jumping: ctx.is_blocked(&controls) || ctx.jump_reaches_target(&controls),
+            ..controls
+        }
+    }
 }
 pub struct DescendMove(pub CardinalDirection);
 impl Move for DescendMove {
-    fn cost(&self, world: &Instance, node: &Node) -> f32 {
+    fn cost(&self, world: &CachedWorld, _danger: &DangerCtx, node: &Node) -> f32 {
         // check whether 3 blocks vertically forward are passable
         if node.vertical_vel == VerticalVel::None
             && is_standable(&(node.pos + self.offset()), world)
@@ -109,10 +257,43 @@ impl Move for DescendMove {
             vertical_vel: VerticalVel::None,
         }
     }
+    fn execute(&self, ctx: &ExecuteCtx) -> Controls {
+        execute_drop(ctx)
+    }
+}
+
+/// Walk off the edge of a block so we land on the target without
+/// overshooting it.
+fn execute_drop(ctx: &ExecuteCtx) -> Controls {
+    let walk = ctx.walk_controls();
+    let sprint = Controls {
+        sprinting: true,
+        ..walk
+    };
+    if !ctx.player.physics.on_ground {
+        // we're already falling, only keep going forward if we wouldn't
+        // overshoot the target
+        return if ctx.simulate_landing(&walk, &walk) == Some(ctx.target) {
+            walk
+        } else {
+            Controls {
+                forward: false,
+                ..walk
+            }
+        };
+    }
+    // stop sprinting before the drop if it'd make us fly past the block
+    // we're trying to land on
+    if ctx.simulate_landing(&sprint, &sprint) == Some(ctx.target) {
+        sprint
+    } else {
+        walk
+    }
 }
+
 pub struct DiagonalMove(pub CardinalDirection);
 impl Move for DiagonalMove {
-    fn cost(&self, world: &Instance, node: &Node) -> f32 {
+    fn cost(&self, world: &CachedWorld, _danger: &DangerCtx, node: &Node) -> f32 {
         if node.vertical_vel != VerticalVel::None {
             return f32::INFINITY;
         }
@@ -146,12 +327,53 @@ impl Move for DiagonalMove {
     }
 }
 
+/// Walk off the edge of a block and fall more than one block down.
+pub struct FallMove {
+    pub direction: CardinalDirection,
+    pub distance: i32,
+}
+impl Move for FallMove {
+    fn cost(&self, world: &CachedWorld, danger: &DangerCtx, node: &Node) -> f32 {
+        if node.vertical_vel != VerticalVel::None {
+            return f32::INFINITY;
+        }
+        let forward = BlockPos::new(
+            node.pos.x + self.direction.x(),
+            node.pos.y,
+            node.pos.z + self.direction.z(),
+        );
+        // check where we'd land first since that rules out most distances
+        // immediately
+        if !is_standable(&(node.pos + self.offset()), world) || !is_passable(&forward, world) {
+            return f32::INFINITY;
+        }
+        // the column we fall through has to be empty
+        for y in 1..self.distance {
+            if !is_block_passable(&forward.down(y), world) {
+                return f32::INFINITY;
+            }
+        }
+        WALK_ONE_BLOCK_COST
+            + FALL_ONE_BLOCK_COST * self.distance as f32
+            + danger.fall_cost(self.distance)
+    }
+    fn offset(&self) -> BlockPos {
+        BlockPos::new(self.direction.x(), -self.distance, self.direction.z())
+    }
+    fn execute(&self, ctx: &ExecuteCtx) -> Controls {
+        execute_drop(ctx)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use azalea_block::BlockState;
-    use azalea_core::ChunkPos;
-    use azalea_world::{Chunk, ChunkStorage, PartialInstance};
+    use azalea_core::{ChunkPos, ResourceLocation, Vec3};
+    use azalea_world::{entity::EntityBundle, Chunk, ChunkStorage, PartialInstance};
+    use parking_lot::RwLock;
+    use std::sync::Arc;
+    use uuid::Uuid;
 
     #[test]
     fn test_is_passable() {
@@ -174,7 +396,7 @@ mod tests {
             &mut chunk_storage,
         );
 
-        let world = chunk_storage.into();
+        let world = CachedWorld::new(Arc::new(RwLock::new(chunk_storage.into())));
         assert_eq!(is_block_passable(&BlockPos::new(0, 0, 0), &world), false);
         assert_eq!(is_block_passable(&BlockPos::new(0, 1, 0), &world), true);
     }
@@ -199,7 +421,7 @@ mod tests {
             &mut chunk_storage,
         );
 
-        let world = chunk_storage.into();
+        let world = CachedWorld::new(Arc::new(RwLock::new(chunk_storage.into())));
         assert_eq!(is_block_solid(&BlockPos::new(0, 0, 0), &world), true);
         assert_eq!(is_block_solid(&BlockPos::new(0, 1, 0), &world), false);
     }
@@ -234,9 +456,87 @@ mod tests {
             &mut chunk_storage,
         );
 
-        let world = chunk_storage.into();
+        let world = CachedWorld::new(Arc::new(RwLock::new(chunk_storage.into())));
         assert!(is_standable(&BlockPos::new(0, 1, 0), &world));
         assert!(!is_standable(&BlockPos::new(0, 0, 0), &world));
         assert!(!is_standable(&BlockPos::new(0, 2, 0), &world));
     }
+
+    #[test]
+    fn test_unknown_chunk_cost() {
+        let mut partial_world = PartialInstance::default();
+        let mut chunk_storage = ChunkStorage::default();
+        partial_world.chunks.set(
+            &ChunkPos { x: 0, z: 0 },
+            Some(Chunk::default()),
+            &mut chunk_storage,
+        );
+        let world = CachedWorld::new(Arc::new(RwLock::new(chunk_storage.into())));
+        assert!(world.is_chunk_loaded(&BlockPos::new(15, 0, 15)));
+        assert!(!world.is_chunk_loaded(&BlockPos::new(16, 0, 0)));
+
+        let forward = ForwardMove(CardinalDirection::East);
+        assert_eq!(unknown_chunk_cost(&forward, 2.), 2.);
+        let fall = FallMove {
+            direction: CardinalDirection::East,
+            distance: 5,
+        };
+        assert_eq!(unknown_chunk_cost(&fall, 2.), f32::INFINITY);
+    }
+
+    #[test]
+    fn test_recovery_jumps_when_blocked() {
+        let mut partial_world = PartialInstance::default();
+        let mut chunk_storage = ChunkStorage::default();
+        partial_world.chunks.set(
+            &ChunkPos { x: 0, z: 0 },
+            Some(Chunk::default()),
+            &mut chunk_storage,
+        );
+        for z in 0..16 {
+            partial_world.chunks.set_block_state(
+                &BlockPos::new(0, 69, z),
+                azalea_registry::Block::Stone.into(),
+                &mut chunk_storage,
+            );
+        }
+        partial_world.chunks.set_block_state(
+            &BlockPos::new(0, 70, 4),
+            azalea_registry::Block::Stone.into(),
+            &mut chunk_storage,
+        );
+        let world: Instance = chunk_storage.into();
+
+        let bundle = EntityBundle::new(
+            Uuid::nil(),
+            Vec3::new(0.5, 70., 0.5),
+            azalea_registry::EntityKind::Player,
+            ResourceLocation::new("minecraft:overworld"),
+        );
+        let mut player =
+            SimulatedPlayer::new(bundle.position, bundle.physics, bundle.attributes, false);
+        for _ in 0..5 {
+            player.tick(&world, &Controls::default());
+        }
+
+        // there's no move straight from here to the top of the step, so this
+        // is what happens when we're off the path
+        let target = BlockPos::new(0, 71, 4);
+        let mut jumped_too_early = false;
+        for _ in 0..60 {
+            let ctx = ExecuteCtx {
+                start: BlockPos::from(&player.position),
+                target,
+                world: &world,
+                player: &player,
+            };
+            let controls = ctx.recovery_controls();
+            if controls.jumping && player.position.z < 3. {
+                jumped_too_early = true;
+            }
+            player.tick(&world, &controls);
+        }
+        assert!(!jumped_too_early);
+        assert_eq!(BlockPos::from(&player.position), target);
+    }
 }
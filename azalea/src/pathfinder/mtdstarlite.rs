@@ -9,7 +9,24 @@
 //! - Store edge costs in their own map
 
 use priority_queue::DoublePriorityQueue;
-use std::{collections::HashMap, fmt::Debug, hash::Hash, ops::Add};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    hash::Hash,
+    ops::Add,
+    time::{Duration, Instant},
+};
+
+/// Limits on how much work a single search can do. If a limit is hit, the
+/// search stops and returns a path to the node that looked closest to the
+/// goal instead.
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+    /// The most nodes that can be expanded in one search.
+    pub max_nodes: Option<usize>,
+    /// The longest one search can run for.
+    pub timeout: Option<Duration>,
+}
 
 /// Nodes are coordinates.
 pub struct MTDStarLite<
@@ -33,6 +50,7 @@ pub struct MTDStarLite<
     /// A simple implementation is to check if the given node is equal to the
     /// goal.
     pub success: SuccessFn,
+    pub limits: SearchLimits,
 
     start: N,
     goal: N,
@@ -45,6 +63,12 @@ pub struct MTDStarLite<
     node_states: HashMap<N, NodeState<N, W>>,
     updated_edge_costs: Vec<ChangedEdge<N, W>>,
 
+    /// The expanded node with the lowest heuristic, used as the end of the
+    /// path if we hit a limit before reaching the goal.
+    best_node: Option<(N, W)>,
+    hit_limit: bool,
+    partial: bool,
+
     /// This only exists so it can be referenced by `state()` when there's no
     /// state.
     default_state: NodeState<N, W>,
@@ -90,6 +114,7 @@ impl<
             successors,
             predecessors,
             success,
+            limits: SearchLimits::default(),
 
             start,
             goal,
@@ -102,6 +127,10 @@ impl<
             node_states: HashMap::new(),
             updated_edge_costs: Vec::new(),
 
+            best_node: None,
+            hit_limit: false,
+            partial: false,
+
             default_state: NodeState::default(),
         };
 
@@ -128,6 +157,10 @@ impl<
     }
 
     fn compute_cost_minimal_path(&mut self) {
+        let start_time = Instant::now();
+        let mut expanded_nodes = 0;
+        self.hit_limit = false;
+
         while {
             if let Some((_, top_key)) = self.open.peek_min() {
                 (top_key < &self.calculate_key(&self.goal)) || {
@@ -138,6 +171,19 @@ impl<
                 false
             }
         } {
+            expanded_nodes += 1;
+            if self.limits.max_nodes.map_or(false, |max| expanded_nodes > max)
+                // checking the time is relatively slow so don't do it every node
+                || (expanded_nodes % 64 == 0
+                    && self
+                        .limits
+                        .timeout
+                        .map_or(false, |timeout| start_time.elapsed() > timeout))
+            {
+                self.hit_limit = true;
+                break;
+            }
+
             let (u_node, k_old) = self.open.pop_min().unwrap();
             let k_new = self.calculate_key(&u_node);
             if k_old < k_new {
@@ -148,6 +194,10 @@ impl<
             if u.g > u.rhs {
                 u.g = u.rhs;
                 self.open.remove(&u_node);
+                let h = (self.heuristic)(&u_node);
+                if self.best_node.map_or(true, |(_, best_h)| h < best_h) {
+                    self.best_node = Some((u_node, h));
+                }
                 for edge in (self.successors)(&u_node) {
                     let s_node = edge.target;
                     let s = self.state(&s_node);
@@ -250,15 +300,25 @@ impl<
         self.old_goal = self.goal;
 
         self.compute_cost_minimal_path();
-        if self.state(&self.goal).rhs == W::max_value() {
+        let end = if self.state(&self.goal).rhs != W::max_value() {
+            self.goal
+        } else if self.hit_limit {
+            // we ran out of time before finding the goal, so just get as close to it
+            // as we can
+            match self.best_node {
+                Some((best_node, _)) if best_node != self.start => best_node,
+                _ => return None,
+            }
+        } else {
             // no path exists
             return None;
-        }
+        };
+        self.partial = end != self.goal;
 
-        let mut reverse_path = vec![self.goal];
+        let mut reverse_path = vec![end];
 
         // identify a path from sstart to sgoal using the parent pointers
-        let mut target = self.state(&self.goal).par;
+        let mut target = self.state(&end).par;
         while !(Some(self.start) == target) {
             let Some(this_target) = target else {
                 break;
@@ -277,6 +337,12 @@ impl<
         Some(path)
     }
 
+    /// Whether the last path returned by [`Self::find_path`] stops before the
+    /// goal because a [`SearchLimits`] limit was hit.
+    pub fn is_partial(&self) -> bool {
+        self.partial
+    }
+
     fn optimized_deletion(&mut self) {
         let start = self.start;
         self.state_mut(&start).par = None;
@@ -451,4 +517,35 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_mtdstarlite_node_limit() {
+        let goal = (4, 0);
+        let heuristic = |n: &(usize, usize)| -> usize { 4 - n.0 };
+        // a straight line, so each node only has one way forward
+        let successors = |n: &(usize, usize)| -> Vec<Edge<(usize, usize), usize>> {
+            let mut successors = Vec::with_capacity(2);
+            if n.0 > 0 {
+                successors.push(Edge {
+                    target: (n.0 - 1, 0),
+                    cost: 1,
+                });
+            }
+            if n.0 < 4 {
+                successors.push(Edge {
+                    target: (n.0 + 1, 0),
+                    cost: 1,
+                });
+            }
+            successors
+        };
+
+        let mut pf = MTDStarLite::new((0, 0), goal, heuristic, successors, successors, |n| {
+            n == &goal
+        });
+        pf.limits.max_nodes = Some(3);
+        let path = pf.find_path().unwrap();
+        assert!(pf.is_partial());
+        assert_eq!(path, vec![(1, 0), (2, 0)]);
+    }
 }
@@ -0,0 +1,167 @@
+//! Simulate a player's movement a few ticks ahead of time, so moves can check
+//! what their controls will actually do before committing to them.
+
+use azalea_core::Vec3;
+use azalea_physics::simulate_tick;
+use azalea_world::{
+    entity::{attributes::sprinting_modifier, Attributes, Physics, Position},
+    Instance,
+};
+
+use crate::bot::direction_looking_at;
+
+/// The inputs that are held down for a tick.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Controls {
+    /// The direction the player is looking horizontally.
+    pub y_rot: f32,
+    /// Whether the forward key is held.
+    pub forward: bool,
+    /// Whether we're sprinting. This only does anything if `forward` is also
+    /// true.
+    pub sprinting: bool,
+    /// Whether the jump key is held.
+    pub jumping: bool,
+}
+
+impl Controls {
+    /// Controls for moving towards `target` from `position`, without sprinting
+    /// or jumping.
+    pub fn towards(position: &Vec3, target: &Vec3) -> Self {
+        let (y_rot, _) = direction_looking_at(position, target);
+        Self {
+            y_rot,
+            forward: true,
+            sprinting: false,
+            jumping: false,
+        }
+    }
+}
+
+/// A copy of a player's physics state that can be ticked without affecting the
+/// real player.
+#[derive(Clone, Debug)]
+pub struct SimulatedPlayer {
+    pub position: Position,
+    pub physics: Physics,
+    pub attributes: Attributes,
+    sprinting: bool,
+}
+
+impl SimulatedPlayer {
+    /// Create a new simulated player. `sprinting` should be whether the
+    /// sprinting modifier is currently in `attributes`.
+    pub fn new(
+        position: Position,
+        physics: Physics,
+        attributes: Attributes,
+        sprinting: bool,
+    ) -> Self {
+        Self {
+            position,
+            physics,
+            attributes,
+            sprinting,
+        }
+    }
+
+    /// Do a single physics tick with the given controls.
+    pub fn tick(&mut self, world: &Instance, controls: &Controls) {
+        self.set_sprinting(controls.forward && controls.sprinting);
+
+        self.physics.y_rot = controls.y_rot;
+        self.physics.xxa = 0.;
+        self.physics.zza = if controls.forward { 1. } else { 0. };
+
+        simulate_tick(
+            world,
+            &mut self.physics,
+            &mut self.position,
+            &self.attributes,
+            self.sprinting,
+            controls.jumping,
+        );
+    }
+
+    fn set_sprinting(&mut self, sprinting: bool) {
+        if self.sprinting == sprinting {
+            return;
+        }
+        self.sprinting = sprinting;
+        let modifier = sprinting_modifier();
+        if sprinting {
+            // this can only fail if it's already there, which is fine
+            let _ = self.attributes.speed.insert(modifier);
+        } else {
+            self.attributes.speed.remove(&modifier.uuid);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azalea_core::{BlockPos, ChunkPos, ResourceLocation};
+    use azalea_world::{entity::EntityBundle, Chunk, ChunkStorage, PartialInstance};
+    use uuid::Uuid;
+
+    fn make_player(pos: Vec3) -> SimulatedPlayer {
+        let bundle = EntityBundle::new(
+            Uuid::nil(),
+            pos,
+            azalea_registry::EntityKind::Player,
+            ResourceLocation::new("minecraft:overworld"),
+        );
+        SimulatedPlayer::new(bundle.position, bundle.physics, bundle.attributes, false)
+    }
+
+    #[test]
+    fn test_simulated_walk_and_jump() {
+        let mut partial_world = PartialInstance::default();
+        let mut chunk_storage = ChunkStorage::default();
+        partial_world.chunks.set(
+            &ChunkPos { x: 0, z: 0 },
+            Some(Chunk::default()),
+            &mut chunk_storage,
+        );
+        for z in 0..16 {
+            partial_world.chunks.set_block_state(
+                &BlockPos::new(0, 69, z),
+                azalea_registry::Block::Stone.into(),
+                &mut chunk_storage,
+            );
+        }
+        // a step up at the end of the floor
+        partial_world.chunks.set_block_state(
+            &BlockPos::new(0, 70, 4),
+            azalea_registry::Block::Stone.into(),
+            &mut chunk_storage,
+        );
+        let world: Instance = chunk_storage.into();
+
+        let mut player = make_player(Vec3::new(0.5, 70., 0.5));
+        // let it land
+        for _ in 0..5 {
+            player.tick(&world, &Controls::default());
+        }
+        assert!(player.physics.on_ground);
+        assert_eq!(player.position.y, 70.);
+
+        // walk into the step without jumping, we should get stuck in front of it
+        let controls = Controls::towards(&player.position, &BlockPos::new(0, 71, 4).center());
+        for _ in 0..40 {
+            player.tick(&world, &controls);
+        }
+        assert_eq!(BlockPos::from(&player.position), BlockPos::new(0, 70, 3));
+
+        // now jump onto it
+        let controls = Controls {
+            jumping: true,
+            ..controls
+        };
+        for _ in 0..20 {
+            player.tick(&world, &controls);
+        }
+        assert_eq!(BlockPos::from(&player.position).y, 71);
+    }
+}
@@ -0,0 +1,242 @@
+//! A per-search cache of which blocks are passable and solid, so the
+//! pathfinder doesn't have to lock the world and look up block shapes for
+//! every node it checks.
+
+use azalea_block::BlockState;
+use azalea_core::{BlockPos, ChunkPos, ChunkSectionBlockPos};
+use azalea_physics::collision::{self, BlockWithShape};
+use azalea_world::{
+    palette::{Palette, PalettedContainer},
+    Instance,
+};
+use parking_lot::RwLock;
+use std::{cell::RefCell, collections::HashMap, sync::Arc};
+
+/// The number of blocks in a chunk section.
+const SECTION_SIZE: usize = 16 * 16 * 16;
+
+/// One bit for every block in a section.
+#[derive(Clone)]
+struct SectionBits([u64; SECTION_SIZE / 64]);
+
+impl SectionBits {
+    fn empty() -> Self {
+        Self([0; SECTION_SIZE / 64])
+    }
+    fn full() -> Self {
+        Self([u64::MAX; SECTION_SIZE / 64])
+    }
+    fn get(&self, index: usize) -> bool {
+        self.0[index / 64] & (1 << (index % 64)) != 0
+    }
+    fn set(&mut self, index: usize) {
+        self.0[index / 64] |= 1 << (index % 64);
+    }
+}
+
+/// A copy of a chunk section along with which of its blocks are passable and
+/// solid.
+struct CachedSection {
+    states: PalettedContainer,
+    passable: SectionBits,
+    solid: SectionBits,
+}
+
+impl CachedSection {
+    fn new(states: &PalettedContainer) -> Self {
+        let mut passable = SectionBits::empty();
+        let mut solid = SectionBits::empty();
+
+        // only look up the shape once for every block in the palette instead of
+        // for every block in the section
+        let palette_shapes: Vec<(bool, bool)> = match &states.palette {
+            Palette::SingleValue(id) => {
+                let (is_passable, is_solid) = shape_of(*id);
+                return Self {
+                    states: states.clone(),
+                    passable: if is_passable {
+                        SectionBits::full()
+                    } else {
+                        SectionBits::empty()
+                    },
+                    solid: if is_solid {
+                        SectionBits::full()
+                    } else {
+                        SectionBits::empty()
+                    },
+                };
+            }
+            Palette::Linear(ids) | Palette::Hashmap(ids) => {
+                ids.iter().map(|id| shape_of(*id)).collect()
+            }
+            Palette::Global => Vec::new(),
+        };
+
+        let mut global_shapes = HashMap::<u32, (bool, bool)>::new();
+        for index in 0..SECTION_SIZE {
+            let paletted_value = states.storage.get(index);
+            let (is_passable, is_solid) = if let Palette::Global = states.palette {
+                *global_shapes
+                    .entry(paletted_value as u32)
+                    .or_insert_with(|| shape_of(paletted_value as u32))
+            } else {
+                palette_shapes
+                    .get(paletted_value as usize)
+                    .copied()
+                    // same as what Section::get does for unknown blocks
+                    .unwrap_or((true, false))
+            };
+            if is_passable {
+                passable.set(index);
+            }
+            if is_solid {
+                solid.set(index);
+            }
+        }
+
+        Self {
+            states: states.clone(),
+            passable,
+            solid,
+        }
+    }
+}
+
+/// Whether the block state with this id is (passable, solid).
+fn shape_of(id: u32) -> (bool, bool) {
+    // if there's an unknown block assume it's air
+    let state = BlockState::try_from(id).unwrap_or(BlockState::AIR);
+    let shape = state.shape();
+    (
+        shape == &collision::empty_shape(),
+        shape == &collision::block_shape(),
+    )
+}
+
+/// A view of the world for a single path search. Chunk sections are copied
+/// the first time they're needed, so the world is only locked briefly
+/// instead of for every block lookup.
+///
+/// Blocks that change after a section was cached won't be noticed, so a new
+/// `CachedWorld` should be made for every search.
+pub struct CachedWorld {
+    world_lock: Arc<RwLock<Instance>>,
+    min_y: i32,
+    /// `None` means the section isn't loaded (or is outside of the world).
+    sections: RefCell<HashMap<(ChunkPos, i32), Option<CachedSection>>>,
+}
+
+impl CachedWorld {
+    pub fn new(world_lock: Arc<RwLock<Instance>>) -> Self {
+        let min_y = world_lock.read().chunks.min_y;
+        Self {
+            world_lock,
+            min_y,
+            sections: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Call `f` with the cached section that `pos` is in and the block's index
+    /// in it, or return `default` if the section isn't loaded.
+    fn with_section<T>(
+        &self,
+        pos: &BlockPos,
+        default: T,
+        f: impl FnOnce(&CachedSection, usize) -> T,
+    ) -> T {
+        let chunk_pos = ChunkPos::from(pos);
+        let section_index = (pos.y - self.min_y).div_euclid(16);
+        let mut sections = self.sections.borrow_mut();
+        let section = sections
+            .entry((chunk_pos, section_index))
+            .or_insert_with(|| self.load_section(&chunk_pos, section_index));
+        let Some(section) = section else {
+            return default;
+        };
+        let block = ChunkSectionBlockPos::from(pos);
+        let index =
+            section
+                .states
+                .index_from_coords(block.x as usize, block.y as usize, block.z as usize);
+        f(section, index)
+    }
+
+    fn load_section(&self, chunk_pos: &ChunkPos, section_index: i32) -> Option<CachedSection> {
+        if section_index < 0 {
+            return None;
+        }
+        let chunk = self.world_lock.read().chunks.get(chunk_pos)?;
+        let chunk = chunk.read();
+        let section = chunk.sections.get(section_index as usize)?;
+        Some(CachedSection::new(&section.states))
+    }
+
+    /// Whether the chunk this block is in is loaded.
+    pub fn is_chunk_loaded(&self, pos: &BlockPos) -> bool {
+        // blocks above or below the world are in loaded chunks if the rest of
+        // the chunk is
+        let pos = BlockPos::new(pos.x, self.min_y, pos.z);
+        self.with_section(&pos, false, |_, _| true)
+    }
+
+    pub fn get_block_state(&self, pos: &BlockPos) -> Option<BlockState> {
+        self.with_section(pos, None, |section, index| {
+            Some(
+                BlockState::try_from(section.states.get_at_index(index)).unwrap_or(BlockState::AIR),
+            )
+        })
+    }
+
+    /// Whether we can walk through this block.
+    pub fn is_block_passable(&self, pos: &BlockPos) -> bool {
+        self.with_section(pos, false, |section, index| section.passable.get(index))
+    }
+
+    /// Whether this block has a full block hitbox (i.e. we can stand on it).
+    pub fn is_block_solid(&self, pos: &BlockPos) -> bool {
+        self.with_section(pos, false, |section, index| section.solid.get(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azalea_world::{Chunk, ChunkStorage, PartialInstance};
+
+    #[test]
+    fn test_cached_shapes() {
+        let mut partial_world = PartialInstance::default();
+        let mut chunk_storage = ChunkStorage::default();
+        partial_world.chunks.set(
+            &ChunkPos { x: 0, z: 0 },
+            Some(Chunk::default()),
+            &mut chunk_storage,
+        );
+        partial_world.chunks.set_block_state(
+            &BlockPos::new(0, 0, 0),
+            azalea_registry::Block::Stone.into(),
+            &mut chunk_storage,
+        );
+        partial_world.chunks.set_block_state(
+            &BlockPos::new(1, 0, 0),
+            azalea_registry::Block::OakSlab.into(),
+            &mut chunk_storage,
+        );
+        let world = CachedWorld::new(Arc::new(RwLock::new(chunk_storage.into())));
+
+        assert!(world.is_block_solid(&BlockPos::new(0, 0, 0)));
+        assert!(!world.is_block_passable(&BlockPos::new(0, 0, 0)));
+        // slabs are neither
+        assert!(!world.is_block_solid(&BlockPos::new(1, 0, 0)));
+        assert!(!world.is_block_passable(&BlockPos::new(1, 0, 0)));
+        assert!(world.is_block_passable(&BlockPos::new(2, 0, 0)));
+        assert_eq!(
+            world.get_block_state(&BlockPos::new(0, 0, 0)),
+            Some(azalea_registry::Block::Stone.into())
+        );
+
+        assert!(world.is_chunk_loaded(&BlockPos::new(0, 1000, 0)));
+        assert!(!world.is_chunk_loaded(&BlockPos::new(16, 0, 0)));
+        assert!(!world.is_block_passable(&BlockPos::new(16, 0, 0)));
+    }
+}
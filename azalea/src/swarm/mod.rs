@@ -4,10 +4,16 @@ mod chat;
 mod events;
 pub mod prelude;
 
-use crate::{bot::DefaultBotPlugins, HandleFn};
+use crate::{
+    bot::DefaultBotPlugins,
+    pathfinder::coordinator::{GotoAllEvent, PathCoordinatorPlugin},
+    HandleFn,
+};
 use azalea_client::{chat::ChatPacket, init_ecs_app, start_ecs, Account, Client, Event, JoinError};
+use azalea_core::BlockPos;
 use azalea_protocol::{
     connect::ConnectionError,
+    proxy::Proxy,
     resolver::{self, ResolverError},
     ServerAddress,
 };
@@ -35,8 +41,12 @@ pub struct Swarm {
     bots: Arc<Mutex<HashMap<Entity, Client>>>,
 
     // bot_datas: Arc<Mutex<Vec<(Client, S)>>>,
-    resolved_address: SocketAddr,
+    /// This is `None` if every bot's proxy does its own DNS lookups, so we
+    /// don't look up the address outside of them.
+    resolved_address: Option<SocketAddr>,
     address: ServerAddress,
+    /// The proxy that's used for accounts that don't have their own.
+    proxy: Option<Proxy>,
     pub world_container: Arc<RwLock<InstanceContainer>>,
 
     bots_tx: mpsc::UnboundedSender<(Option<Event>, Client)>,
@@ -72,6 +82,8 @@ where
     /// a duration of 0, since if a duration is present the bots will wait for
     /// the previous one to be ready.
     join_delay: Option<std::time::Duration>,
+    /// The proxy that's used for accounts that don't have their own.
+    proxy: Option<Proxy>,
 }
 impl<S, SS, Fut, SwarmFut> SwarmBuilder<S, SS, Fut, SwarmFut>
 where
@@ -94,6 +106,7 @@ where
             handler: None,
             swarm_handler: None,
             join_delay: None,
+            proxy: None,
         }
         .add_plugins(DefaultSwarmPlugins)
         .add_plugins(DefaultBotPlugins)
@@ -225,6 +238,15 @@ where
         self
     }
 
+    /// Connect every bot through the given proxy, except for accounts that
+    /// have their own [`proxy`](Account::proxy). Use
+    /// [`Account::with_proxy`] to give each bot a different proxy.
+    #[must_use]
+    pub fn set_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
     /// Build this `SwarmBuilder` into an actual [`Swarm`] and join the given
     /// server.
     ///
@@ -245,8 +267,20 @@ where
             Err(_) => return Err(SwarmStartError::InvalidAddress),
         };
 
-        // resolve the address
-        let resolved_address = resolver::resolve_address(&address).await?;
+        // resolve the address, unless every bot connects through a proxy that does
+        // it for us
+        let needs_resolving = self.accounts.iter().any(|account| {
+            !account
+                .proxy
+                .as_ref()
+                .or(self.proxy.as_ref())
+                .map_or(false, |proxy| proxy.remote_dns)
+        });
+        let resolved_address = if needs_resolving {
+            Some(resolver::resolve_address(&address).await?)
+        } else {
+            None
+        };
 
         let world_container = Arc::new(RwLock::new(InstanceContainer::default()));
 
@@ -263,6 +297,7 @@ where
 
             resolved_address,
             address,
+            proxy: self.proxy,
             world_container,
 
             bots_tx,
@@ -456,11 +491,15 @@ impl Swarm {
         // An event that causes the schedule to run. This is only used internally.
         // let (run_schedule_sender, run_schedule_receiver) = mpsc::unbounded_channel();
         // let ecs_lock = start_ecs(run_schedule_receiver, run_schedule_sender.clone());
+        let mut account = account.clone();
+        if account.proxy.is_none() {
+            account.proxy = self.proxy.clone();
+        }
         let (bot, mut rx) = Client::start_client(
             self.ecs_lock.clone(),
-            account,
+            &account,
             &self.address,
-            &self.resolved_address,
+            self.resolved_address.as_ref(),
             self.run_schedule_sender.clone(),
         )
         .await?;
@@ -494,6 +533,16 @@ impl Swarm {
         Ok(bot)
     }
 
+    /// Send every bot in the swarm to `goal`. Their paths are planned together
+    /// so they don't block each other, and each bot gets its own block near the
+    /// goal to stand on.
+    pub fn goto_all(&self, goal: BlockPos) {
+        let entities = self.bots.lock().keys().copied().collect();
+        self.ecs_lock
+            .lock()
+            .send_event(GotoAllEvent { entities, goal });
+    }
+
     /// Add a new account to the swarm, retrying if it couldn't join. This will
     /// run forever until the bot joins or the task is aborted.
     ///
@@ -563,5 +612,6 @@ impl PluginGroup for DefaultSwarmPlugins {
         PluginGroupBuilder::start::<Self>()
             .add(chat::SwarmChatPlugin)
             .add(events::SwarmPlugin)
+            .add(PathCoordinatorPlugin)
     }
 }